@@ -0,0 +1,68 @@
+//! Compares rebuilding every cell of a console's vertices with rebuilding only the cells that
+//! changed. Run with `cargo bench --bench console_rebuild`.
+//!
+//! Only building the vertices on the CPU is measured. Uploading them (`buffer_data` for a full
+//! rebuild, `buffer_sub_data` per changed range) needs an OpenGL context, so it isn't covered.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use fractal::backend::font::Font;
+use fractal::backend::tile_vertices::TileVertices;
+use fractal::color;
+use fractal::console::Tile;
+
+const SIZES: [(u32, u32); 3] = [(80, 50), (200, 100), (400, 200)];
+
+fn tiles(width: u32, height: u32) -> Vec<Tile> {
+    (0..width * height)
+        .map(|i| Tile {
+            glyph: (i % 256) as u16,
+            fg: color::WHITE.into(),
+            bg: color::BLACK.into(),
+        })
+        .collect()
+}
+
+fn rebuild(c: &mut Criterion) {
+    let font = Font::new("bench.png", 128, 128, (8, 8));
+    let mut group = c.benchmark_group("rebuild");
+
+    for (width, height) in SIZES.iter().cloned() {
+        let size = format!("{}x{}", width, height);
+        let mut console = tiles(width, height);
+
+        // What every rebuild used to cost: all of the vertices, every time. A changed offset
+        // forces a full rebuild, so the offset flips between two values.
+        let mut vertices = TileVertices::new(width as usize, height as usize);
+        let mut offset = 0.0;
+        group.bench_function(BenchmarkId::new("full", &size), |b| {
+            b.iter(|| {
+                offset = 0.5 - offset;
+                black_box(vertices.update(&font, height, width, &console, offset, 0.0));
+            })
+        });
+
+        // A status line changing while the rest of the screen stays put
+        let mut vertices = TileVertices::new(width as usize, height as usize);
+        vertices.update(&font, height, width, &console, 0.0, 0.0);
+        let mut frame = 0u16;
+        group.bench_function(BenchmarkId::new("one_line", &size), |b| {
+            b.iter(|| {
+                frame = frame.wrapping_add(1);
+                for tile in console.iter_mut().take(width as usize) {
+                    tile.glyph = frame % 256;
+                }
+                black_box(vertices.update(&font, height, width, &console, 0.0, 0.0));
+            })
+        });
+
+        // cls() followed by drawing the same screen again
+        group.bench_function(BenchmarkId::new("unchanged", &size), |b| {
+            b.iter(|| black_box(vertices.update(&font, height, width, &console, 0.0, 0.0)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, rebuild);
+criterion_main!(benches);
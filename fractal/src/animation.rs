@@ -0,0 +1,336 @@
+//! Tweens: a tile's foreground or background color, a sequence of glyphs, or a console's offset,
+//! changing over time along an easing curve. Flashing a tile on damage, fading out a message or
+//! blinking a cursor becomes one call to `Fractal::animate`, instead of a frame counter.
+//!
+//! The main loop advances animations by the frame time, and applies them to the consoles after
+//! `GameState::tick` has drawn the frame, just before the consoles are rebuilt. An animation
+//! overwrites whatever the game drew in its tile, so games can keep redrawing the whole screen
+//! every frame. Animations that don't repeat are applied once at their end state, then removed.
+
+use crate::color::{self, RGBA};
+use crate::console::Console;
+use crate::fractal::DisplayConsole;
+
+/// How progress through an animation is mapped onto the change, from 0 to 1
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Easing {
+    /// Constant speed
+    #[default]
+    Linear,
+    /// Starts slow, speeds up
+    QuadIn,
+    /// Starts fast, slows down
+    QuadOut,
+    /// Slow at both ends
+    QuadInOut,
+    /// Like QuadIn, but more pronounced
+    CubicIn,
+    /// Like QuadOut, but more pronounced
+    CubicOut,
+    /// Like QuadInOut, but more pronounced
+    CubicInOut,
+    /// Slow at both ends, following a sine wave
+    SineInOut,
+}
+
+impl Easing {
+    /// Eases t, which is clamped to 0..1
+    #[allow(clippy::manual_clamp)]
+    pub fn apply(self, t: f32) -> f32 {
+        let t = f32::max(0.0, f32::min(1.0, t));
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => t * (2.0 - t),
+            Easing::QuadInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::SineInOut => -((std::f32::consts::PI * t).cos() - 1.0) / 2.0,
+        }
+    }
+}
+
+/// What happens when an animation reaches its end
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Repeat {
+    /// Stops, and is removed
+    #[default]
+    Once,
+    /// Starts again from the beginning, until cancelled
+    Loop,
+    /// Plays backwards to the beginning, then forwards again, until cancelled
+    PingPong,
+}
+
+/// What an animation changes
+#[derive(Clone, Debug, PartialEq)]
+pub enum Tween {
+    /// A tile's foreground color
+    Fg {
+        /// Tile x position
+        x: i32,
+        /// Tile y position
+        y: i32,
+        /// Color at the start
+        from: RGBA,
+        /// Color at the end
+        to: RGBA,
+    },
+    /// A tile's background color
+    Bg {
+        /// Tile x position
+        x: i32,
+        /// Tile y position
+        y: i32,
+        /// Color at the start
+        from: RGBA,
+        /// Color at the end
+        to: RGBA,
+    },
+    /// A tile's glyph, stepping through the sequence evenly over the duration
+    Glyphs {
+        /// Tile x position
+        x: i32,
+        /// Tile y position
+        y: i32,
+        /// The glyphs to show, in order
+        glyphs: Vec<u16>,
+    },
+    /// The whole console's offset, in characters
+    Offset {
+        /// Offset at the start
+        from: (f32, f32),
+        /// Offset at the end
+        to: (f32, f32),
+    },
+}
+
+/// A tween with its timing. Build one with the constructors and `with_` methods, then start it
+/// with `Fractal::animate`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Animation {
+    /// What changes
+    pub tween: Tween,
+    /// Console to animate; None means the console that is active when it is started
+    pub console: Option<usize>,
+    /// Length of one run through the animation, in milliseconds
+    pub duration_ms: f32,
+    /// How progress is mapped onto the change
+    pub easing: Easing,
+    /// What happens at the end
+    pub repeat: Repeat,
+    /// Milliseconds since it started
+    pub elapsed_ms: f32,
+}
+
+impl Animation {
+    /// An animation of any tween, linear and played once
+    pub fn new(tween: Tween, duration_ms: f32) -> Animation {
+        Animation {
+            tween,
+            console: None,
+            duration_ms,
+            easing: Easing::default(),
+            repeat: Repeat::default(),
+            elapsed_ms: 0.0,
+        }
+    }
+
+    /// Changes a tile's foreground color
+    pub fn fg<C: Into<RGBA>>(x: i32, y: i32, from: C, to: C, duration_ms: f32) -> Animation {
+        let (from, to) = (from.into(), to.into());
+        Animation::new(Tween::Fg { x, y, from, to }, duration_ms)
+    }
+
+    /// Changes a tile's background color
+    pub fn bg<C: Into<RGBA>>(x: i32, y: i32, from: C, to: C, duration_ms: f32) -> Animation {
+        let (from, to) = (from.into(), to.into());
+        Animation::new(Tween::Bg { x, y, from, to }, duration_ms)
+    }
+
+    /// Steps a tile through a sequence of glyphs
+    pub fn glyphs(x: i32, y: i32, glyphs: Vec<u16>, duration_ms: f32) -> Animation {
+        Animation::new(Tween::Glyphs { x, y, glyphs }, duration_ms)
+    }
+
+    /// Slides the whole console between two offsets, in characters
+    pub fn offset(from: (f32, f32), to: (f32, f32), duration_ms: f32) -> Animation {
+        Animation::new(Tween::Offset { from, to }, duration_ms)
+    }
+
+    /// Sets the easing curve
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Sets what happens at the end
+    pub fn with_repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Animates a particular console, rather than the active one
+    pub fn on_console(mut self, console: usize) -> Self {
+        self.console = Some(console);
+        self
+    }
+
+    /// True once an animation that doesn't repeat has reached its end
+    pub fn is_finished(&self) -> bool {
+        self.repeat == Repeat::Once && self.elapsed_ms >= self.duration_ms
+    }
+
+    /// How far through the change the animation is, 0..1, after repeating and easing
+    pub fn progress(&self) -> f32 {
+        if self.duration_ms <= 0.0 {
+            return 1.0;
+        }
+        let cycles = self.elapsed_ms / self.duration_ms;
+        let t = match self.repeat {
+            Repeat::Once => f32::min(cycles, 1.0),
+            Repeat::Loop => cycles.fract(),
+            Repeat::PingPong => {
+                let t = cycles % 2.0;
+                if t > 1.0 {
+                    2.0 - t
+                } else {
+                    t
+                }
+            }
+        };
+        self.easing.apply(t)
+    }
+
+    /// Writes the animation's current state into a console
+    fn apply_to(&self, console: &mut DisplayConsole) {
+        let t = self.progress();
+        let console = console.console.as_mut();
+        match &self.tween {
+            Tween::Fg { x, y, from, to } => {
+                if let Some((glyph, _, bg)) = tile(console, *x, *y) {
+                    console.set(*x, *y, from.lerp(*to, t), bg, glyph);
+                }
+            }
+            Tween::Bg { x, y, from, to } => {
+                if let Some((glyph, fg, _)) = tile(console, *x, *y) {
+                    console.set(*x, *y, fg, from.lerp(*to, t), glyph);
+                }
+            }
+            Tween::Glyphs { x, y, glyphs } => {
+                if glyphs.is_empty() || !in_bounds(console, *x, *y) {
+                    return;
+                }
+                let index = usize::min((t * glyphs.len() as f32) as usize, glyphs.len() - 1);
+                // Sparse consoles have nothing in unset tiles, so the glyph goes on its own
+                let (fg, bg) = tile(console, *x, *y)
+                    .map(|(_, fg, bg)| (fg, bg))
+                    .unwrap_or((RGBA::from(color::WHITE), color::TRANSPARENT));
+                console.set(*x, *y, fg, bg, glyphs[index]);
+            }
+            Tween::Offset { from, to } => {
+                console.set_offset(lerp(from.0, to.0, t), lerp(from.1, to.1, t));
+            }
+        }
+    }
+}
+
+/// Handle to a running animation
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AnimationId(u64);
+
+/// The running animations
+#[derive(Clone, Debug, Default)]
+pub struct Animator {
+    animations: Vec<(AnimationId, Animation)>,
+    next_id: u64,
+}
+
+impl Animator {
+    /// Creates an animator with nothing running
+    pub fn new() -> Animator {
+        Animator::default()
+    }
+
+    /// Starts an animation. Its console has to be set, or it is never applied;
+    /// `Fractal::animate` fills in the active one.
+    pub fn start(&mut self, animation: Animation) -> AnimationId {
+        let id = AnimationId(self.next_id);
+        self.next_id += 1;
+        self.animations.push((id, animation));
+        id
+    }
+
+    /// Stops an animation, leaving its tile or console as it was last drawn
+    pub fn cancel(&mut self, id: AnimationId) {
+        self.animations.retain(|(a, _)| *a != id);
+    }
+
+    /// Stops every animation
+    pub fn clear(&mut self) {
+        self.animations.clear();
+    }
+
+    /// True while the animation hasn't finished or been cancelled
+    pub fn is_running(&self, id: AnimationId) -> bool {
+        self.animations.iter().any(|(a, _)| *a == id)
+    }
+
+    /// Looks up a running animation, to change it in place
+    pub fn get_mut(&mut self, id: AnimationId) -> Option<&mut Animation> {
+        self.animations
+            .iter_mut()
+            .find(|(a, _)| *a == id)
+            .map(|(_, animation)| animation)
+    }
+
+    /// Moves every animation forward. Animations that had already been applied at their end
+    /// state are removed first.
+    pub fn advance(&mut self, elapsed_ms: f32) {
+        self.animations.retain(|(_, a)| !a.is_finished());
+        for (_, animation) in self.animations.iter_mut() {
+            animation.elapsed_ms += elapsed_ms;
+        }
+    }
+
+    /// Writes every animation's current state into its console, in the order they were
+    /// started. Animations of consoles that don't exist are skipped.
+    pub fn apply(&self, consoles: &mut [DisplayConsole]) {
+        for (_, animation) in &self.animations {
+            if let Some(console) = animation.console.and_then(|c| consoles.get_mut(c)) {
+                animation.apply_to(console);
+            }
+        }
+    }
+}
+
+/// True if x, y is a tile of the console
+pub(crate) fn in_bounds(console: &dyn Console, x: i32, y: i32) -> bool {
+    let (width, height) = console.get_char_size();
+    x >= 0 && y >= 0 && (x as u32) < width && (y as u32) < height
+}
+
+/// A tile's glyph and colors, if it is on the console and has been set
+pub(crate) fn tile(console: &dyn Console, x: i32, y: i32) -> Option<(u16, RGBA, RGBA)> {
+    if !in_bounds(console, x, y) {
+        return None;
+    }
+    console.get(x, y).map(|(glyph, fg, bg)| (*glyph, *fg, *bg))
+}
+
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
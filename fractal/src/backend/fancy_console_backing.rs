@@ -0,0 +1,193 @@
+//! OpenGL vertex buffers for [FancyConsole]
+
+#![allow(unsafe_code)]
+
+use crate::backend::font::Font;
+use crate::backend::shader::Shader;
+use crate::backend::tile_vertices::{vertex_data, FLOATS_PER_VERTEX};
+use crate::fancy_console::FancyTile;
+use glow::HasContext;
+use std::mem::size_of;
+
+/// The OpenGL buffers backing a [FancyConsole]. Each glyph gets its own, possibly rotated, quad.
+pub struct FancyConsoleBackend {
+    vertex_buffer: Vec<f32>,
+    index_buffer: Vec<i32>,
+    vbo: u32,
+    vao: u32,
+    ebo: u32,
+}
+
+impl FancyConsoleBackend {
+    /// Constructor
+    pub fn new(gl: &glow::Context) -> FancyConsoleBackend {
+        let (vbo, vao, ebo) = FancyConsoleBackend::init_gl_for_console(gl);
+        FancyConsoleBackend {
+            vertex_buffer: Vec::new(),
+            index_buffer: Vec::new(),
+            vbo,
+            vao,
+            ebo,
+        }
+    }
+
+    fn init_gl_for_console(gl: &glow::Context) -> (u32, u32, u32) {
+        let (vbo, vao, ebo);
+
+        unsafe {
+            // Generate buffers and arrays, as well as attributes.
+            vao = gl.create_vertex_array().unwrap();
+            vbo = gl.create_buffer().unwrap();
+            ebo = gl.create_buffer().unwrap();
+
+            gl.bind_vertex_array(Some(vao));
+
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
+
+            let stride = (FLOATS_PER_VERTEX * size_of::<f32>()) as i32;
+            // position attribute
+            gl.vertex_attrib_pointer_f32(0, 3, glow::FLOAT, false, stride, 0);
+            gl.enable_vertex_attrib_array(0);
+            // color attribute
+            gl.vertex_attrib_pointer_f32(
+                1,
+                4,
+                glow::FLOAT,
+                false,
+                stride,
+                (3 * size_of::<f32>()) as i32,
+            );
+            gl.enable_vertex_attrib_array(1);
+            // bgcolor attribute
+            gl.vertex_attrib_pointer_f32(
+                2,
+                4,
+                glow::FLOAT,
+                false,
+                stride,
+                (7 * size_of::<f32>()) as i32,
+            );
+            gl.enable_vertex_attrib_array(2);
+            // texture coord attribute
+            gl.vertex_attrib_pointer_f32(
+                3,
+                2,
+                glow::FLOAT,
+                false,
+                stride,
+                (11 * size_of::<f32>()) as i32,
+            );
+            gl.enable_vertex_attrib_array(3);
+        };
+
+        (vbo, vao, ebo)
+    }
+
+    /// Rebuilds the OpenGL backing buffer. The glyphs are transformed in pixels, so rotated
+    /// glyphs keep their shape on cells that aren't square.
+    #[allow(clippy::too_many_arguments)]
+    pub fn rebuild_vertices(
+        &mut self,
+        gl: &glow::Context,
+        font: &Font,
+        height: u32,
+        width: u32,
+        width_pixels: u32,
+        height_pixels: u32,
+        tiles: &[FancyTile],
+        offset_x: f32,
+        offset_y: f32,
+    ) {
+        self.vertex_buffer.clear();
+        self.index_buffer.clear();
+
+        let cell_width = width_pixels as f32 / width as f32;
+        let cell_height = height_pixels as f32 / height as f32;
+        let to_screen = |(px, py): (f32, f32)| {
+            (
+                (px / width_pixels as f32) * 2.0 - 1.0,
+                1.0 - (py / height_pixels as f32) * 2.0,
+            )
+        };
+
+        let mut index_count: i32 = 0;
+        for tile in tiles {
+            let (glyph_left, glyph_top, glyph_right, glyph_bottom) = font.glyph_uv(tile.glyph);
+
+            let corners = tile.corners_pixels(cell_width, cell_height);
+            let uvs = [
+                (glyph_right, glyph_top),
+                (glyph_right, glyph_bottom),
+                (glyph_left, glyph_bottom),
+                (glyph_left, glyph_top),
+            ];
+            for (corner, uv) in corners.iter().zip(uvs.iter()) {
+                let (x, y) = to_screen(*corner);
+                self.vertex_buffer.extend_from_slice(&vertex_data(
+                    x + offset_x,
+                    y + offset_y,
+                    tile.fg,
+                    tile.bg,
+                    uv.0,
+                    uv.1,
+                ));
+            }
+
+            self.index_buffer.extend_from_slice(&[
+                index_count,
+                1 + index_count,
+                3 + index_count,
+                1 + index_count,
+                2 + index_count,
+                3 + index_count,
+            ]);
+
+            index_count += 4;
+        }
+
+        unsafe {
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
+            gl.buffer_data_u8_slice(
+                glow::ARRAY_BUFFER,
+                self.vertex_buffer.align_to::<u8>().1,
+                glow::STATIC_DRAW,
+            );
+
+            gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(self.ebo));
+            gl.buffer_data_u8_slice(
+                glow::ELEMENT_ARRAY_BUFFER,
+                self.index_buffer.align_to::<u8>().1,
+                glow::STATIC_DRAW,
+            );
+        }
+    }
+
+    /// Draws the console to the OpenGL context
+    pub fn gl_draw(&mut self, font: &Font, shader: &Shader, gl: &glow::Context) {
+        if self.index_buffer.is_empty() {
+            return;
+        }
+
+        unsafe {
+            gl.enable(glow::BLEND);
+            gl.blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
+
+            // bind Texture
+            gl.bind_texture(glow::TEXTURE_2D, font.texture_id);
+
+            // render container
+            shader.useProgram(gl);
+            gl.bind_vertex_array(Some(self.vao));
+            gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(self.ebo));
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
+            gl.draw_elements(
+                glow::TRIANGLES,
+                self.index_buffer.len() as i32,
+                glow::UNSIGNED_INT,
+                0,
+            );
+
+            gl.disable(glow::BLEND);
+        }
+    }
+}
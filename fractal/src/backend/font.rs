@@ -1,30 +1,47 @@
 //! This module contains the Font struct and implementaions 
 
-#![allow(unsafe_code)]
-
-use crate::backend::FractalPlatform;
+use super::font_atlas;
+use crate::codepage437::{to_char, to_cp437};
 use crate::embedding;
-use glow::HasContext;
-use image::{ColorType, GenericImageView};
+use crate::error::FractalError;
+use image::{GenericImageView, RgbaImage};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Maps Unicode characters to glyph indices in a rasterised font
+#[derive(PartialEq, Clone, Debug)]
+pub struct Charset {
+    /// The character drawn at each glyph index
+    pub chars: Vec<char>,
+    /// Glyph index for each character
+    pub indices: HashMap<char, u16>,
+}
 
-#[derive(PartialEq, Clone)]
+#[derive(Clone)]
 /// FRACTAL's representation of a font or tileset file.
 pub struct Font {
     /// Name of the file
     pub bitmap_file: String,
-    /// Number of charaters in a row
+    /// Width of the font image in pixels
     pub width: u32,
-    /// Number of rows
+    /// Height of the font image in pixels
     pub height: u32,
 
-    /// OpenGL id
-    pub gl_id: Option<u32>,
+    /// Texture handle, set by the backend once the font is loaded
+    pub texture_id: Option<u32>,
 
     /// Size of one character
     pub tile_size: (u32, u32),
+
+    /// The decoded glyph image: the bitmap file's pixels, or an atlas rasterised from a
+    /// TrueType/OpenType font. Fonts made with `new` leave it empty, and their file is read
+    /// when they are registered.
+    pub atlas: Option<RgbaImage>,
+
+    /// Character mapping for rasterised fonts
+    pub charset: Option<Charset>,
 }
 
-#[allow(non_snake_case)]
 impl Font {
     /// Creates an unloaded texture with filename and size parameters provided.
     pub fn new<S: ToString>(filename: S, width: u32, height: u32, tile_size: (u32, u32)) -> Font {
@@ -32,102 +49,163 @@ impl Font {
             bitmap_file: filename.to_string(),
             width,
             height,
-            gl_id: None,
+            texture_id: None,
             tile_size,
+            atlas: None,
+            charset: None,
         }
     }
 
-    fn load_image(filename: &str) -> image::DynamicImage {
+    /// Loads an image, from the embedded resources if present or from disk otherwise
+    pub(crate) fn load_image(filename: &str) -> Result<image::DynamicImage, FractalError> {
         let resource = embedding::EMBED
             .lock()
             .unwrap()
             .get_resource(filename.to_string());
-        match resource {
-            None => image::open(std::path::Path::new(&filename.to_string()))
-                .expect("Failed to load texture"),
-            Some(res) => image::load_from_memory(res).expect("Failed to load texture from memory"),
+        let decoded = match resource {
+            Some(res) => image::load_from_memory(res),
+            None if Path::new(filename).exists() => image::open(filename),
+            None => return Err(FractalError::MissingResource(filename.to_string())),
+        };
+        decoded.map_err(|e| FractalError::ImageDecode {
+            path: filename.to_string(),
+            message: e.to_string(),
+        })
+    }
+
+    /// The image the backend should upload for this font
+    pub(crate) fn image(&self) -> Result<image::DynamicImage, FractalError> {
+        match &self.atlas {
+            Some(atlas) => Ok(image::DynamicImage::ImageRgba8(atlas.clone())),
+            None => Font::load_image(&self.bitmap_file),
         }
     }
 
-    /// Loads a font file (texture) to obtain the width and height for you
-    pub fn load<S: ToString>(filename: S, tile_size: (u32, u32)) -> Font {
-        let img = Font::load_image(&filename.to_string());
-        Font {
+    /// Number of glyphs in a row of the font image
+    pub fn columns(&self) -> u32 {
+        u32::max(1, self.width / u32::max(1, self.tile_size.0))
+    }
+
+    /// Number of rows of glyphs in the font image
+    pub fn rows(&self) -> u32 {
+        u32::max(1, self.height / u32::max(1, self.tile_size.1))
+    }
+
+    /// Texture coordinates of a glyph as (left, top, right, bottom), with the origin at the
+    /// bottom-left of the image like OpenGL expects. Glyphs past the end of the font use the
+    /// last one.
+    pub fn glyph_uv(&self, glyph: u16) -> (f32, f32, f32, f32) {
+        let columns = self.columns();
+        let rows = self.rows();
+        let glyph = u32::min(u32::from(glyph), columns * rows - 1);
+        let glyph_size_x = 1.0f32 / columns as f32;
+        let glyph_size_y = 1.0f32 / rows as f32;
+
+        let glyph_x = glyph % columns;
+        let glyph_y = rows - (glyph / columns);
+
+        (
+            glyph_x as f32 * glyph_size_x,
+            glyph_y as f32 * glyph_size_y,
+            (glyph_x + 1) as f32 * glyph_size_x,
+            (glyph_y as f32 - 0.95) * glyph_size_y,
+        )
+    }
+
+    /// Loads a font file (texture) to obtain the width and height for you. Fails if the file
+    /// is missing or isn't an image.
+    pub fn load<S: ToString>(filename: S, tile_size: (u32, u32)) -> Result<Font, FractalError> {
+        let img = Font::load_image(&filename.to_string())?;
+        Ok(Font {
             bitmap_file: filename.to_string(),
             width: img.width(),
             height: img.height(),
-            gl_id: None,
+            texture_id: None,
             tile_size,
-        }
+            atlas: Some(img.to_rgba()),
+            charset: None,
+        })
     }
 
-    /// Load a font, and allocate it as an OpenGL resource. Returns the OpenGL binding number (which is also set in the structure).
-    pub fn setup_gl_texture(&mut self, platform: &FractalPlatform) -> u32 {
-        let gl = &platform.platform.gl;
-        let texture;
-
-        unsafe {
-            texture = gl.create_texture().unwrap();
-            gl.bind_texture(glow::TEXTURE_2D, Some(texture));
-            gl.tex_parameter_i32(
-                glow::TEXTURE_2D,
-                glow::TEXTURE_WRAP_S,
-                glow::CLAMP_TO_EDGE as i32,
-            ); // set texture wrapping to gl::REPEAT (default wrapping method)
-            gl.tex_parameter_i32(
-                glow::TEXTURE_2D,
-                glow::TEXTURE_WRAP_T,
-                glow::CLAMP_TO_EDGE as i32,
-            );
-            // set texture filtering parameters
-            gl.tex_parameter_i32(
-                glow::TEXTURE_2D,
-                glow::TEXTURE_MIN_FILTER,
-                glow::NEAREST as i32,
-            );
-            gl.tex_parameter_i32(
-                glow::TEXTURE_2D,
-                glow::TEXTURE_MAG_FILTER,
-                glow::NEAREST as i32,
-            );
-
-            let img_orig = Font::load_image(&self.bitmap_file);
-            let img = img_orig.flipv();
-            let data = img.raw_pixels();
-            let format = match img.color() {
-                ColorType::RGB(_) => glow::RGB,
-                ColorType::RGBA(_) => glow::RGBA,
-                _ => {
-                    panic!(
-                        "unexpected image format {:?} for {}",
-                        img.color(),
-                        self.bitmap_file
-                    );
-                }
-            };
-            gl.tex_image_2d(
-                glow::TEXTURE_2D,
-                0,
-                format as i32,
-                img.width() as i32,
-                img.height() as i32,
-                0,
-                format,
-                glow::UNSIGNED_BYTE,
-                Some(&data),
-            );
+    /// Rasterises a TrueType/OpenType font into an atlas with cells of `tile_size` pixels.
+    /// The first 256 glyphs follow codepage 437, so text prints the same way as with a bitmap
+    /// font; `extra_chars` are appended after them and can be looked up with `glyph_index`.
+    pub fn load_ttf<S: ToString, I: IntoIterator<Item = char>>(
+        filename: S,
+        tile_size: (u32, u32),
+        extra_chars: I,
+    ) -> Result<Font, FractalError> {
+        let filename = filename.to_string();
+        let resource = embedding::EMBED
+            .lock()
+            .unwrap()
+            .get_resource(filename.clone());
+        let data = match resource {
+            Some(res) => res.to_vec(),
+            None if Path::new(&filename).exists() => std::fs::read(&filename)?,
+            None => return Err(FractalError::MissingResource(filename)),
+        };
+
+        let mut chars: Vec<char> = (0..=255u8)
+            .map(|i| if i == 0 || i == 32 { ' ' } else { to_char(i) })
+            .collect();
+        let mut indices = HashMap::new();
+        for (i, c) in chars.iter().enumerate() {
+            indices.entry(*c).or_insert(i as u16);
         }
+        for c in extra_chars {
+            if !indices.contains_key(&c) && chars.len() <= u16::MAX as usize {
+                indices.insert(c, chars.len() as u16);
+                chars.push(c);
+            }
+        }
+
+        let atlas = font_atlas::rasterize(data, tile_size, &chars)
+            .ok_or_else(|| FractalError::FontDecode(filename.clone()))?;
+        Ok(Font {
+            bitmap_file: filename,
+            width: atlas.width(),
+            height: atlas.height(),
+            texture_id: None,
+            tile_size,
+            atlas: Some(atlas),
+            charset: Some(Charset { chars, indices }),
+        })
+    }
 
-        self.gl_id = Some(texture);
+    /// Reads the font's file again, keeping its tile size, its extra characters (for rasterised
+    /// fonts) and its texture handle. The backend still has to be given the new image.
+    pub fn reload(&mut self) -> Result<(), FractalError> {
+        let reloaded = match &self.charset {
+            Some(charset) => Font::load_ttf(
+                &self.bitmap_file,
+                self.tile_size,
+                charset.chars.iter().skip(256).cloned(),
+            )?,
+            None => Font::load(&self.bitmap_file, self.tile_size)?,
+        };
+        *self = Font {
+            texture_id: self.texture_id,
+            ..reloaded
+        };
+        Ok(())
+    }
 
-        texture
+    /// Glyph index of a character. Rasterised fonts look it up in their charset; anything
+    /// else (or a character that wasn't rasterised) falls back to codepage 437.
+    pub fn glyph_index(&self, c: char) -> u16 {
+        self.charset
+            .as_ref()
+            .and_then(|charset| charset.indices.get(&c).cloned())
+            .unwrap_or_else(|| to_cp437(c))
     }
 
-    /// Sets this font file as the active texture
-    pub fn bind_texture(&self, platform: &FractalPlatform) {
-        let gl = &platform.platform.gl;
-        unsafe {
-            gl.bind_texture(glow::TEXTURE_2D, self.gl_id);
-        }
+    /// Converts a string to glyph indices in this font
+    pub fn string_to_glyphs<S: AsRef<str>>(&self, input: S) -> Vec<u16> {
+        input
+            .as_ref()
+            .chars()
+            .map(|c| self.glyph_index(c))
+            .collect()
     }
 }
@@ -0,0 +1,56 @@
+//! Rasterises TrueType/OpenType fonts into glyph atlases
+
+use image::{Rgba, RgbaImage};
+use rusttype::{point, Scale};
+
+/// Number of glyphs in a row of a generated atlas
+pub const ATLAS_COLUMNS: u32 = 16;
+
+/// Draws each character into its own cell of an atlas, 16 cells to a row, in the order given.
+/// Glyphs are drawn white on black, scaled to the cell height, centered horizontally and sat on
+/// a shared baseline so that box-drawing characters join up. Returns None if the data isn't a
+/// font rusttype can read.
+pub fn rasterize(font_data: Vec<u8>, tile_size: (u32, u32), chars: &[char]) -> Option<RgbaImage> {
+    let font = rusttype::Font::from_bytes(font_data).ok()?;
+    let (cell_width, cell_height) = tile_size;
+    let rows = (chars.len() as u32).div_ceil(ATLAS_COLUMNS);
+
+    let mut atlas = RgbaImage::from_pixel(
+        ATLAS_COLUMNS * cell_width,
+        u32::max(1, rows) * cell_height,
+        Rgba([0, 0, 0, 255]),
+    );
+
+    // Scale so that everything between the ascent and descent fits in the cell
+    let unit_metrics = font.v_metrics(Scale::uniform(cell_height as f32));
+    let unit_height = unit_metrics.ascent - unit_metrics.descent;
+    let scale = Scale::uniform(cell_height as f32 * cell_height as f32 / unit_height);
+    let ascent = font.v_metrics(scale).ascent;
+
+    for (i, c) in chars.iter().enumerate() {
+        let cell_x = (i as u32 % ATLAS_COLUMNS) * cell_width;
+        let cell_y = (i as u32 / ATLAS_COLUMNS) * cell_height;
+
+        let glyph = font.glyph(*c).scaled(scale);
+        let advance = glyph.h_metrics().advance_width;
+        let glyph = glyph.positioned(point((cell_width as f32 - advance) / 2.0, ascent));
+
+        if let Some(bounds) = glyph.pixel_bounding_box() {
+            glyph.draw(|x, y, coverage| {
+                let px = x as i32 + bounds.min.x;
+                let py = y as i32 + bounds.min.y;
+                if px < 0 || py < 0 || px >= cell_width as i32 || py >= cell_height as i32 {
+                    return;
+                }
+                let value = (coverage * 255.0) as u8;
+                atlas.put_pixel(
+                    cell_x + px as u32,
+                    cell_y + py as u32,
+                    Rgba([value, value, value, 255]),
+                );
+            });
+        }
+    }
+
+    Some(atlas)
+}
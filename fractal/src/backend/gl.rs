@@ -0,0 +1,585 @@
+//! The OpenGL backend, using glutin for the window and glow for rendering
+
+#![allow(unsafe_code)]
+
+use crate::backend::fancy_console_backing::FancyConsoleBackend;
+use crate::backend::font::Font;
+use crate::backend::framebuffer::Framebuffer;
+use crate::backend::post_process::{PostEffect, Uniform};
+use crate::backend::quadrender::setup_quad;
+use crate::backend::shader::Shader;
+use crate::backend::shader_strings;
+use crate::backend::simple_console_backing::SimpleConsoleBackend;
+use crate::backend::sparse_console_backing::SparseConsoleBackend;
+use crate::backend::sprite_console_backing::SpriteConsoleBackend;
+use crate::backend::sprite_sheet::SpriteSheet;
+use crate::backend::{Backend, BackendEvent};
+use crate::console::Tile;
+use crate::error::FractalError;
+use crate::fancy_console::FancyTile;
+use crate::sparse_console::SparseTile;
+use crate::sprite_console::RenderSprite;
+use crate::viewport::Viewport;
+use glow::HasContext;
+use glutin::dpi::PhysicalSize;
+use glutin::event::{
+    DeviceEvent, ElementState, Event, KeyboardInput, ModifiersState, MouseScrollDelta, WindowEvent,
+};
+use glutin::event_loop::{ControlFlow, EventLoop};
+use glutin::platform::desktop::EventLoopExtDesktop;
+use image::{imageops, ColorType, GenericImageView, RgbaImage};
+use std::any::Any;
+
+/// Index of the shader used to present the backing buffer unchanged
+const SHADER_BACKING: usize = 2;
+/// Index of the scanlines post-processing shader
+const SHADER_SCANLINES: usize = 3;
+/// Index of the shader that draws sprites
+const SHADER_SPRITES: usize = 4;
+
+/// Touchpads scroll in pixels; this many make up one line of a mouse wheel
+const PIXELS_PER_LINE: f32 = 16.0;
+
+/// A simple console's OpenGL buffers, along with its size in characters
+struct TileBacking {
+    buffers: SimpleConsoleBackend,
+    width: u32,
+    height: u32,
+}
+
+/// The OpenGL platform
+pub struct PlatformGL {
+    /// The glow context
+    pub gl: glow::Context,
+    /// Vertex array for a single quad covering the screen
+    pub quad_vao: u32,
+    /// The window's event loop
+    pub el: EventLoop<()>,
+    /// The window and its OpenGL context
+    pub wc: glutin::WindowedContext<glutin::PossiblyCurrent>,
+    /// Framebuffer that consoles render into when post-processing is enabled
+    pub backing_buffer: Framebuffer,
+    /// Second framebuffer, so post-processing passes can ping-pong between the two
+    pub post_buffer: Framebuffer,
+    /// The shaders the backend draws with
+    pub shaders: Vec<Shader>,
+    /// The user-defined post-processing shaders
+    pub post_shaders: Vec<Shader>,
+    /// Width of the window in pixels
+    pub width_pixels: u32,
+    /// Height of the window in pixels
+    pub height_pixels: u32,
+    /// The part of the window the consoles are drawn into
+    pub viewport: Viewport,
+    backings: Vec<TileBacking>,
+    sparse_backings: Vec<SparseConsoleBackend>,
+    fancy_backings: Vec<FancyConsoleBackend>,
+    sprite_backings: Vec<SpriteConsoleBackend>,
+    drawing_to_backing: bool,
+    /// Modifier keys held down, as last reported by the keyboard
+    modifiers: ModifiersState,
+}
+
+impl PlatformGL {
+    /// Wraps an already current OpenGL context, along with the window it belongs to.
+    pub fn new(
+        gl: glow::Context,
+        el: EventLoop<()>,
+        wc: glutin::WindowedContext<glutin::PossiblyCurrent>,
+        shaders: Vec<Shader>,
+        width_pixels: u32,
+        height_pixels: u32,
+    ) -> PlatformGL {
+        let backing_buffer = Framebuffer::build_fbo(&gl, width_pixels as i32, height_pixels as i32);
+        let post_buffer = Framebuffer::build_fbo(&gl, width_pixels as i32, height_pixels as i32);
+        let quad_vao = setup_quad(&gl);
+
+        unsafe {
+            gl.viewport(0, 0, width_pixels as i32, height_pixels as i32);
+        }
+
+        PlatformGL {
+            gl,
+            quad_vao,
+            el,
+            wc,
+            backing_buffer,
+            post_buffer,
+            shaders,
+            post_shaders: Vec::new(),
+            width_pixels,
+            height_pixels,
+            viewport: Viewport::full(width_pixels, height_pixels),
+            backings: Vec::new(),
+            sparse_backings: Vec::new(),
+            fancy_backings: Vec::new(),
+            sprite_backings: Vec::new(),
+            drawing_to_backing: false,
+            modifiers: ModifiersState::default(),
+        }
+    }
+
+    /// Allocates an image as an OpenGL texture
+    fn load_texture(&self, img_orig: image::DynamicImage) -> u32 {
+        let gl = &self.gl;
+        let texture;
+
+        unsafe {
+            texture = gl.create_texture().unwrap();
+            gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_WRAP_S,
+                glow::CLAMP_TO_EDGE as i32,
+            ); // set texture wrapping to gl::REPEAT (default wrapping method)
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_WRAP_T,
+                glow::CLAMP_TO_EDGE as i32,
+            );
+            // set texture filtering parameters
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_MIN_FILTER,
+                glow::NEAREST as i32,
+            );
+            gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_MAG_FILTER,
+                glow::NEAREST as i32,
+            );
+        }
+        self.upload_texture(texture, img_orig);
+
+        texture
+    }
+
+    /// Replaces the contents of a texture with an image
+    fn upload_texture(&self, texture: u32, img_orig: image::DynamicImage) {
+        let gl = &self.gl;
+        unsafe {
+            gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+            // Greyscale and paletted images are expanded to RGBA rather than rejected
+            let img = match img_orig.color() {
+                ColorType::RGB(_) | ColorType::RGBA(_) => img_orig.flipv(),
+                _ => image::DynamicImage::ImageRgba8(img_orig.to_rgba()).flipv(),
+            };
+            let data = img.raw_pixels();
+            let format = match img.color() {
+                ColorType::RGB(_) => glow::RGB,
+                _ => glow::RGBA,
+            };
+            gl.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                format as i32,
+                img.width() as i32,
+                img.height() as i32,
+                0,
+                format,
+                glow::UNSIGNED_BYTE,
+                Some(&data),
+            );
+        }
+    }
+
+    /// Runs the post-processing passes (if any), leaving the result in the default framebuffer.
+    /// Passes ping-pong between the backing buffer and the post buffer; the last one draws to
+    /// the screen.
+    fn post_process(
+        &mut self,
+        effects: &[PostEffect],
+        post_scanlines: bool,
+        post_screenburn: bool,
+    ) {
+        if !self.drawing_to_backing {
+            return;
+        }
+        self.drawing_to_backing = false;
+
+        // Post-processing works on the whole window, bars included
+        unsafe {
+            self.gl
+                .viewport(0, 0, self.width_pixels as i32, self.height_pixels as i32);
+        }
+
+        let mut passes: Vec<Pass> = effects
+            .iter()
+            .filter(|e| e.enabled)
+            .map(Pass::Effect)
+            .collect();
+        if post_scanlines {
+            passes.push(Pass::Scanlines);
+        }
+        if passes.is_empty() {
+            passes.push(Pass::Copy);
+        }
+
+        let mut source = self.backing_buffer.texture;
+        for (i, pass) in passes.iter().enumerate() {
+            let target = if i % 2 == 0 {
+                &self.post_buffer
+            } else {
+                &self.backing_buffer
+            };
+            if i == passes.len() - 1 {
+                // Now we return to the primary screen
+                target.default(&self.gl);
+            } else {
+                target.bind(&self.gl);
+            }
+
+            let shader = match pass {
+                Pass::Effect(effect) => &self.post_shaders[effect.handle],
+                Pass::Scanlines => &self.shaders[SHADER_SCANLINES],
+                Pass::Copy => &self.shaders[SHADER_BACKING],
+            };
+
+            unsafe {
+                shader.useProgram(&self.gl);
+                shader.setVec3(
+                    &self.gl,
+                    "screenSize",
+                    self.width_pixels as f32,
+                    self.height_pixels as f32,
+                    0.0,
+                );
+                match pass {
+                    Pass::Effect(effect) => {
+                        for (name, value) in effect.uniforms.iter() {
+                            match *value {
+                                Uniform::Float(v) => shader.setFloat(&self.gl, name, v),
+                                Uniform::Vec2(x, y) => shader.setVec2(&self.gl, name, x, y),
+                                Uniform::Vec3(x, y, z) => shader.setVec3(&self.gl, name, x, y, z),
+                                Uniform::Bool(v) => shader.setBool(&self.gl, name, v),
+                            }
+                        }
+                    }
+                    Pass::Scanlines => shader.setBool(&self.gl, "screenBurn", post_screenburn),
+                    Pass::Copy => {}
+                }
+
+                self.gl.bind_vertex_array(Some(self.quad_vao));
+                self.gl.bind_texture(glow::TEXTURE_2D, Some(source));
+                self.gl.draw_arrays(glow::TRIANGLES, 0, 6);
+            }
+
+            source = target.texture;
+        }
+    }
+}
+
+/// A single post-processing pass
+enum Pass<'a> {
+    /// A user-defined effect
+    Effect(&'a PostEffect),
+    /// The built-in scanlines shader
+    Scanlines,
+    /// Copies the backing buffer to the screen unchanged
+    Copy,
+}
+
+impl Backend for PlatformGL {
+    /// Load a font, and allocate it as an OpenGL texture.
+    fn setup_font(&mut self, font: &mut Font) -> Result<(), FractalError> {
+        font.texture_id = Some(self.load_texture(font.image()?));
+        Ok(())
+    }
+
+    fn reload_font(&mut self, font: &Font) -> Result<(), FractalError> {
+        if let Some(texture) = font.texture_id {
+            self.upload_texture(texture, font.image()?);
+        }
+        Ok(())
+    }
+
+    fn create_tile_backing(&mut self, width: u32, height: u32) -> usize {
+        self.backings.push(TileBacking {
+            buffers: SimpleConsoleBackend::new(&self.gl, width as usize, height as usize),
+            width,
+            height,
+        });
+        self.backings.len() - 1
+    }
+
+    fn rebuild_tiles(
+        &mut self,
+        backing: usize,
+        font: &Font,
+        width: u32,
+        height: u32,
+        tiles: &[Tile],
+        offset_x: f32,
+        offset_y: f32,
+    ) {
+        let target = &mut self.backings[backing];
+        target.width = width;
+        target.height = height;
+        target
+            .buffers
+            .rebuild_vertices(&self.gl, font, height, width, tiles, offset_x, offset_y);
+    }
+
+    fn draw_tiles(&mut self, backing: usize, font: &Font, shader_index: usize) {
+        let target = &mut self.backings[backing];
+        target.buffers.gl_draw(
+            font,
+            &self.shaders[shader_index],
+            &self.gl,
+            target.width,
+            target.height,
+        );
+    }
+
+    fn create_sparse_backing(&mut self, _width: u32, _height: u32) -> usize {
+        self.sparse_backings
+            .push(SparseConsoleBackend::new(&self.gl));
+        self.sparse_backings.len() - 1
+    }
+
+    fn rebuild_sparse(
+        &mut self,
+        backing: usize,
+        font: &Font,
+        width: u32,
+        height: u32,
+        tiles: &[SparseTile],
+        offset_x: f32,
+        offset_y: f32,
+    ) {
+        self.sparse_backings[backing]
+            .rebuild_vertices(&self.gl, font, height, width, tiles, offset_x, offset_y);
+    }
+
+    fn draw_sparse(&mut self, backing: usize, font: &Font, shader_index: usize) {
+        self.sparse_backings[backing].gl_draw(font, &self.shaders[shader_index], &self.gl);
+    }
+
+    fn create_fancy_backing(&mut self, _width: u32, _height: u32) -> usize {
+        self.fancy_backings.push(FancyConsoleBackend::new(&self.gl));
+        self.fancy_backings.len() - 1
+    }
+
+    fn rebuild_fancy(
+        &mut self,
+        backing: usize,
+        font: &Font,
+        width: u32,
+        height: u32,
+        tiles: &[FancyTile],
+        offset_x: f32,
+        offset_y: f32,
+    ) {
+        self.fancy_backings[backing].rebuild_vertices(
+            &self.gl,
+            font,
+            height,
+            width,
+            self.viewport.width,
+            self.viewport.height,
+            tiles,
+            offset_x,
+            offset_y,
+        );
+    }
+
+    fn draw_fancy(&mut self, backing: usize, font: &Font, shader_index: usize) {
+        self.fancy_backings[backing].gl_draw(font, &self.shaders[shader_index], &self.gl);
+    }
+
+    fn setup_sprite_sheet(&mut self, sheet: &mut SpriteSheet) -> Result<(), FractalError> {
+        sheet.texture_id = Some(self.load_texture(Font::load_image(&sheet.backing_file)?));
+        Ok(())
+    }
+
+    fn create_sprite_backing(&mut self) -> usize {
+        self.sprite_backings
+            .push(SpriteConsoleBackend::new(&self.gl));
+        self.sprite_backings.len() - 1
+    }
+
+    fn rebuild_sprites(
+        &mut self,
+        backing: usize,
+        width: u32,
+        height: u32,
+        sheet: &SpriteSheet,
+        sprites: &[RenderSprite],
+    ) {
+        self.sprite_backings[backing].rebuild_vertices(&self.gl, width, height, sheet, sprites);
+    }
+
+    fn draw_sprites(&mut self, backing: usize, sheet: &SpriteSheet) {
+        self.sprite_backings[backing].gl_draw(sheet, &self.shaders[SHADER_SPRITES], &self.gl);
+    }
+
+    fn poll_events(&mut self) -> Vec<BackendEvent> {
+        let mut events = Vec::new();
+        let modifiers = &mut self.modifiers;
+
+        self.el.run_return(|event, _, control_flow| {
+            *control_flow = ControlFlow::Poll;
+
+            match event {
+                Event::MainEventsCleared => *control_flow = ControlFlow::Exit,
+                Event::WindowEvent { ref event, .. } => match event {
+                    WindowEvent::Resized(physical_size) => events.push(BackendEvent::Resized {
+                        width: physical_size.width,
+                        height: physical_size.height,
+                    }),
+                    WindowEvent::CloseRequested => events.push(BackendEvent::CloseRequested),
+                    WindowEvent::CursorMoved { position: pos, .. } => {
+                        events.push(BackendEvent::CursorMoved { x: pos.x, y: pos.y })
+                    }
+                    WindowEvent::MouseInput { state, button, .. } => {
+                        events.push(BackendEvent::MouseInput {
+                            button: *button,
+                            pressed: *state == ElementState::Pressed,
+                        })
+                    }
+                    WindowEvent::MouseWheel { delta, .. } => {
+                        let (x, y) = match delta {
+                            MouseScrollDelta::LineDelta(x, y) => (*x, *y),
+                            MouseScrollDelta::PixelDelta(pos) => (
+                                pos.x as f32 / PIXELS_PER_LINE,
+                                pos.y as f32 / PIXELS_PER_LINE,
+                            ),
+                        };
+                        events.push(BackendEvent::MouseWheel { x, y })
+                    }
+                    WindowEvent::ReceivedCharacter(c) => {
+                        events.push(BackendEvent::ReceivedCharacter(*c))
+                    }
+                    WindowEvent::Focused(focused) => events.push(BackendEvent::Focused(*focused)),
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                virtual_keycode: Some(virtual_keycode),
+                                state,
+                                ..
+                            },
+                        ..
+                    } => events.push(BackendEvent::KeyboardInput {
+                        key: *virtual_keycode,
+                        pressed: *state == ElementState::Pressed,
+                        shift: modifiers.shift(),
+                        control: modifiers.ctrl(),
+                        alt: modifiers.alt(),
+                    }),
+                    _ => (),
+                },
+                Event::DeviceEvent {
+                    event: DeviceEvent::ModifiersChanged(state),
+                    ..
+                } => *modifiers = state,
+                _ => (),
+            }
+        });
+
+        events
+    }
+
+    fn create_post_effect(&mut self, fragment_source: &str) -> Result<usize, FractalError> {
+        // Effects draw the same full-screen quad as the scanlines shader
+        self.post_shaders.push(Shader::new(
+            &self.gl,
+            shader_strings::SCANLINES_VS,
+            fragment_source,
+        )?);
+        Ok(self.post_shaders.len() - 1)
+    }
+
+    fn reload_post_effect(
+        &mut self,
+        handle: usize,
+        fragment_source: &str,
+    ) -> Result<(), FractalError> {
+        let shader = Shader::new(&self.gl, shader_strings::SCANLINES_VS, fragment_source)?;
+        let old = std::mem::replace(&mut self.post_shaders[handle], shader);
+        unsafe {
+            self.gl.delete_program(old.id);
+        }
+        Ok(())
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.width_pixels = width;
+        self.height_pixels = height;
+        self.wc.resize(PhysicalSize::new(width, height));
+        self.viewport = Viewport::full(width, height);
+        self.backing_buffer = Framebuffer::build_fbo(&self.gl, width as i32, height as i32);
+        self.post_buffer = Framebuffer::build_fbo(&self.gl, width as i32, height as i32);
+    }
+
+    fn begin_frame(&mut self, post_process: bool) {
+        // Bind to the backing buffer
+        self.drawing_to_backing = post_process;
+        if post_process {
+            self.backing_buffer.bind(&self.gl);
+        }
+
+        // Clear the screen, including any bars around the viewport
+        unsafe {
+            self.gl
+                .viewport(0, 0, self.width_pixels as i32, self.height_pixels as i32);
+            self.gl.clear_color(0.0, 0.0, 0.0, 1.0);
+            self.gl.clear(glow::COLOR_BUFFER_BIT);
+
+            // OpenGL measures the viewport from the bottom of the window
+            let vp = self.viewport;
+            self.gl.viewport(
+                vp.x,
+                self.height_pixels as i32 - vp.y - vp.height as i32,
+                vp.width as i32,
+                vp.height as i32,
+            );
+        }
+    }
+
+    fn set_viewport(&mut self, viewport: Viewport) {
+        self.viewport = viewport;
+    }
+
+    fn end_frame(&mut self, effects: &[PostEffect], post_scanlines: bool, post_screenburn: bool) {
+        self.post_process(effects, post_scanlines, post_screenburn);
+        self.wc.swap_buffers().unwrap();
+    }
+
+    fn present_frame(&mut self) {
+        self.wc.swap_buffers().unwrap();
+    }
+
+    fn capture_frame(
+        &mut self,
+        effects: &[PostEffect],
+        post_scanlines: bool,
+        post_screenburn: bool,
+    ) -> RgbaImage {
+        self.post_process(effects, post_scanlines, post_screenburn);
+
+        let mut data = vec![0u8; (self.width_pixels * self.height_pixels * 4) as usize];
+        unsafe {
+            self.gl.read_pixels(
+                0,
+                0,
+                self.width_pixels as i32,
+                self.height_pixels as i32,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                &mut data,
+            );
+        }
+
+        // OpenGL rows start at the bottom of the screen
+        let image = RgbaImage::from_raw(self.width_pixels, self.height_pixels, data).unwrap();
+        imageops::flip_vertical(&image)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,635 @@
+//! A backend that needs no window or GPU. Consoles are rasterised with their font bitmaps
+//! into an in-memory RGBA image, which makes it possible to run the game loop on CI machines
+//! and compare the output pixel by pixel.
+//!
+//! Scanlines are emulated on the CPU; custom post-processing effects need a GPU and are
+//! skipped.
+
+use crate::backend::font::Font;
+use crate::backend::post_process::PostEffect;
+use crate::backend::sprite_sheet::SpriteSheet;
+use crate::backend::{Backend, BackendEvent, SHADER_NO_BG};
+use crate::console::Tile;
+use crate::error::FractalError;
+use crate::fancy_console::FancyTile;
+use crate::sparse_console::SparseTile;
+use crate::sprite_console::RenderSprite;
+use crate::viewport::Viewport;
+use image::{Rgba, RgbaImage};
+use std::any::Any;
+use std::collections::VecDeque;
+
+/// A copy of a simple console's tiles, taken when it was last rebuilt
+struct TileBacking {
+    width: u32,
+    height: u32,
+    tiles: Vec<Tile>,
+    offset_x: f32,
+    offset_y: f32,
+}
+
+/// A copy of a sparse console's tiles, taken when it was last rebuilt
+struct SparseBacking {
+    width: u32,
+    height: u32,
+    tiles: Vec<SparseTile>,
+    offset_x: f32,
+    offset_y: f32,
+}
+
+/// A copy of a fancy console's glyphs, taken when it was last rebuilt
+struct FancyBacking {
+    width: u32,
+    height: u32,
+    tiles: Vec<FancyTile>,
+    offset_x: f32,
+    offset_y: f32,
+}
+
+/// A copy of a sprite console's sprites, taken when it was last rebuilt
+struct SpriteBacking {
+    width: u32,
+    height: u32,
+    sprites: Vec<RenderSprite>,
+}
+
+/// Software rendering backend
+pub struct HeadlessBackend {
+    /// Width of the output in pixels
+    pub width_pixels: u32,
+    /// Height of the output in pixels
+    pub height_pixels: u32,
+    /// The part of the output the consoles are drawn into
+    pub viewport: Viewport,
+    pixels: RgbaImage,
+    frame: RgbaImage,
+    fonts: Vec<RgbaImage>,
+    backings: Vec<TileBacking>,
+    sparse_backings: Vec<SparseBacking>,
+    fancy_backings: Vec<FancyBacking>,
+    sprite_sheets: Vec<RgbaImage>,
+    sprite_backings: Vec<SpriteBacking>,
+    events: VecDeque<BackendEvent>,
+    frame_limit: Option<u64>,
+    frames: u64,
+}
+
+impl HeadlessBackend {
+    /// Creates a headless backend with an output of the given size in pixels
+    pub fn new(width_pixels: u32, height_pixels: u32) -> HeadlessBackend {
+        HeadlessBackend {
+            width_pixels,
+            height_pixels,
+            viewport: Viewport::full(width_pixels, height_pixels),
+            pixels: RgbaImage::from_pixel(width_pixels, height_pixels, Rgba([0, 0, 0, 255])),
+            frame: RgbaImage::from_pixel(width_pixels, height_pixels, Rgba([0, 0, 0, 255])),
+            fonts: Vec::new(),
+            backings: Vec::new(),
+            sparse_backings: Vec::new(),
+            fancy_backings: Vec::new(),
+            sprite_sheets: Vec::new(),
+            sprite_backings: Vec::new(),
+            events: VecDeque::new(),
+            frame_limit: None,
+            frames: 0,
+        }
+    }
+
+    /// Requests that the backend asks the main loop to close after this many frames, so
+    /// `main_loop` returns on its own.
+    pub fn with_frame_limit(mut self, frames: u64) -> HeadlessBackend {
+        self.frame_limit = Some(frames);
+        self
+    }
+
+    /// Queues an event, which will be handed to the main loop on its next poll
+    pub fn push_event(&mut self, event: BackendEvent) {
+        self.events.push_back(event);
+    }
+
+    /// The last frame that was presented
+    pub fn frame(&self) -> &RgbaImage {
+        &self.frame
+    }
+
+    /// The number of frames presented so far
+    pub fn frames_rendered(&self) -> u64 {
+        self.frames
+    }
+
+    /// Applies the scanlines shader to the frame, the same way SCANLINES_FS does.
+    fn apply_scanlines(&mut self, post_screenburn: bool) {
+        let (w, h) = (self.width_pixels, self.height_pixels);
+        for (x, y, pixel) in self.pixels.enumerate_pixels_mut() {
+            let col = [
+                f32::from(pixel[0]) / 255.0,
+                f32::from(pixel[1]) / 255.0,
+                f32::from(pixel[2]) / 255.0,
+            ];
+            // gl_FragCoord is measured from the bottom left, at pixel centers
+            let frag_x = x as f32 + 0.5;
+            let frag_y = (h - 1 - y) as f32 + 0.5;
+
+            let out = if col[0] < 0.1 && col[1] < 0.1 && col[2] < 0.1 {
+                if post_screenburn {
+                    let dx = frag_x / w as f32 - 0.5;
+                    let dy = frag_y / h as f32 - 0.5;
+                    let dist = (1.0 - f32::sqrt(dx * dx + dy * dy)) * 0.2;
+                    [0.0, dist, dist]
+                } else {
+                    [0.0, 0.0, 0.0]
+                }
+            } else {
+                let scan_line = (frag_y % 2.0) * 0.25;
+                [col[0] - scan_line, col[1] - scan_line, col[2] - scan_line]
+            };
+
+            *pixel = Rgba([to_byte(out[0]), to_byte(out[1]), to_byte(out[2]), 255]);
+        }
+    }
+}
+
+/// Clamps to 0..1. Unlike f32::clamp, NaN comes out as 0 rather than NaN.
+#[allow(clippy::manual_clamp)]
+fn clamp01(value: f32) -> f32 {
+    f32::max(0.0, f32::min(value, 1.0))
+}
+
+/// Converts a 0..1 color channel to 0..255, clamping like OpenGL does
+fn to_byte(channel: f32) -> u8 {
+    (clamp01(channel) * 255.0) as u8
+}
+
+/// Multiplies a color channel by a tint channel, both 0..255
+fn tint(channel: u8, by: u8) -> u8 {
+    (u32::from(channel) * u32::from(by) / 255) as u8
+}
+
+/// Composites one straight-alpha color over another, like CONSOLE_WITH_BG does with the glyph
+/// and background colors
+fn over(top: [f32; 4], bottom: [f32; 4]) -> [f32; 4] {
+    let alpha = top[3] + bottom[3] * (1.0 - top[3]);
+    if alpha <= 0.0 {
+        return [0.0; 4];
+    }
+    let channel = |i: usize| (top[i] * top[3] + bottom[i] * bottom[3] * (1.0 - top[3])) / alpha;
+    [channel(0), channel(1), channel(2), alpha]
+}
+
+/// Draws a glyph from a font's image into a rectangle of the output, given by its top-left
+/// corner and size in pixels, turned clockwise by rotation (in radians) around its center.
+/// Mirrors the CONSOLE_WITH_BG and CONSOLE_NO_BG shaders, blending by alpha with whatever was
+/// drawn before.
+#[allow(clippy::too_many_arguments)]
+fn draw_glyph(
+    pixels: &mut RgbaImage,
+    font: &Font,
+    font_image: &RgbaImage,
+    top_left: (f32, f32),
+    size: (f32, f32),
+    rotation: f32,
+    tile: &Tile,
+    no_bg: bool,
+) {
+    let columns = font.columns();
+    let glyph_width = font_image.width() / columns;
+    let glyph_height = font_image.height() / font.rows();
+    let glyph = u32::min(u32::from(tile.glyph), columns * font.rows() - 1);
+
+    let center = (top_left.0 + size.0 / 2.0, top_left.1 + size.1 / 2.0);
+    let (sin, cos) = rotation.sin_cos();
+    let extent_x = (cos.abs() * size.0.abs() + sin.abs() * size.1.abs()) / 2.0;
+    let extent_y = (sin.abs() * size.0.abs() + cos.abs() * size.1.abs()) / 2.0;
+
+    let x_start = i32::max(0, (center.0 - extent_x).floor() as i32);
+    let y_start = i32::max(0, (center.1 - extent_y).floor() as i32);
+    let x_end = i32::min(pixels.width() as i32, (center.0 + extent_x).ceil() as i32);
+    let y_end = i32::min(pixels.height() as i32, (center.1 + extent_y).ceil() as i32);
+
+    for py in y_start..y_end {
+        for px in x_start..x_end {
+            // Sample at the pixel's center, like OpenGL does, undoing the rotation
+            let dx = px as f32 + 0.5 - center.0;
+            let dy = py as f32 + 0.5 - center.1;
+            let u = (dx * cos + dy * sin) / size.0 + 0.5;
+            let v = (dy * cos - dx * sin) / size.1 + 0.5;
+            if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+                continue;
+            }
+
+            let texel_x = (glyph % columns) * glyph_width + (u * glyph_width as f32) as u32;
+            let texel_y = (glyph / columns) * glyph_height + (v * glyph_height as f32) as u32;
+            let texel = font_image.get_pixel(texel_x, texel_y);
+
+            let any_lit = texel[0] > 25 || texel[1] > 25 || texel[2] > 25;
+            let all_lit = texel[0] > 25 && texel[1] > 25 && texel[2] > 25;
+
+            let glyph_color = [
+                f32::from(tint(texel[0], tile.fg.r)) / 255.0,
+                f32::from(tint(texel[1], tile.fg.g)) / 255.0,
+                f32::from(tint(texel[2], tile.fg.b)) / 255.0,
+                f32::from(tint(texel[3], tile.fg.a)) / 255.0,
+            ];
+            let background = [tile.bg.rf(), tile.bg.gf(), tile.bg.bf(), tile.bg.af()];
+
+            let color = if no_bg {
+                if !all_lit {
+                    continue;
+                }
+                glyph_color
+            } else if any_lit {
+                over(glyph_color, background)
+            } else {
+                background
+            };
+
+            // Blended with SRC_ALPHA, ONE_MINUS_SRC_ALPHA
+            let under = *pixels.get_pixel(px as u32, py as u32);
+            let blend = |src: f32, dst: u8| {
+                let value = src * color[3] + f32::from(dst) / 255.0 * (1.0 - color[3]);
+                (clamp01(value) * 255.0).round() as u8
+            };
+            pixels.put_pixel(
+                px as u32,
+                py as u32,
+                Rgba([
+                    blend(color[0], under[0]),
+                    blend(color[1], under[1]),
+                    blend(color[2], under[2]),
+                    255,
+                ]),
+            );
+        }
+    }
+}
+
+impl Backend for HeadlessBackend {
+    fn setup_font(&mut self, font: &mut Font) -> Result<(), FractalError> {
+        self.fonts.push(font.image()?.to_rgba());
+        font.texture_id = Some((self.fonts.len() - 1) as u32);
+        Ok(())
+    }
+
+    fn reload_font(&mut self, font: &Font) -> Result<(), FractalError> {
+        if let Some(id) = font.texture_id {
+            self.fonts[id as usize] = font.image()?.to_rgba();
+        }
+        Ok(())
+    }
+
+    fn create_tile_backing(&mut self, width: u32, height: u32) -> usize {
+        self.backings.push(TileBacking {
+            width,
+            height,
+            tiles: Vec::new(),
+            offset_x: 0.0,
+            offset_y: 0.0,
+        });
+        self.backings.len() - 1
+    }
+
+    fn rebuild_tiles(
+        &mut self,
+        backing: usize,
+        _font: &Font,
+        width: u32,
+        height: u32,
+        tiles: &[Tile],
+        offset_x: f32,
+        offset_y: f32,
+    ) {
+        let target = &mut self.backings[backing];
+        target.width = width;
+        target.height = height;
+        target.tiles.clear();
+        target.tiles.extend_from_slice(tiles);
+        target.offset_x = offset_x;
+        target.offset_y = offset_y;
+    }
+
+    fn draw_tiles(&mut self, backing: usize, font: &Font, shader_index: usize) {
+        let font_image = match font.texture_id {
+            Some(id) => &self.fonts[id as usize],
+            None => return,
+        };
+        let target = &self.backings[backing];
+        if target.tiles.is_empty() {
+            return;
+        }
+
+        let vp = self.viewport;
+        let cell_width = vp.width as f32 / target.width as f32;
+        let cell_height = vp.height as f32 / target.height as f32;
+        // Offsets are in screen units, where 2.0 is the whole viewport
+        let shift_x = vp.x as f32 + target.offset_x * vp.width as f32 / 2.0;
+        let shift_y = target.offset_y * vp.height as f32 / 2.0 - vp.y as f32;
+
+        for (idx, tile) in target.tiles.iter().enumerate() {
+            // Tiles are stored bottom row first
+            let x = (idx as u32 % target.width) as f32;
+            let y = (target.height - 1 - idx as u32 / target.width) as f32;
+            draw_glyph(
+                &mut self.pixels,
+                font,
+                font_image,
+                (x * cell_width + shift_x, y * cell_height - shift_y),
+                (cell_width, cell_height),
+                0.0,
+                tile,
+                shader_index == SHADER_NO_BG,
+            );
+        }
+    }
+
+    fn create_sparse_backing(&mut self, width: u32, height: u32) -> usize {
+        self.sparse_backings.push(SparseBacking {
+            width,
+            height,
+            tiles: Vec::new(),
+            offset_x: 0.0,
+            offset_y: 0.0,
+        });
+        self.sparse_backings.len() - 1
+    }
+
+    fn rebuild_sparse(
+        &mut self,
+        backing: usize,
+        _font: &Font,
+        width: u32,
+        height: u32,
+        tiles: &[SparseTile],
+        offset_x: f32,
+        offset_y: f32,
+    ) {
+        let target = &mut self.sparse_backings[backing];
+        target.width = width;
+        target.height = height;
+        target.tiles.clear();
+        target.tiles.extend_from_slice(tiles);
+        target.offset_x = offset_x;
+        target.offset_y = offset_y;
+    }
+
+    fn draw_sparse(&mut self, backing: usize, font: &Font, shader_index: usize) {
+        let font_image = match font.texture_id {
+            Some(id) => &self.fonts[id as usize],
+            None => return,
+        };
+        let target = &self.sparse_backings[backing];
+
+        let vp = self.viewport;
+        let cell_width = vp.width as f32 / target.width as f32;
+        let cell_height = vp.height as f32 / target.height as f32;
+        let shift_x = vp.x as f32 + target.offset_x * vp.width as f32 / 2.0;
+        let shift_y = target.offset_y * vp.height as f32 / 2.0 - vp.y as f32;
+
+        for sparse in &target.tiles {
+            let x = (sparse.idx as u32 % target.width) as f32;
+            let y = (target.height - 1 - sparse.idx as u32 / target.width) as f32;
+            let tile = Tile {
+                glyph: sparse.glyph,
+                fg: sparse.fg,
+                bg: sparse.bg,
+            };
+            draw_glyph(
+                &mut self.pixels,
+                font,
+                font_image,
+                (x * cell_width + shift_x, y * cell_height - shift_y),
+                (cell_width, cell_height),
+                0.0,
+                &tile,
+                shader_index == SHADER_NO_BG,
+            );
+        }
+    }
+
+    fn create_fancy_backing(&mut self, width: u32, height: u32) -> usize {
+        self.fancy_backings.push(FancyBacking {
+            width,
+            height,
+            tiles: Vec::new(),
+            offset_x: 0.0,
+            offset_y: 0.0,
+        });
+        self.fancy_backings.len() - 1
+    }
+
+    fn rebuild_fancy(
+        &mut self,
+        backing: usize,
+        _font: &Font,
+        width: u32,
+        height: u32,
+        tiles: &[FancyTile],
+        offset_x: f32,
+        offset_y: f32,
+    ) {
+        let target = &mut self.fancy_backings[backing];
+        target.width = width;
+        target.height = height;
+        target.tiles.clear();
+        target.tiles.extend_from_slice(tiles);
+        target.offset_x = offset_x;
+        target.offset_y = offset_y;
+    }
+
+    fn draw_fancy(&mut self, backing: usize, font: &Font, shader_index: usize) {
+        let font_image = match font.texture_id {
+            Some(id) => &self.fonts[id as usize],
+            None => return,
+        };
+        let target = &self.fancy_backings[backing];
+
+        let vp = self.viewport;
+        let cell_width = vp.width as f32 / target.width as f32;
+        let cell_height = vp.height as f32 / target.height as f32;
+        let shift_x = vp.x as f32 + target.offset_x * vp.width as f32 / 2.0;
+        let shift_y = target.offset_y * vp.height as f32 / 2.0 - vp.y as f32;
+
+        for fancy in &target.tiles {
+            let (cx, cy) = fancy.center_pixels(cell_width, cell_height);
+            let size = (cell_width * fancy.scale.0, cell_height * fancy.scale.1);
+            let tile = Tile {
+                glyph: fancy.glyph,
+                fg: fancy.fg,
+                bg: fancy.bg,
+            };
+            draw_glyph(
+                &mut self.pixels,
+                font,
+                font_image,
+                (cx - size.0 / 2.0 + shift_x, cy - size.1 / 2.0 - shift_y),
+                size,
+                fancy.rotation,
+                &tile,
+                shader_index == SHADER_NO_BG,
+            );
+        }
+    }
+
+    fn setup_sprite_sheet(&mut self, sheet: &mut SpriteSheet) -> Result<(), FractalError> {
+        self.sprite_sheets
+            .push(Font::load_image(&sheet.backing_file)?.to_rgba());
+        sheet.texture_id = Some((self.sprite_sheets.len() - 1) as u32);
+        Ok(())
+    }
+
+    fn create_sprite_backing(&mut self) -> usize {
+        self.sprite_backings.push(SpriteBacking {
+            width: 1,
+            height: 1,
+            sprites: Vec::new(),
+        });
+        self.sprite_backings.len() - 1
+    }
+
+    fn rebuild_sprites(
+        &mut self,
+        backing: usize,
+        width: u32,
+        height: u32,
+        _sheet: &SpriteSheet,
+        sprites: &[RenderSprite],
+    ) {
+        let target = &mut self.sprite_backings[backing];
+        target.width = width;
+        target.height = height;
+        target.sprites.clear();
+        target.sprites.extend_from_slice(sprites);
+    }
+
+    fn draw_sprites(&mut self, backing: usize, sheet: &SpriteSheet) {
+        let sheet_image = match sheet.texture_id {
+            Some(id) => &self.sprite_sheets[id as usize],
+            None => return,
+        };
+        let target = &self.sprite_backings[backing];
+
+        let vp = self.viewport;
+        let scale_x = vp.width as f32 / target.width as f32;
+        let scale_y = vp.height as f32 / target.height as f32;
+
+        for sprite in &target.sprites {
+            let source = sheet.sprites[sprite.sprite].sheet_location;
+            let dest = sprite.destination;
+            let left = vp.x as f32 + dest.x1 as f32 * scale_x;
+            let top = vp.y as f32 + dest.y1 as f32 * scale_y;
+            let width = dest.width() as f32 * scale_x;
+            let height = dest.height() as f32 * scale_y;
+
+            let x_start = i32::max(vp.x, left.floor() as i32);
+            let y_start = i32::max(vp.y, top.floor() as i32);
+            let x_end = i32::min(vp.x + vp.width as i32, (left + width).ceil() as i32);
+            let y_end = i32::min(vp.y + vp.height as i32, (top + height).ceil() as i32);
+
+            for py in y_start..y_end {
+                for px in x_start..x_end {
+                    // Sample at the pixel's center, like OpenGL does
+                    let u = (px as f32 + 0.5 - left) / width;
+                    let v = (py as f32 + 0.5 - top) / height;
+                    if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+                        continue;
+                    }
+
+                    let texel = sheet_image.get_pixel(
+                        source.x1 as u32 + (u * source.width() as f32) as u32,
+                        source.y1 as u32 + (v * source.height() as f32) as u32,
+                    );
+                    // Mirrors SPRITE_FS, blended with SRC_ALPHA, ONE_MINUS_SRC_ALPHA
+                    if texel[3] < 3 {
+                        continue;
+                    }
+                    let alpha = u32::from(texel[3]);
+                    let under = *self.pixels.get_pixel(px as u32, py as u32);
+                    let blend = |src: u8, dst: u8| {
+                        ((u32::from(src) * alpha + u32::from(dst) * (255 - alpha)) / 255) as u8
+                    };
+                    self.pixels.put_pixel(
+                        px as u32,
+                        py as u32,
+                        Rgba([
+                            blend(tint(texel[0], sprite.tint.r), under[0]),
+                            blend(tint(texel[1], sprite.tint.g), under[1]),
+                            blend(tint(texel[2], sprite.tint.b), under[2]),
+                            255,
+                        ]),
+                    );
+                }
+            }
+        }
+    }
+
+    fn poll_events(&mut self) -> Vec<BackendEvent> {
+        let mut events: Vec<BackendEvent> = self.events.drain(..).collect();
+        if let Some(limit) = self.frame_limit {
+            if self.frames >= limit {
+                events.push(BackendEvent::CloseRequested);
+            }
+        }
+        events
+    }
+
+    fn create_post_effect(&mut self, _fragment_source: &str) -> Result<usize, FractalError> {
+        Ok(0)
+    }
+
+    fn reload_post_effect(
+        &mut self,
+        _handle: usize,
+        _fragment_source: &str,
+    ) -> Result<(), FractalError> {
+        Ok(())
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.width_pixels = width;
+        self.height_pixels = height;
+        self.viewport = Viewport::full(width, height);
+        self.pixels = RgbaImage::from_pixel(width, height, Rgba([0, 0, 0, 255]));
+        self.frame = RgbaImage::from_pixel(width, height, Rgba([0, 0, 0, 255]));
+    }
+
+    fn set_viewport(&mut self, viewport: Viewport) {
+        self.viewport = viewport;
+    }
+
+    fn begin_frame(&mut self, _post_process: bool) {
+        for pixel in self.pixels.pixels_mut() {
+            *pixel = Rgba([0, 0, 0, 255]);
+        }
+    }
+
+    fn end_frame(&mut self, _effects: &[PostEffect], post_scanlines: bool, post_screenburn: bool) {
+        if post_scanlines {
+            self.apply_scanlines(post_screenburn);
+        }
+        self.present_frame();
+    }
+
+    fn present_frame(&mut self) {
+        self.frame = self.pixels.clone();
+        self.frames += 1;
+    }
+
+    fn capture_frame(
+        &mut self,
+        _effects: &[PostEffect],
+        post_scanlines: bool,
+        post_screenburn: bool,
+    ) -> RgbaImage {
+        if post_scanlines {
+            self.apply_scanlines(post_screenburn);
+        }
+        self.pixels.clone()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -1,97 +1,138 @@
-//! This module contains a single helper function
+//! Opens the window and OpenGL context, or falls back to the terminal
 
 #![allow(unsafe_code)]
 
-use crate::backend::framebuffer::Framebuffer;
-use crate::backend::quadrender::setup_quad;
+use crate::backend::gl::PlatformGL;
 use crate::backend::shader::Shader;
 use crate::backend::shader_strings;
-use crate::backend::FractalPlatform;
-use crate::backend::PlatformGL;
-use crate::backend::WrappedContext;
+use crate::backend::terminal::TerminalBackend;
+use crate::error::FractalError;
 use crate::fractal::Fractal;
-use glutin::{dpi::LogicalSize, event_loop::EventLoop, window::WindowBuilder, ContextBuilder};
+use glutin::window::{Fullscreen, WindowBuilder};
+use glutin::{dpi::LogicalSize, event_loop::EventLoop, ContextBuilder, GlProfile};
+use std::env;
+
+/// Environment variable that selects the backend. Set it to "terminal" to run in the terminal
+/// instead of opening a window.
+pub const BACKEND_VAR: &str = "FRACTAL_BACKEND";
+
+/// Settings for the window and OpenGL context
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InitHints {
+    /// Wait for the display's vertical sync before presenting each frame
+    pub vsync: bool,
+    /// Ask for an sRGB-capable framebuffer
+    pub srgb: bool,
+    /// Cover the primary monitor with a borderless window
+    pub fullscreen: bool,
+    /// Which OpenGL profile to ask for
+    pub gl_profile: GlProfile,
+}
+
+impl Default for InitHints {
+    fn default() -> InitHints {
+        InitHints {
+            vsync: true,
+            srgb: true,
+            fullscreen: false,
+            gl_profile: GlProfile::Core,
+        }
+    }
+}
+
+/// Creates a raw Fractal instance, rendering through OpenGL (or the terminal, if requested by
+/// the FRACTAL_BACKEND environment variable). Fails if the window or context can't be created,
+/// or the built-in shaders don't compile.
+pub fn init_raw<S: ToString>(
+    width_pixels: u32,
+    height_pixels: u32,
+    window_title: S,
+) -> Result<Fractal, FractalError> {
+    init_with_hints(
+        width_pixels,
+        height_pixels,
+        window_title,
+        &InitHints::default(),
+    )
+}
+
+/// Like init_raw, but with control over the window and context settings
+pub fn init_with_hints<S: ToString>(
+    width_pixels: u32,
+    height_pixels: u32,
+    window_title: S,
+    hints: &InitHints,
+) -> Result<Fractal, FractalError> {
+    if env::var(BACKEND_VAR)
+        .map(|b| b == "terminal")
+        .unwrap_or(false)
+    {
+        return Ok(Fractal::init_with_backend(
+            Box::new(TerminalBackend::new(width_pixels, height_pixels)?),
+            width_pixels,
+            height_pixels,
+        ));
+    }
 
-/// Creates a raw Fractal instance
-pub fn init_raw<S: ToString>(width_pixels: u32, height_pixels: u32, window_title: S) -> Fractal {
     let el = EventLoop::new();
-    let wb = WindowBuilder::new()
+    let mut wb = WindowBuilder::new()
         .with_title(window_title.to_string())
         .with_inner_size(LogicalSize::new(
             f64::from(width_pixels),
             f64::from(height_pixels),
         ));
+    if hints.fullscreen {
+        wb = wb.with_fullscreen(Some(Fullscreen::Borderless(el.primary_monitor())));
+    }
     let windowed_context = ContextBuilder::new()
         .with_gl(glutin::GlRequest::Latest)
-        .with_gl_profile(glutin::GlProfile::Core)
+        .with_gl_profile(hints.gl_profile)
         .with_hardware_acceleration(Some(true))
-        .with_vsync(true)
-        .with_srgb(true)
+        .with_vsync(hints.vsync)
+        .with_srgb(hints.srgb)
         .build_windowed(wb, &el)
-        .unwrap();
-    let windowed_context = unsafe { windowed_context.make_current().unwrap() };
+        .map_err(|e| FractalError::Window(e.to_string()))?;
+    let windowed_context = unsafe {
+        windowed_context
+            .make_current()
+            .map_err(|(_, e)| FractalError::Window(e.to_string()))?
+    };
 
     let gl = glow::Context::from_loader_function(|ptr| windowed_context.get_proc_address(ptr));
 
     // Load our basic shaders
-    let mut shaders: Vec<Shader> = Vec::new();
-
-    shaders.push(Shader::new(
-        &gl,
-        shader_strings::CONSOLE_WITH_BG_VS,
-        shader_strings::CONSOLE_WITH_BG_FS,
-    ));
-    shaders.push(Shader::new(
-        &gl,
-        shader_strings::CONSOLE_NO_BG_VS,
-        shader_strings::CONSOLE_NO_BG_FS,
-    ));
-    shaders.push(Shader::new(
-        &gl,
-        shader_strings::BACKING_VS,
-        shader_strings::BACKING_FS,
-    ));
-    shaders.push(Shader::new(
-        &gl,
-        shader_strings::SCANLINES_VS,
-        shader_strings::SCANLINES_FS,
-    ));
-
-    // Build the backing frame-buffer
-    let backing_fbo = Framebuffer::build_fbo(&gl, width_pixels as i32, height_pixels as i32);
+    let shaders: Vec<Shader> = vec![
+        Shader::new(
+            &gl,
+            shader_strings::CONSOLE_WITH_BG_VS,
+            shader_strings::CONSOLE_WITH_BG_FS,
+        )?,
+        Shader::new(
+            &gl,
+            shader_strings::CONSOLE_NO_BG_VS,
+            shader_strings::CONSOLE_NO_BG_FS,
+        )?,
+        Shader::new(&gl, shader_strings::BACKING_VS, shader_strings::BACKING_FS)?,
+        Shader::new(
+            &gl,
+            shader_strings::SCANLINES_VS,
+            shader_strings::SCANLINES_FS,
+        )?,
+        Shader::new(&gl, shader_strings::SPRITE_VS, shader_strings::SPRITE_FS)?,
+    ];
 
-    // Build a simple quad rendering vao
-    let quad_vao = setup_quad(&gl);
+    let platform = PlatformGL::new(
+        gl,
+        el,
+        windowed_context,
+        shaders,
+        width_pixels,
+        height_pixels,
+    );
 
-    Fractal {
-        backend: FractalPlatform {
-            platform: PlatformGL {
-                gl,
-                quad_vao,
-                context_wrapper: Some(WrappedContext {
-                    el,
-                    wc: windowed_context,
-                }),
-                backing_buffer: backing_fbo,
-            },
-        },
+    Ok(Fractal::init_with_backend(
+        Box::new(platform),
         width_pixels,
         height_pixels,
-        fonts: Vec::new(),
-        consoles: Vec::new(),
-        shaders,
-        fps: 0.0,
-        frame_time_ms: 0.0,
-        active_console: 0,
-        key: None,
-        mouse_pos: (0, 0),
-        left_click: false,
-        shift: false,
-        control: false,
-        alt: false,
-        web_button: None,
-        quitting: false,
-        post_scanlines: false,
-        post_screenburn: false,
-    }
+    ))
 }
@@ -1,138 +1,218 @@
 //! This module contains the main loop
 
-#![allow(unsafe_code)]
-
-use crate::backend::framebuffer::Framebuffer;
+use crate::backend::post_process::Uniform;
+use crate::backend::BackendEvent;
 use crate::console::Console;
+use crate::draw_batch::render_draw_buffer;
+use crate::error::FractalError;
 use crate::fractal::Fractal;
-use crate::GameState;
-use glow::HasContext;
-use glutin::{event::Event, event::WindowEvent, event_loop::ControlFlow};
-use std::time::Instant;
+use crate::hot_reload::Asset;
+use crate::input::{InputEvent, MouseButton};
+use crate::perf;
+use crate::rex::XpFile;
+use crate::{log, GameState};
+use image::RgbaImage;
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
 
-const TICK_TYPE: ControlFlow = ControlFlow::Poll;
+/// The longest stretch of time fixed-timestep updates will try to catch up on in one frame.
+/// Anything more (a breakpoint, a dragged window) is dropped, rather than spending the following
+/// frames doing nothing but updates.
+const MAX_CATCH_UP_SECONDS: f32 = 0.25;
 
-/// The main loop 
+/// The main loop
 pub fn main_loop<GS: GameState>(mut fractal: Fractal, mut gamestate: GS) {
-    unsafe {
-        fractal.backend.platform.gl.viewport(
-            0,
-            0,
-            fractal.width_pixels as i32,
-            fractal.height_pixels as i32,
-        );
-    }
     let now = Instant::now();
     let mut prev_seconds = now.elapsed().as_secs();
-    let mut prev_ms = now.elapsed().as_millis();
     let mut frames = 0;
+    let mut last_frame = Instant::now();
+    let mut accumulator = 0.0f32;
 
-    // We're doing a little dance here to get around lifetime/borrow checking.
-    // Removing the context data from FRACTAL in an atomic swap, so it isn't borrowed after move.
-    let wrap = std::mem::replace(&mut fractal.backend.platform.context_wrapper, None);
-    let unwrap = wrap.unwrap();
+    while !fractal.quitting {
+        let frame_start = Instant::now();
+        fractal.input.begin_frame();
+        fractal.left_click = false;
+        fractal.key = None;
+        fractal.shift = false;
+        fractal.control = false;
+        fractal.alt = false;
 
-    let el = unwrap.el;
-    let wc = unwrap.wc;
-
-    el.run(move |event, _, control_flow| {
-        *control_flow = TICK_TYPE;
+        let live = fractal.backend.poll_events();
+        let elapsed = frame_start.duration_since(last_frame).as_secs_f32();
+        last_frame = frame_start;
+        let frame = fractal.replay.next_frame(live, elapsed);
+        for event in frame.events {
+            handle_event(&mut fractal, event);
+        }
 
         if fractal.quitting {
-            *control_flow = ControlFlow::Exit;
+            break;
         }
 
-        match event {
-            Event::NewEvents(_) => {
-                fractal.left_click = false;
-                fractal.key = None;
-                fractal.shift = false;
-                fractal.control = false;
-                fractal.alt = false;
-            }
-            Event::MainEventsCleared => {
-                tock(
-                    &mut fractal,
-                    &mut gamestate,
-                    &mut frames,
-                    &mut prev_seconds,
-                    &mut prev_ms,
-                    &now,
-                );
-                wc.swap_buffers().unwrap();
+        reload_assets(&mut fractal, frame.elapsed);
+
+        fixed_updates(
+            &mut fractal,
+            &mut gamestate,
+            &mut accumulator,
+            frame.elapsed,
+        );
+
+        tock(
+            &mut fractal,
+            &mut gamestate,
+            &mut frames,
+            &mut prev_seconds,
+            &now,
+            frame.elapsed,
+        );
+
+        limit_frame_rate(&fractal, frame_start);
+    }
+}
+
+/// Runs as many fixed-timestep updates as the time since the last frame calls for, and works out
+/// the interpolation alpha for the frame about to be drawn.
+fn fixed_updates<GS: GameState>(
+    fractal: &mut Fractal,
+    gamestate: &mut GS,
+    accumulator: &mut f32,
+    elapsed: f32,
+) {
+    let step = match fractal.fixed_timestep {
+        Some(step) if step > 0.0 => step,
+        _ => {
+            fractal.frame_alpha = 0.0;
+            return;
+        }
+    };
+
+    *accumulator += f32::min(elapsed, MAX_CATCH_UP_SECONDS);
+    while *accumulator >= step {
+        gamestate.update(fractal, step);
+        *accumulator -= step;
+    }
+    fractal.frame_alpha = *accumulator / step;
+}
+
+/// Reloads the watched fonts, shaders and xp files whose files changed. A file that fails to
+/// load is logged and its old version kept, so a half-saved file doesn't end the game.
+fn reload_assets(fractal: &mut Fractal, elapsed: f32) {
+    for (asset, path) in fractal.hot_reload.changed(elapsed) {
+        match reload_asset(fractal, asset, &path) {
+            Ok(()) => log(format!("Reloaded {}", path.display())),
+            Err(e) => log(format!("Unable to reload {}: {}", path.display(), e)),
+        }
+    }
+}
+
+/// Reads one asset's file again and hands the result to the backend. Consoles pick up a font
+/// whose size changed the next time they are redrawn.
+fn reload_asset(fractal: &mut Fractal, asset: Asset, path: &Path) -> Result<(), FractalError> {
+    match asset {
+        Asset::Font(handle) => {
+            let font = &mut fractal.fonts[handle];
+            font.reload()?;
+            fractal.backend.reload_font(font)
+        }
+        Asset::PostEffect(handle) => {
+            let source = fs::read_to_string(path)?;
+            fractal.backend.reload_post_effect(handle, &source)
+        }
+        Asset::Xp(handle) => {
+            fractal.xp_files[handle] = XpFile::load(&path.to_string_lossy())?;
+            Ok(())
+        }
+    }
+}
+
+/// Sleeps away whatever is left of the frame, if the frame rate is capped.
+fn limit_frame_rate(fractal: &Fractal, frame_start: Instant) {
+    if let Some(fps) = fractal.target_fps {
+        if fps > 0.0 {
+            let frame = Duration::from_secs_f32(1.0 / fps);
+            let spent = frame_start.elapsed();
+            if spent < frame {
+                thread::sleep(frame - spent);
             }
-            Event::LoopDestroyed => (),
-            Event::WindowEvent { ref event, .. } => match event {
-                WindowEvent::Resized(physical_size) => {
-                    // Commenting out to see if it helps the Linux world
-                    //let dpi_factor = wc.window().hidpi_factor();
-                    wc.resize(*physical_size);
-                    fractal.resize_pixels(physical_size.width, physical_size.height);
-                    unsafe {
-                        fractal.backend.platform.gl.viewport(
-                            0,
-                            0,
-                            physical_size.width as i32,
-                            physical_size.height as i32,
-                        );
-                    }
-                    fractal.backend.platform.backing_buffer = Framebuffer::build_fbo(
-                        &fractal.backend.platform.gl,
-                        physical_size.width as i32,
-                        physical_size.height as i32,
-                    );
-                }
-                /*WindowEvent::RedrawRequested => {
-                    //tock(&mut fractal, &mut gamestate, &mut frames, &mut prev_seconds, &mut prev_ms, &now);
-                    wc.swap_buffers().unwrap();
-                }*/
-                WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
-
-                WindowEvent::CursorMoved { position: pos, .. } => {
-                    fractal.mouse_pos = (pos.x, pos.y);
-                }
+        }
+    }
+}
 
-                WindowEvent::MouseInput { .. } => {
+/// Applies an event from the backend to the context
+fn handle_event(fractal: &mut Fractal, event: BackendEvent) {
+    match event {
+        BackendEvent::Resized { width, height } => {
+            fractal.backend.resize(width, height);
+            fractal.resize_pixels(width, height);
+        }
+        BackendEvent::CloseRequested => fractal.quitting = true,
+        BackendEvent::CursorMoved { x, y } => {
+            fractal.mouse_pos = (x, y);
+            fractal.input.push(InputEvent::MouseMoved { x, y });
+        }
+        BackendEvent::MouseInput { button, pressed } => {
+            if pressed {
+                fractal.input.push(InputEvent::MouseDown(button));
+                if button == MouseButton::Left {
                     fractal.left_click = true;
                 }
+            } else {
+                fractal.input.push(InputEvent::MouseUp(button));
+            }
+        }
+        BackendEvent::MouseWheel { x, y } => {
+            fractal.input.push(InputEvent::MouseWheel { x, y });
+        }
+        BackendEvent::KeyboardInput {
+            key,
+            pressed,
+            shift,
+            control,
+            alt,
+        } => {
+            fractal.input.shift = shift;
+            fractal.input.control = control;
+            fractal.input.alt = alt;
+            if !pressed {
+                fractal.input.push(InputEvent::KeyUp(key));
+                return;
+            }
 
-                WindowEvent::KeyboardInput {
-                    input:
-                        glutin::event::KeyboardInput {
-                            virtual_keycode: Some(virtual_keycode),
-                            state: glutin::event::ElementState::Pressed,
-                            modifiers,
-                            ..
-                        },
-                    ..
-                } => {
-                    fractal.key = Some(*virtual_keycode);
-                    if modifiers.shift() {
-                        fractal.shift = true;
-                    }
-                    if modifiers.alt() {
-                        fractal.alt = true;
-                    }
-                    if modifiers.ctrl() {
-                        fractal.control = true;
-                    }
-                }
-
-                _ => (),
-            },
-            _ => (),
+            fractal.input.push(InputEvent::KeyDown(key));
+            fractal.key = Some(key);
+            if shift {
+                fractal.shift = true;
+            }
+            if alt {
+                fractal.alt = true;
+            }
+            if control {
+                fractal.control = true;
+            }
+        }
+        BackendEvent::ReceivedCharacter(c) => {
+            fractal.input.push(InputEvent::Character(c));
         }
-    });
+        BackendEvent::Focused(focused) => {
+            if !focused {
+                fractal.input.release_all();
+            }
+        }
+    }
 }
 
-/// Internal handling of the main loop.
+/// Internal handling of the main loop. elapsed is the frame's time in seconds, which comes from
+/// the replay while one is playing.
 fn tock<GS: GameState>(
     fractal: &mut Fractal,
     gamestate: &mut GS,
     frames: &mut i32,
     prev_seconds: &mut u64,
-    prev_ms: &mut u128,
     now: &Instant,
+    elapsed: f32,
 ) {
     let now_seconds = now.elapsed().as_secs();
     *frames += 1;
@@ -143,80 +223,110 @@ fn tock<GS: GameState>(
         *prev_seconds = now_seconds;
     }
 
-    let now_ms = now.elapsed().as_millis();
-    if now_ms > *prev_ms {
-        fractal.frame_time_ms = (now_ms - *prev_ms) as f32;
-        *prev_ms = now_ms;
+    fractal.frame_time_ms = elapsed * 1000.0;
+
+    let seconds = now.elapsed().as_secs_f32();
+    for effect in fractal.post_effects.iter_mut() {
+        effect
+            .uniforms
+            .insert("time".to_string(), Uniform::Float(seconds));
     }
 
+    fractal.animations.advance(fractal.frame_time_ms);
+    fractal.particles.advance(fractal.frame_time_ms);
+
+    fractal.perf.begin_frame();
+    let tick_start = Instant::now();
     gamestate.tick(fractal);
+    fractal.perf.record(perf::TICK, tick_start.elapsed());
+
+    render(fractal);
+}
+
+/// Draws the console stack through the backend and presents the frame. The main loop calls
+/// this every tick; it is public so that headless tests can render without running the loop.
+pub fn render(fractal: &mut Fractal) {
+    // Batches submitted since the last frame go onto their consoles first
+    render_draw_buffer(fractal);
+
+    // Then animations and particles, over whatever the game drew
+    fractal.animations.apply(&mut fractal.consoles);
+    fractal.particles.render(&mut fractal.consoles);
 
     // Console structure - doesn't really have to be every frame...
-    for cons in &mut fractal.consoles {
-        cons.console.rebuild_if_dirty(&fractal.backend);
-    }
+    let rebuild_start = Instant::now();
+    rebuild_consoles(fractal);
+    fractal.perf.record(perf::REBUILD, rebuild_start.elapsed());
+
+    let draw_start = Instant::now();
+    draw_consoles(fractal, None);
+    fractal.perf.record(perf::DRAW, draw_start.elapsed());
 
-    // Bind to the backing buffer
-    if fractal.post_scanlines {
+    // The consoles go back to what the game drew, ready for the next frame
+    fractal.particles.restore(&mut fractal.consoles);
+
+    // The overlay lines up with the first console, and isn't part of the timings it shows
+    if fractal.perf.show_overlay && !fractal.consoles.is_empty() {
+        let (width, height) = fractal.consoles[0].console.get_char_size();
+        let font = &fractal.fonts[fractal.consoles[0].font_index];
         fractal
-            .backend
-            .platform
-            .backing_buffer
-            .bind(&fractal.backend.platform.gl);
+            .perf
+            .draw_overlay(width, height, font, fractal.backend.as_mut());
     }
 
-    // Clear the screen
-    unsafe {
-        fractal.backend.platform.gl.clear_color(0.0, 0.0, 0.0, 1.0);
-        fractal.backend.platform.gl.clear(glow::COLOR_BUFFER_BIT);
+    let post_start = Instant::now();
+    if fractal.screenshot_requested {
+        // Read back after post-processing, then present what was read
+        let image = fractal.backend.capture_frame(
+            &fractal.post_effects,
+            fractal.post_scanlines,
+            fractal.post_screenburn,
+        );
+        fractal.backend.present_frame();
+        fractal.screenshot = Some(image);
+        fractal.screenshot_requested = false;
+    } else {
+        fractal.backend.end_frame(
+            &fractal.post_effects,
+            fractal.post_scanlines,
+            fractal.post_screenburn,
+        );
     }
+    fractal.perf.record(perf::POST, post_start.elapsed());
+}
 
-    // Tell each console to draw itself
+/// Draws a single console as it stands, without post-processing, and reads the result back
+/// without presenting it. Nothing the main loop does each frame is run, so draw batches,
+/// animations and particles that haven't been applied yet are left out.
+pub fn capture_console(fractal: &mut Fractal, console: usize) -> RgbaImage {
+    rebuild_consoles(fractal);
+    draw_consoles(fractal, Some(console));
+    fractal.backend.capture_frame(&[], false, false)
+}
+
+/// Rebuilds the vertices of every console whose tiles changed
+fn rebuild_consoles(fractal: &mut Fractal) {
     for cons in &mut fractal.consoles {
         let font = &fractal.fonts[cons.font_index];
-        let shader = &fractal.shaders[cons.shader_index];
-        cons.console.gl_draw(font, shader, &fractal.backend);
+        cons.console
+            .rebuild_if_dirty(font, fractal.backend.as_mut());
     }
+}
 
-    if fractal.post_scanlines {
-        // Now we return to the primary screen
-        fractal
-            .backend
-            .platform
-            .backing_buffer
-            .default(&fractal.backend.platform.gl);
-        unsafe {
-            if fractal.post_scanlines {
-                fractal.shaders[3].useProgram(&fractal.backend.platform.gl);
-                fractal.shaders[3].setVec3(
-                    &fractal.backend.platform.gl,
-                    "screenSize",
-                    fractal.width_pixels as f32,
-                    fractal.height_pixels as f32,
-                    0.0,
-                );
-                fractal.shaders[3].setBool(
-                    &fractal.backend.platform.gl,
-                    "screenBurn",
-                    fractal.post_screenburn,
-                );
-            } else {
-                fractal.shaders[2].useProgram(&fractal.backend.platform.gl);
-            }
-            fractal
-                .backend
-                .platform
-                .gl
-                .bind_vertex_array(Some(fractal.backend.platform.quad_vao));
-            fractal.backend.platform.gl.bind_texture(
-                glow::TEXTURE_2D,
-                Some(fractal.backend.platform.backing_buffer.texture),
-            );
-            fractal
-                .backend
-                .platform
-                .gl
-                .draw_arrays(glow::TRIANGLES, 0, 6);
+/// Starts a frame and draws the consoles (or just one of them) into it, ready for
+/// end_frame/capture_frame.
+fn draw_consoles(fractal: &mut Fractal, only_console: Option<usize>) {
+    let post_process = (fractal.post_scanlines || fractal.post_effects.iter().any(|e| e.enabled))
+        && only_console.is_none();
+    fractal.backend.begin_frame(post_process);
+
+    // Tell each console to draw itself
+    for (i, cons) in fractal.consoles.iter_mut().enumerate() {
+        if only_console.is_some_and(|only| only != i) {
+            continue;
         }
+        let font = &fractal.fonts[cons.font_index];
+        cons.console
+            .draw(font, cons.shader_index, fractal.backend.as_mut());
     }
 }
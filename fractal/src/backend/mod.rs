@@ -1,40 +1,233 @@
-//! Contains all backend capabilities for OpenGL
+//! Contains the rendering backends, and the trait they all implement
 
-use crate::backend::framebuffer::Framebuffer;
+use crate::backend::font::Font;
+use crate::backend::post_process::PostEffect;
+use crate::backend::sprite_sheet::SpriteSheet;
+use crate::console::Tile;
+use crate::error::FractalError;
+use crate::fancy_console::FancyTile;
+use crate::sparse_console::SparseTile;
+use crate::sprite_console::RenderSprite;
+use crate::viewport::Viewport;
+use glutin::event::{MouseButton, VirtualKeyCode};
+use image::RgbaImage;
+use std::any::Any;
 
+pub mod fancy_console_backing;
 pub mod font;
+pub mod font_atlas;
 pub mod framebuffer;
+pub mod gl;
+pub mod headless;
 pub mod init;
 pub mod mainloop;
+pub mod post_process;
 pub mod quadrender;
 pub mod shader;
 pub mod shader_strings;
 pub mod simple_console_backing;
+pub mod sparse_console_backing;
+pub mod sprite_console_backing;
+pub mod sprite_sheet;
+pub mod terminal;
+pub mod tile_vertices;
 
-// TODO merge [FractalPlatform] and [PlatformGL]
+/// Shader index that draws tiles with their background color
+pub const SHADER_WITH_BG: usize = 0;
+/// Shader index that only draws the glyphs, so lower consoles show through
+pub const SHADER_NO_BG: usize = 1;
 
-/// The platform to use
-pub struct FractalPlatform {
-    /// The opengl platform
-    pub platform: PlatformGL,
+/// Events that a backend hands back to the main loop. Each backend translates its own
+/// window or terminal events into these.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum BackendEvent {
+    /// The output surface changed size, in pixels
+    Resized {
+        /// New width in pixels
+        width: u32,
+        /// New height in pixels
+        height: u32,
+    },
+    /// The user asked to close the window
+    CloseRequested,
+    /// The mouse moved to a new pixel position
+    CursorMoved {
+        /// X position in pixels
+        x: i32,
+        /// Y position in pixels
+        y: i32,
+    },
+    /// A mouse button was pressed or released
+    MouseInput {
+        /// The button that was used
+        button: MouseButton,
+        /// True if it went down, false if it was released
+        pressed: bool,
+    },
+    /// The mouse wheel turned, in lines
+    MouseWheel {
+        /// Horizontal scroll; positive is to the right
+        x: f32,
+        /// Vertical scroll; positive is up
+        y: f32,
+    },
+    /// A key was pressed or released
+    KeyboardInput {
+        /// The key that was used
+        key: VirtualKeyCode,
+        /// True if it went down, false if it was released
+        pressed: bool,
+        /// True if shift was held down
+        shift: bool,
+        /// True if control was held down
+        control: bool,
+        /// True if alt was held down
+        alt: bool,
+    },
+    /// A character was typed
+    ReceivedCharacter(char),
+    /// The window gained (true) or lost (false) the keyboard focus
+    Focused(bool),
 }
 
-/// The OpenGl platform
-pub struct PlatformGL {
-    /// TODO
-    pub gl: glow::Context,
-    /// TODO
-    pub quad_vao: u32,
-    /// TODO
-    pub context_wrapper: Option<WrappedContext>,
-    /// TODO
-    pub backing_buffer: Framebuffer,
-}
+/// Trait that must be implemented by rendering backends. `Fractal`, the consoles and the
+/// main loop only ever talk to the platform through this.
+pub trait Backend {
+    /// Loads a font's bitmap into the backend, and stores the resulting texture handle in it.
+    fn setup_font(&mut self, font: &mut Font) -> Result<(), FractalError>;
+
+    /// Uploads a font's image again, into the texture setup_font made for it. Used when the
+    /// font file changes on disk.
+    fn reload_font(&mut self, font: &Font) -> Result<(), FractalError>;
+
+    /// Allocates whatever is needed to draw a tile console of width x height characters, and
+    /// returns a handle to it.
+    fn create_tile_backing(&mut self, width: u32, height: u32) -> usize;
+
+    /// Rebuilds a tile backing from a console's tiles, with glyphs laid out as in the given
+    /// font. Offsets are in screen units (-1..1 spans the whole output).
+    #[allow(clippy::too_many_arguments)]
+    fn rebuild_tiles(
+        &mut self,
+        backing: usize,
+        font: &Font,
+        width: u32,
+        height: u32,
+        tiles: &[Tile],
+        offset_x: f32,
+        offset_y: f32,
+    );
+
+    /// Draws a tile backing with the given font and shader.
+    fn draw_tiles(&mut self, backing: usize, font: &Font, shader_index: usize);
+
+    /// Allocates whatever is needed to draw a sparse console of width x height characters, and
+    /// returns a handle to it.
+    fn create_sparse_backing(&mut self, width: u32, height: u32) -> usize;
+
+    /// Rebuilds a sparse backing from the tiles that were set on a sparse console.
+    #[allow(clippy::too_many_arguments)]
+    fn rebuild_sparse(
+        &mut self,
+        backing: usize,
+        font: &Font,
+        width: u32,
+        height: u32,
+        tiles: &[SparseTile],
+        offset_x: f32,
+        offset_y: f32,
+    );
+
+    /// Draws a sparse backing with the given font and shader.
+    fn draw_sparse(&mut self, backing: usize, font: &Font, shader_index: usize);
+
+    /// Allocates whatever is needed to draw a fancy console of width x height characters, and
+    /// returns a handle to it.
+    fn create_fancy_backing(&mut self, width: u32, height: u32) -> usize;
+
+    /// Rebuilds a fancy backing from a fancy console's glyphs, which arrive sorted by z-order.
+    #[allow(clippy::too_many_arguments)]
+    fn rebuild_fancy(
+        &mut self,
+        backing: usize,
+        font: &Font,
+        width: u32,
+        height: u32,
+        tiles: &[FancyTile],
+        offset_x: f32,
+        offset_y: f32,
+    );
+
+    /// Draws a fancy backing with the given font and shader.
+    fn draw_fancy(&mut self, backing: usize, font: &Font, shader_index: usize);
+
+    /// Loads a sprite sheet's image into the backend, and stores the resulting texture handle
+    /// in it.
+    fn setup_sprite_sheet(&mut self, sheet: &mut SpriteSheet) -> Result<(), FractalError>;
+
+    /// Allocates whatever is needed to draw a sprite console, and returns a handle to it.
+    fn create_sprite_backing(&mut self) -> usize;
+
+    /// Rebuilds a sprite backing from a sprite console's sprites, which arrive sorted by
+    /// z-order. Destinations are in pixels of a width x height canvas.
+    fn rebuild_sprites(
+        &mut self,
+        backing: usize,
+        width: u32,
+        height: u32,
+        sheet: &SpriteSheet,
+        sprites: &[RenderSprite],
+    );
+
+    /// Draws a sprite backing from the given sheet.
+    fn draw_sprites(&mut self, backing: usize, sheet: &SpriteSheet);
+
+    /// Collects the events that arrived since the last call.
+    fn poll_events(&mut self) -> Vec<BackendEvent>;
+
+    /// Resizes the output surface. The viewport goes back to covering all of it.
+    fn resize(&mut self, width: u32, height: u32);
+
+    /// Restricts console drawing to part of the output; the rest is left black. Backends that
+    /// can't, such as terminals, ignore it.
+    fn set_viewport(&mut self, viewport: Viewport);
+
+    /// Compiles a post-processing fragment shader, and returns a handle to it. Backends that
+    /// can't run shaders hand back a handle and skip the effect.
+    fn create_post_effect(&mut self, fragment_source: &str) -> Result<usize, FractalError>;
+
+    /// Recompiles a post-processing effect in place, keeping its handle. If the new source
+    /// doesn't compile, the old shader stays in use.
+    fn reload_post_effect(
+        &mut self,
+        handle: usize,
+        fragment_source: &str,
+    ) -> Result<(), FractalError>;
+
+    /// Clears the output, ready for the consoles to draw. If post_process is set, drawing goes
+    /// to an intermediate buffer.
+    fn begin_frame(&mut self, post_process: bool);
+
+    /// Applies post-processing (the enabled effects in order, then scanlines) and presents the
+    /// finished frame.
+    fn end_frame(&mut self, effects: &[PostEffect], post_scanlines: bool, post_screenburn: bool);
+
+    /// Applies post-processing like end_frame, but reads the finished frame back into an image
+    /// instead of presenting it.
+    fn capture_frame(
+        &mut self,
+        effects: &[PostEffect],
+        post_scanlines: bool,
+        post_screenburn: bool,
+    ) -> RgbaImage;
+
+    /// Presents a frame that capture_frame has already post-processed and read back.
+    fn present_frame(&mut self);
+
+    /// Produces the implementor as an Any that can be matched to determine type and access
+    /// natively.
+    fn as_any(&self) -> &dyn Any;
 
-/// Wrapper for the windows event loop and inputs
-pub struct WrappedContext {
-    /// event loop
-    pub el: glutin::event_loop::EventLoop<()>,
-    /// window context
-    pub wc: glutin::WindowedContext<glutin::PossiblyCurrent>,
+    /// Mutable version of as_any
+    fn as_any_mut(&mut self) -> &mut dyn Any;
 }
@@ -0,0 +1,44 @@
+//! User-defined post-processing effects, applied to the finished frame in order
+
+use std::collections::HashMap;
+
+/// A value handed to a post-processing shader's uniform
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Uniform {
+    /// A float uniform
+    Float(f32),
+    /// A vec2 uniform
+    Vec2(f32, f32),
+    /// A vec3 uniform
+    Vec3(f32, f32, f32),
+    /// A bool uniform
+    Bool(bool),
+}
+
+/// One pass of the post-processing chain. The fragment shader gets the previous pass (or the
+/// console stack, for the first one) as `uniform sampler2D screenTexture`, with
+/// `in vec2 TexCoords`. The uniforms `time` (float, seconds since the main loop started) and
+/// `screenSize` (vec3, pixels) are set every frame, along with any set by the game.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PostEffect {
+    /// Name of the effect
+    pub name: String,
+    /// Disabled effects are skipped
+    pub enabled: bool,
+    /// Uniforms to set before the pass runs
+    pub uniforms: HashMap<String, Uniform>,
+    /// Handle of the compiled effect in the backend
+    pub handle: usize,
+}
+
+impl PostEffect {
+    /// Creates an enabled effect, with no uniforms
+    pub fn new<S: ToString>(name: S, handle: usize) -> PostEffect {
+        PostEffect {
+            name: name.to_string(),
+            enabled: true,
+            uniforms: HashMap::new(),
+            handle,
+        }
+    }
+}
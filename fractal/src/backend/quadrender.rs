@@ -3,7 +3,7 @@
 #![allow(unsafe_code)]
 
 use glow::HasContext;
-use std::mem;
+use std::mem::size_of;
 
 /// Sets up a simple VAO/VBO to render a single quad
 /// Used for presenting the backing buffer and in post-process chains.
@@ -22,11 +22,11 @@ pub fn setup_quad(gl: &glow::Context) -> u32 {
         gl.bind_buffer(glow::ARRAY_BUFFER, Some(quad_vbo));
         gl.buffer_data_u8_slice(
             glow::ARRAY_BUFFER,
-            &quad_vertices.align_to::<u8>().1,
+            quad_vertices.align_to::<u8>().1,
             glow::STATIC_DRAW,
         );
         gl.enable_vertex_attrib_array(0);
-        let stride = 4 * mem::size_of::<f32>() as i32;
+        let stride = 4 * size_of::<f32>() as i32;
         gl.vertex_attrib_pointer_f32(0, 2, glow::FLOAT, false, stride, 0);
         gl.enable_vertex_attrib_array(1);
         gl.vertex_attrib_pointer_f32(
@@ -35,7 +35,7 @@ pub fn setup_quad(gl: &glow::Context) -> u32 {
             glow::FLOAT,
             false,
             stride,
-            2 * mem::size_of::<f32>() as i32,
+            2 * size_of::<f32>() as i32,
         );
     }
 
@@ -2,7 +2,7 @@
 
 #![allow(unsafe_code)]
 
-use crate::log;
+use crate::error::FractalError;
 use glow::HasContext;
 use std::str;
 use ultraviolet::Vec3;
@@ -15,49 +15,82 @@ pub struct Shader {
 
 /// NOTE: mixture of `shader_s.h` and `shader_m.h` (the latter just contains
 /// a few more setters for uniforms)
+#[allow(clippy::missing_safety_doc)]
 impl Shader {
-    /// Constructor
-    pub fn new(gl: &glow::Context, vertex_code: &str, fragment_code: &str) -> Shader {
+    /// Compiles and links a shader program. Fails with the driver's info log if either stage
+    /// doesn't compile or the program doesn't link.
+    pub fn new(
+        gl: &glow::Context,
+        vertex_code: &str,
+        fragment_code: &str,
+    ) -> Result<Shader, FractalError> {
         // 1. compile shaders from strings
         let shader;
         unsafe {
             // vertex shader
-            let vertex = gl.create_shader(glow::VERTEX_SHADER).unwrap();
-            gl.shader_source(vertex, &vertex_code);
+            let vertex = gl.create_shader(glow::VERTEX_SHADER).map_err(|log| {
+                FractalError::ShaderCompile {
+                    stage: "vertex",
+                    log,
+                }
+            })?;
+            gl.shader_source(vertex, vertex_code);
             gl.compile_shader(vertex);
             if !gl.get_shader_compile_status(vertex) {
-                log(&vertex_code);
-                log(&gl.get_shader_info_log(vertex));
-                panic!();
+                let log = gl.get_shader_info_log(vertex);
+                gl.delete_shader(vertex);
+                return Err(FractalError::ShaderCompile {
+                    stage: "vertex",
+                    log,
+                });
             }
 
             // fragment Shader
-            let fragment = gl.create_shader(glow::FRAGMENT_SHADER).unwrap();
-            gl.shader_source(fragment, &fragment_code);
+            let fragment = gl.create_shader(glow::FRAGMENT_SHADER).map_err(|log| {
+                gl.delete_shader(vertex);
+                FractalError::ShaderCompile {
+                    stage: "fragment",
+                    log,
+                }
+            })?;
+            gl.shader_source(fragment, fragment_code);
             gl.compile_shader(fragment);
             if !gl.get_shader_compile_status(fragment) {
-                log(&fragment_code);
-                log(&gl.get_shader_info_log(fragment));
-                panic!();
+                let log = gl.get_shader_info_log(fragment);
+                gl.delete_shader(vertex);
+                gl.delete_shader(fragment);
+                return Err(FractalError::ShaderCompile {
+                    stage: "fragment",
+                    log,
+                });
             }
 
             // shader Program
-            let id = gl.create_program().unwrap();
+            let id = gl.create_program().map_err(|log| {
+                gl.delete_shader(vertex);
+                gl.delete_shader(fragment);
+                FractalError::ShaderCompile { stage: "link", log }
+            })?;
             gl.attach_shader(id, vertex);
             gl.attach_shader(id, fragment);
             gl.link_program(id);
-            if !gl.get_program_link_status(id) {
-                log(&gl.get_program_info_log(id));
-                panic!();
-            }
+            let linked = gl.get_program_link_status(id);
 
             // delete the shaders as they're linked into our program now and no longer necessary
+            gl.delete_shader(vertex);
+            gl.delete_shader(fragment);
+            if !linked {
+                let log = gl.get_program_info_log(id);
+                gl.delete_program(id);
+                return Err(FractalError::ShaderCompile { stage: "link", log });
+            }
+
             shader = Shader { id }
         }
 
         //log("Shaders Compiled");
 
-        shader
+        Ok(shader)
     }
 
     #[allow(non_snake_case)]
@@ -97,6 +130,12 @@ impl Shader {
         );
     }
 
+    #[allow(non_snake_case)]
+    /// ------------------------------------------------------------------------
+    pub unsafe fn setVec2(&self, gl: &glow::Context, name: &str, x: f32, y: f32) {
+        gl.uniform_2_f32(gl.get_uniform_location(self.id, name), x, y);
+    }
+
     #[allow(non_snake_case)]
     /// ------------------------------------------------------------------------
     pub unsafe fn setVec3(&self, gl: &glow::Context, name: &str, x: f32, y: f32, z: f32) {
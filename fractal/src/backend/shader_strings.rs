@@ -29,9 +29,9 @@ void main()
 pub static CONSOLE_NO_BG_FS: &str = r#"#version 330 core
 out vec4 FragColor;
 
-in vec3 ourColor;
+in vec4 ourColor;
 in vec2 TexCoord;
-in vec3 ourBackground;
+in vec4 ourBackground;
 
 // texture sampler
 uniform sampler2D texture1;
@@ -40,19 +40,19 @@ void main()
 {
     vec4 original = texture(texture1, TexCoord);
     if (original.r < 0.1f || original.g < 0.1f || original.b < 0.1f) discard;
-    vec4 fg = original * vec4(ourColor, 1.f);
+    vec4 fg = original * ourColor;
 	FragColor = fg;
 }
 "#;
 
 pub static CONSOLE_NO_BG_VS: &str = r#"#version 330 core
 layout (location = 0) in vec3 aPos;
-layout (location = 1) in vec3 aColor;
-layout (location = 2) in vec3 bColor;
+layout (location = 1) in vec4 aColor;
+layout (location = 2) in vec4 bColor;
 layout (location = 3) in vec2 aTexCoord;
 
-out vec3 ourColor;
-out vec3 ourBackground;
+out vec4 ourColor;
+out vec4 ourBackground;
 out vec2 TexCoord;
 
 void main()
@@ -66,9 +66,9 @@ void main()
 pub static CONSOLE_WITH_BG_FS: &str = r#"#version 330 core
 out vec4 FragColor;
 
-in vec3 ourColor;
+in vec4 ourColor;
 in vec2 TexCoord;
-in vec3 ourBackground;
+in vec4 ourBackground;
 
 // texture sampler
 uniform sampler2D texture1;
@@ -76,19 +76,26 @@ uniform sampler2D texture1;
 void main()
 {
     vec4 original = texture(texture1, TexCoord);
-    vec4 fg = original.r > 0.1f || original.g > 0.1f || original.b > 0.1f ? original * vec4(ourColor, 1.f) : vec4(ourBackground, 1.f);
-	FragColor = fg;
+    if (original.r > 0.1f || original.g > 0.1f || original.b > 0.1f) {
+        // Composite the glyph over its background; the result is blended with the frame
+        vec4 fg = original * ourColor;
+        float alpha = fg.a + ourBackground.a * (1.f - fg.a);
+        vec3 rgb = alpha > 0.f ? (fg.rgb * fg.a + ourBackground.rgb * ourBackground.a * (1.f - fg.a)) / alpha : vec3(0.f);
+        FragColor = vec4(rgb, alpha);
+    } else {
+        FragColor = ourBackground;
+    }
 }
 "#;
 
 pub static CONSOLE_WITH_BG_VS: &str = r#"#version 330 core
 layout (location = 0) in vec3 aPos;
-layout (location = 1) in vec3 aColor;
-layout (location = 2) in vec3 bColor;
+layout (location = 1) in vec4 aColor;
+layout (location = 2) in vec4 bColor;
 layout (location = 3) in vec2 aTexCoord;
 
-out vec3 ourColor;
-out vec3 ourBackground;
+out vec4 ourColor;
+out vec4 ourBackground;
 out vec2 TexCoord;
 
 void main()
@@ -138,3 +145,35 @@ void main()
     gl_Position = vec4(aPos.x, aPos.y, 0.0, 1.0);
 }
 "#;
+
+pub static SPRITE_FS: &str = r#"#version 330 core
+out vec4 FragColor;
+
+in vec3 ourColor;
+in vec2 TexCoord;
+
+// texture sampler
+uniform sampler2D texture1;
+
+void main()
+{
+    vec4 original = texture(texture1, TexCoord);
+    if (original.a < 0.01f) discard;
+	FragColor = original * vec4(ourColor, 1.f);
+}
+"#;
+
+pub static SPRITE_VS: &str = r#"#version 330 core
+layout (location = 0) in vec3 aPos;
+layout (location = 1) in vec3 aColor;
+layout (location = 2) in vec2 aTexCoord;
+
+out vec3 ourColor;
+out vec2 TexCoord;
+
+void main()
+{
+	gl_Position = vec4(aPos, 1.0);
+	ourColor = aColor;
+	TexCoord = vec2(aTexCoord.x, aTexCoord.y);
+}"#;
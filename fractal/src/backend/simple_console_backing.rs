@@ -1,48 +1,35 @@
-//! Backend for [SimpleConsole]
+//! OpenGL vertex buffers for [SimpleConsole]
 
 #![allow(unsafe_code)]
 
 use crate::backend::font::Font;
 use crate::backend::shader::Shader;
-use crate::backend::FractalPlatform;
-use crate::color::RGB;
+use crate::backend::tile_vertices::{
+    TileVertices, VertexUpdate, FLOATS_PER_TILE, FLOATS_PER_VERTEX,
+};
 use crate::console::Tile;
 use glow::HasContext;
-use std::mem;
+use std::mem::size_of;
 
-/// The backend for [SimpleConsole]
+/// The OpenGL buffers backing a [SimpleConsole]. Only the cells that changed since the last
+/// rebuild are re-uploaded.
 pub struct SimpleConsoleBackend {
-    vertex_buffer: Vec<f32>,
-    index_buffer: Vec<i32>,
+    vertices: TileVertices,
     vbo: u32,
     vao: u32,
     ebo: u32,
-    vertex_counter: usize,
-    index_counter: usize,
 }
 
 impl SimpleConsoleBackend {
     /// Constructor
-    pub fn new(platform: &FractalPlatform, width: usize, height: usize) -> SimpleConsoleBackend {
-        let vertex_capacity: usize = (11 * width * height) * 4;
-        let index_capacity: usize = 6 * width * height;
-        let (vbo, vao, ebo) = SimpleConsoleBackend::init_gl_for_console(&platform.platform.gl);
-        let mut result = SimpleConsoleBackend {
-            vertex_buffer: Vec::with_capacity(vertex_capacity),
-            index_buffer: Vec::with_capacity(index_capacity),
+    pub fn new(gl: &glow::Context, width: usize, height: usize) -> SimpleConsoleBackend {
+        let (vbo, vao, ebo) = SimpleConsoleBackend::init_gl_for_console(gl);
+        SimpleConsoleBackend {
+            vertices: TileVertices::new(width, height),
             vbo,
             vao,
             ebo,
-            vertex_counter: 0,
-            index_counter: 0,
-        };
-        for _ in 0..vertex_capacity {
-            result.vertex_buffer.push(0.0);
-        }
-        for _ in 0..index_capacity {
-            result.index_buffer.push(0);
         }
-        result
     }
 
     fn init_gl_for_console(gl: &glow::Context) -> (u32, u32, u32) {
@@ -58,28 +45,28 @@ impl SimpleConsoleBackend {
 
             gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
 
-            let stride = 11 * mem::size_of::<f32>() as i32;
+            let stride = (FLOATS_PER_VERTEX * size_of::<f32>()) as i32;
             // position attribute
             gl.vertex_attrib_pointer_f32(0, 3, glow::FLOAT, false, stride, 0);
             gl.enable_vertex_attrib_array(0);
             // color attribute
             gl.vertex_attrib_pointer_f32(
                 1,
-                3,
+                4,
                 glow::FLOAT,
                 false,
                 stride,
-                (3 * mem::size_of::<f32>()) as i32,
+                (3 * size_of::<f32>()) as i32,
             );
             gl.enable_vertex_attrib_array(1);
             // bgcolor attribute
             gl.vertex_attrib_pointer_f32(
                 2,
-                3,
+                4,
                 glow::FLOAT,
                 false,
                 stride,
-                (6 * mem::size_of::<f32>()) as i32,
+                (7 * size_of::<f32>()) as i32,
             );
             gl.enable_vertex_attrib_array(2);
             // texture coord attribute
@@ -89,7 +76,7 @@ impl SimpleConsoleBackend {
                 glow::FLOAT,
                 false,
                 stride,
-                (9 * mem::size_of::<f32>()) as i32,
+                (11 * size_of::<f32>()) as i32,
             );
             gl.enable_vertex_attrib_array(3);
 
@@ -122,137 +109,55 @@ impl SimpleConsoleBackend {
         (vbo, vao, ebo)
     }
 
-    /// Helper function to add all the elements required by the shader for a given point.
+    /// Rebuilds the OpenGL backing buffer. Everything is uploaded the first time and whenever
+    /// the font or offset changes; otherwise only the runs of cells that changed are sent with
+    /// `buffer_sub_data`.
     #[allow(clippy::too_many_arguments)]
-    fn push_point(
-        &mut self,
-        x: f32,
-        y: f32,
-        fg: RGB,
-        bg: RGB,
-        ux: f32,
-        uy: f32,
-        offset_x: f32,
-        offset_y: f32,
-    ) {
-        self.vertex_buffer[self.vertex_counter] = x + offset_x;
-        self.vertex_buffer[self.vertex_counter + 1] = y + offset_y;
-        self.vertex_buffer[self.vertex_counter + 2] = 0.0f32;
-        self.vertex_buffer[self.vertex_counter + 3] = fg.rf();
-        self.vertex_buffer[self.vertex_counter + 4] = fg.gf();
-        self.vertex_buffer[self.vertex_counter + 5] = fg.bf();
-        self.vertex_buffer[self.vertex_counter + 6] = bg.rf();
-        self.vertex_buffer[self.vertex_counter + 7] = bg.gf();
-        self.vertex_buffer[self.vertex_counter + 8] = bg.bf();
-        self.vertex_buffer[self.vertex_counter + 9] = ux;
-        self.vertex_buffer[self.vertex_counter + 10] = uy;
-        self.vertex_counter += 11;
-    }
-
-    /// Rebuilds the OpenGL backing buffer.
     pub fn rebuild_vertices(
         &mut self,
-        platform: &FractalPlatform,
+        gl: &glow::Context,
+        font: &Font,
         height: u32,
         width: u32,
         tiles: &[Tile],
         offset_x: f32,
         offset_y: f32,
     ) {
-        self.vertex_counter = 0;
-        self.index_counter = 0;
-        let glyph_size_x: f32 = 1.0f32 / 16.0f32;
-        let glyph_size_y: f32 = 1.0f32 / 16.0f32;
-
-        let step_x: f32 = 2.0f32 / width as f32;
-        let step_y: f32 = 2.0f32 / height as f32;
-
-        let mut index_count: i32 = 0;
-        let mut screen_y: f32 = -1.0f32;
-        for y in 0..height {
-            let mut screen_x: f32 = -1.0f32;
-            for x in 0..width {
-                let fg = tiles[((y * width) + x) as usize].fg;
-                let bg = tiles[((y * width) + x) as usize].bg;
-                let glyph = tiles[((y * width) + x) as usize].glyph;
-                let glyph_x = glyph % 16;
-                let glyph_y = 16 - (glyph / 16);
-
-                let glyph_left = f32::from(glyph_x) * glyph_size_x;
-                let glyph_right = f32::from(glyph_x + 1) * glyph_size_x;
-                let glyph_top = f32::from(glyph_y) * glyph_size_y;
-                let glyph_bottom = (f32::from(glyph_y) - 0.95) * glyph_size_y;
+        let update = self
+            .vertices
+            .update(font, height, width, tiles, offset_x, offset_y);
 
-                self.push_point(
-                    screen_x + step_x,
-                    screen_y + step_y,
-                    fg,
-                    bg,
-                    glyph_right,
-                    glyph_top,
-                    offset_x,
-                    offset_y,
-                );
-                self.push_point(
-                    screen_x + step_x,
-                    screen_y,
-                    fg,
-                    bg,
-                    glyph_right,
-                    glyph_bottom,
-                    offset_x,
-                    offset_y,
-                );
-                self.push_point(
-                    screen_x,
-                    screen_y,
-                    fg,
-                    bg,
-                    glyph_left,
-                    glyph_bottom,
-                    offset_x,
-                    offset_y,
-                );
-                self.push_point(
-                    screen_x,
-                    screen_y + step_y,
-                    fg,
-                    bg,
-                    glyph_left,
-                    glyph_top,
-                    offset_x,
-                    offset_y,
-                );
-
-                self.index_buffer[self.index_counter] = index_count;
-                self.index_buffer[self.index_counter + 1] = 1 + index_count;
-                self.index_buffer[self.index_counter + 2] = 3 + index_count;
-                self.index_buffer[self.index_counter + 3] = 1 + index_count;
-                self.index_buffer[self.index_counter + 4] = 2 + index_count;
-                self.index_buffer[self.index_counter + 5] = 3 + index_count;
-                self.index_counter += 6;
-
-                index_count += 4;
-                screen_x += step_x;
-            }
-            screen_y += step_y;
-        }
-
-        let gl = &platform.platform.gl;
         unsafe {
-            gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
-            gl.buffer_data_u8_slice(
-                glow::ARRAY_BUFFER,
-                &self.vertex_buffer.align_to::<u8>().1,
-                glow::STATIC_DRAW,
-            );
-
-            gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(self.ebo));
-            gl.buffer_data_u8_slice(
-                glow::ELEMENT_ARRAY_BUFFER,
-                &self.index_buffer.align_to::<u8>().1,
-                glow::STATIC_DRAW,
-            );
+            match update {
+                VertexUpdate::None => {}
+                VertexUpdate::Full => {
+                    gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
+                    gl.buffer_data_u8_slice(
+                        glow::ARRAY_BUFFER,
+                        self.vertices.vertex_buffer.align_to::<u8>().1,
+                        glow::DYNAMIC_DRAW,
+                    );
+
+                    gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(self.ebo));
+                    gl.buffer_data_u8_slice(
+                        glow::ELEMENT_ARRAY_BUFFER,
+                        self.vertices.index_buffer.align_to::<u8>().1,
+                        glow::STATIC_DRAW,
+                    );
+                }
+                VertexUpdate::Partial(ranges) => {
+                    gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
+                    for range in ranges {
+                        let start = range.start * FLOATS_PER_TILE;
+                        let end = range.end * FLOATS_PER_TILE;
+                        gl.buffer_sub_data_u8_slice(
+                            glow::ARRAY_BUFFER,
+                            (start * size_of::<f32>()) as i32,
+                            self.vertices.vertex_buffer[start..end].align_to::<u8>().1,
+                        );
+                    }
+                }
+            }
         }
     }
 
@@ -261,14 +166,16 @@ impl SimpleConsoleBackend {
         &mut self,
         font: &Font,
         shader: &Shader,
-        platform: &FractalPlatform,
+        gl: &glow::Context,
         width: u32,
         height: u32,
     ) {
-        let gl = &platform.platform.gl;
         unsafe {
+            gl.enable(glow::BLEND);
+            gl.blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
+
             // bind Texture
-            font.bind_texture(platform);
+            gl.bind_texture(glow::TEXTURE_2D, font.texture_id);
 
             // render container
             shader.useProgram(gl);
@@ -281,6 +188,8 @@ impl SimpleConsoleBackend {
                 glow::UNSIGNED_INT,
                 0,
             );
+
+            gl.disable(glow::BLEND);
         }
     }
 }
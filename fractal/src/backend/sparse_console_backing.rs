@@ -0,0 +1,181 @@
+//! OpenGL vertex buffers for [SparseConsole]
+
+#![allow(unsafe_code)]
+
+use crate::backend::font::Font;
+use crate::backend::shader::Shader;
+use crate::backend::tile_vertices::{vertex_data, FLOATS_PER_VERTEX};
+use crate::sparse_console::SparseTile;
+use glow::HasContext;
+use std::mem::size_of;
+
+/// The OpenGL buffers backing a [SparseConsole]. Only the tiles that were set get a quad, so
+/// the buffers are rebuilt to size every time.
+pub struct SparseConsoleBackend {
+    vertex_buffer: Vec<f32>,
+    index_buffer: Vec<i32>,
+    vbo: u32,
+    vao: u32,
+    ebo: u32,
+}
+
+impl SparseConsoleBackend {
+    /// Constructor
+    pub fn new(gl: &glow::Context) -> SparseConsoleBackend {
+        let (vbo, vao, ebo) = SparseConsoleBackend::init_gl_for_console(gl);
+        SparseConsoleBackend {
+            vertex_buffer: Vec::new(),
+            index_buffer: Vec::new(),
+            vbo,
+            vao,
+            ebo,
+        }
+    }
+
+    fn init_gl_for_console(gl: &glow::Context) -> (u32, u32, u32) {
+        let (vbo, vao, ebo);
+
+        unsafe {
+            // Generate buffers and arrays, as well as attributes.
+            vao = gl.create_vertex_array().unwrap();
+            vbo = gl.create_buffer().unwrap();
+            ebo = gl.create_buffer().unwrap();
+
+            gl.bind_vertex_array(Some(vao));
+
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
+
+            let stride = (FLOATS_PER_VERTEX * size_of::<f32>()) as i32;
+            // position attribute
+            gl.vertex_attrib_pointer_f32(0, 3, glow::FLOAT, false, stride, 0);
+            gl.enable_vertex_attrib_array(0);
+            // color attribute
+            gl.vertex_attrib_pointer_f32(
+                1,
+                4,
+                glow::FLOAT,
+                false,
+                stride,
+                (3 * size_of::<f32>()) as i32,
+            );
+            gl.enable_vertex_attrib_array(1);
+            // bgcolor attribute
+            gl.vertex_attrib_pointer_f32(
+                2,
+                4,
+                glow::FLOAT,
+                false,
+                stride,
+                (7 * size_of::<f32>()) as i32,
+            );
+            gl.enable_vertex_attrib_array(2);
+            // texture coord attribute
+            gl.vertex_attrib_pointer_f32(
+                3,
+                2,
+                glow::FLOAT,
+                false,
+                stride,
+                (11 * size_of::<f32>()) as i32,
+            );
+            gl.enable_vertex_attrib_array(3);
+        };
+
+        (vbo, vao, ebo)
+    }
+
+    /// Rebuilds the OpenGL backing buffer.
+    #[allow(clippy::too_many_arguments)]
+    pub fn rebuild_vertices(
+        &mut self,
+        gl: &glow::Context,
+        font: &Font,
+        height: u32,
+        width: u32,
+        tiles: &[SparseTile],
+        offset_x: f32,
+        offset_y: f32,
+    ) {
+        self.vertex_buffer.clear();
+        self.index_buffer.clear();
+
+        let step_x: f32 = 2.0f32 / width as f32;
+        let step_y: f32 = 2.0f32 / height as f32;
+
+        let mut index_count: i32 = 0;
+        for tile in tiles {
+            let x = tile.idx as u32 % width;
+            let y = tile.idx as u32 / width;
+            let screen_x = (step_x * x as f32) - 1.0 + offset_x;
+            let screen_y = (step_y * y as f32) - 1.0 + offset_y;
+            let (glyph_left, glyph_top, glyph_right, glyph_bottom) = font.glyph_uv(tile.glyph);
+
+            let corners = [
+                (screen_x + step_x, screen_y + step_y, glyph_right, glyph_top),
+                (screen_x + step_x, screen_y, glyph_right, glyph_bottom),
+                (screen_x, screen_y, glyph_left, glyph_bottom),
+                (screen_x, screen_y + step_y, glyph_left, glyph_top),
+            ];
+            for (x, y, ux, uy) in corners.iter() {
+                self.vertex_buffer
+                    .extend_from_slice(&vertex_data(*x, *y, tile.fg, tile.bg, *ux, *uy));
+            }
+
+            self.index_buffer.extend_from_slice(&[
+                index_count,
+                1 + index_count,
+                3 + index_count,
+                1 + index_count,
+                2 + index_count,
+                3 + index_count,
+            ]);
+
+            index_count += 4;
+        }
+
+        unsafe {
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
+            gl.buffer_data_u8_slice(
+                glow::ARRAY_BUFFER,
+                self.vertex_buffer.align_to::<u8>().1,
+                glow::STATIC_DRAW,
+            );
+
+            gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(self.ebo));
+            gl.buffer_data_u8_slice(
+                glow::ELEMENT_ARRAY_BUFFER,
+                self.index_buffer.align_to::<u8>().1,
+                glow::STATIC_DRAW,
+            );
+        }
+    }
+
+    /// Draws the console to the OpenGL context
+    pub fn gl_draw(&mut self, font: &Font, shader: &Shader, gl: &glow::Context) {
+        if self.index_buffer.is_empty() {
+            return;
+        }
+
+        unsafe {
+            gl.enable(glow::BLEND);
+            gl.blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
+
+            // bind Texture
+            gl.bind_texture(glow::TEXTURE_2D, font.texture_id);
+
+            // render container
+            shader.useProgram(gl);
+            gl.bind_vertex_array(Some(self.vao));
+            gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(self.ebo));
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
+            gl.draw_elements(
+                glow::TRIANGLES,
+                self.index_buffer.len() as i32,
+                glow::UNSIGNED_INT,
+                0,
+            );
+
+            gl.disable(glow::BLEND);
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! OpenGL vertex buffers for [SpriteConsole]
+
+#![allow(unsafe_code)]
+
+use crate::backend::shader::Shader;
+use crate::backend::sprite_sheet::SpriteSheet;
+use crate::color::RGB;
+use crate::sprite_console::RenderSprite;
+use glow::HasContext;
+use std::mem::size_of;
+
+/// The OpenGL buffers backing a [SpriteConsole]
+pub struct SpriteConsoleBackend {
+    vertex_buffer: Vec<f32>,
+    index_buffer: Vec<i32>,
+    vbo: u32,
+    vao: u32,
+    ebo: u32,
+}
+
+impl SpriteConsoleBackend {
+    /// Constructor
+    pub fn new(gl: &glow::Context) -> SpriteConsoleBackend {
+        let (vbo, vao, ebo) = SpriteConsoleBackend::init_gl_for_console(gl);
+        SpriteConsoleBackend {
+            vertex_buffer: Vec::new(),
+            index_buffer: Vec::new(),
+            vbo,
+            vao,
+            ebo,
+        }
+    }
+
+    fn init_gl_for_console(gl: &glow::Context) -> (u32, u32, u32) {
+        let (vbo, vao, ebo);
+
+        unsafe {
+            // Generate buffers and arrays, as well as attributes.
+            vao = gl.create_vertex_array().unwrap();
+            vbo = gl.create_buffer().unwrap();
+            ebo = gl.create_buffer().unwrap();
+
+            gl.bind_vertex_array(Some(vao));
+
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
+
+            let stride = 8 * size_of::<f32>() as i32;
+            // position attribute
+            gl.vertex_attrib_pointer_f32(0, 3, glow::FLOAT, false, stride, 0);
+            gl.enable_vertex_attrib_array(0);
+            // tint attribute
+            gl.vertex_attrib_pointer_f32(
+                1,
+                3,
+                glow::FLOAT,
+                false,
+                stride,
+                (3 * size_of::<f32>()) as i32,
+            );
+            gl.enable_vertex_attrib_array(1);
+            // texture coord attribute
+            gl.vertex_attrib_pointer_f32(
+                2,
+                2,
+                glow::FLOAT,
+                false,
+                stride,
+                (6 * size_of::<f32>()) as i32,
+            );
+            gl.enable_vertex_attrib_array(2);
+        };
+
+        (vbo, vao, ebo)
+    }
+
+    /// Helper function to add all the elements required by the shader for a given point.
+    fn push_point(&mut self, x: f32, y: f32, tint: RGB, ux: f32, uy: f32) {
+        self.vertex_buffer.extend_from_slice(&[
+            x,
+            y,
+            0.0f32,
+            tint.rf(),
+            tint.gf(),
+            tint.bf(),
+            ux,
+            uy,
+        ]);
+    }
+
+    /// Rebuilds the OpenGL backing buffer.
+    pub fn rebuild_vertices(
+        &mut self,
+        gl: &glow::Context,
+        width: u32,
+        height: u32,
+        sheet: &SpriteSheet,
+        sprites: &[RenderSprite],
+    ) {
+        self.vertex_buffer.clear();
+        self.index_buffer.clear();
+
+        let to_screen_x = |x: i32| (x as f32 / width as f32) * 2.0 - 1.0;
+        let to_screen_y = |y: i32| 1.0 - (y as f32 / height as f32) * 2.0;
+
+        let mut index_count: i32 = 0;
+        for sprite in sprites {
+            let (u_left, v_top, u_right, v_bottom) = sheet.uv(sprite.sprite);
+            let left = to_screen_x(sprite.destination.x1);
+            let right = to_screen_x(sprite.destination.x2);
+            let top = to_screen_y(sprite.destination.y1);
+            let bottom = to_screen_y(sprite.destination.y2);
+
+            self.push_point(right, top, sprite.tint, u_right, v_top);
+            self.push_point(right, bottom, sprite.tint, u_right, v_bottom);
+            self.push_point(left, bottom, sprite.tint, u_left, v_bottom);
+            self.push_point(left, top, sprite.tint, u_left, v_top);
+
+            self.index_buffer.extend_from_slice(&[
+                index_count,
+                1 + index_count,
+                3 + index_count,
+                1 + index_count,
+                2 + index_count,
+                3 + index_count,
+            ]);
+
+            index_count += 4;
+        }
+
+        unsafe {
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
+            gl.buffer_data_u8_slice(
+                glow::ARRAY_BUFFER,
+                self.vertex_buffer.align_to::<u8>().1,
+                glow::STATIC_DRAW,
+            );
+
+            gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(self.ebo));
+            gl.buffer_data_u8_slice(
+                glow::ELEMENT_ARRAY_BUFFER,
+                self.index_buffer.align_to::<u8>().1,
+                glow::STATIC_DRAW,
+            );
+        }
+    }
+
+    /// Draws the sprites to the OpenGL context, blending them by their alpha channel
+    pub fn gl_draw(&mut self, sheet: &SpriteSheet, shader: &Shader, gl: &glow::Context) {
+        if self.index_buffer.is_empty() {
+            return;
+        }
+
+        unsafe {
+            gl.enable(glow::BLEND);
+            gl.blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
+
+            // bind Texture
+            gl.bind_texture(glow::TEXTURE_2D, sheet.texture_id);
+
+            // render container
+            shader.useProgram(gl);
+            gl.bind_vertex_array(Some(self.vao));
+            gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(self.ebo));
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
+            gl.draw_elements(
+                glow::TRIANGLES,
+                self.index_buffer.len() as i32,
+                glow::UNSIGNED_INT,
+                0,
+            );
+
+            gl.disable(glow::BLEND);
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! This module contains the SpriteSheet struct and implementations
+
+use crate::backend::font::Font;
+use crate::error::FractalError;
+use crate::geometry::Rect;
+use image::GenericImageView;
+use std::collections::HashMap;
+
+/// A named rectangle of a sprite sheet
+#[derive(PartialEq, Clone, Debug)]
+pub struct Sprite {
+    /// Name of the sprite
+    pub name: String,
+    /// Where the sprite is on the sheet, in pixels from the top-left
+    pub sheet_location: Rect,
+}
+
+/// FRACTAL's representation of an image holding sprites of any size, unlike a font's grid of
+/// equal tiles.
+#[derive(PartialEq, Clone, Debug)]
+pub struct SpriteSheet {
+    /// Name of the file
+    pub backing_file: String,
+    /// Width of the image in pixels
+    pub width: u32,
+    /// Height of the image in pixels
+    pub height: u32,
+    /// The sprites on the sheet, in the order they were added
+    pub sprites: Vec<Sprite>,
+
+    /// Texture handle, set by the backend once the sheet is loaded
+    pub texture_id: Option<u32>,
+
+    names: HashMap<String, usize>,
+}
+
+impl SpriteSheet {
+    /// Loads a sprite sheet image to obtain its width and height. Sprites are added with
+    /// add_sprite. Fails if the file is missing or isn't an image.
+    pub fn load<S: ToString>(filename: S) -> Result<SpriteSheet, FractalError> {
+        let img = Font::load_image(&filename.to_string())?;
+        Ok(SpriteSheet {
+            backing_file: filename.to_string(),
+            width: img.width(),
+            height: img.height(),
+            sprites: Vec::new(),
+            texture_id: None,
+            names: HashMap::new(),
+        })
+    }
+
+    /// Adds a named sprite covering the given rectangle of the sheet, in pixels from the
+    /// top-left.
+    pub fn add_sprite<S: ToString>(mut self, name: S, sheet_location: Rect) -> SpriteSheet {
+        self.names.insert(name.to_string(), self.sprites.len());
+        self.sprites.push(Sprite {
+            name: name.to_string(),
+            sheet_location,
+        });
+        self
+    }
+
+    /// Adds a grid of equally sized sprites, named by the given prefix and their index (left to
+    /// right, then top to bottom). Handy for tilesets.
+    pub fn add_grid<S: ToString>(
+        mut self,
+        prefix: S,
+        tile_width: u32,
+        tile_height: u32,
+    ) -> SpriteSheet {
+        let mut n = 0;
+        for y in 0..self.height / tile_height {
+            for x in 0..self.width / tile_width {
+                self = self.add_sprite(
+                    format!("{}{}", prefix.to_string(), n),
+                    Rect::new(
+                        (x * tile_width) as i32,
+                        (y * tile_height) as i32,
+                        tile_width as i32,
+                        tile_height as i32,
+                    ),
+                );
+                n += 1;
+            }
+        }
+        self
+    }
+
+    /// Looks up a sprite's index by name
+    pub fn sprite_index(&self, name: &str) -> Option<usize> {
+        self.names.get(name).cloned()
+    }
+
+    /// Texture coordinates of a sprite as (left, top, right, bottom), with the origin at the
+    /// bottom-left of the sheet like OpenGL expects.
+    pub fn uv(&self, sprite: usize) -> (f32, f32, f32, f32) {
+        let loc = self.sprites[sprite].sheet_location;
+        let w = self.width as f32;
+        let h = self.height as f32;
+        (
+            loc.x1 as f32 / w,
+            1.0 - loc.y1 as f32 / h,
+            loc.x2 as f32 / w,
+            1.0 - loc.y2 as f32 / h,
+        )
+    }
+}
@@ -0,0 +1,677 @@
+//! A backend that draws the console stack into the terminal it was started from, using 24-bit
+//! ANSI colors. Useful for playing over SSH. Select it with `FRACTAL_BACKEND=terminal`, or
+//! construct it directly with `Fractal::init_with_backend`.
+//!
+//! Each terminal cell shows one cell of the first console that was created; other consoles are
+//! scaled onto the same grid. Sub-cell offsets and post-processing are not supported, and fancy
+//! glyphs are snapped to the nearest cell without rotation or scale. Sprites are not drawn, and
+//! glyphs past the 256 CP437 characters show as '?'.
+
+use crate::backend::font::Font;
+use crate::backend::post_process::PostEffect;
+use crate::backend::sprite_sheet::SpriteSheet;
+use crate::backend::{Backend, BackendEvent, SHADER_NO_BG};
+use crate::codepage437::to_char;
+use crate::color;
+use crate::color::{RGB, RGBA};
+use crate::console::Tile;
+use crate::error::FractalError;
+use crate::fancy_console::FancyTile;
+use crate::sparse_console::SparseTile;
+use crate::sprite_console::RenderSprite;
+use crate::viewport::Viewport;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent};
+use crossterm::style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor};
+use crossterm::{cursor, event, queue, terminal};
+use glutin::event::{MouseButton, VirtualKeyCode};
+use image::{Rgba, RgbaImage};
+use std::any::Any;
+use std::convert::TryFrom;
+use std::io::{stdout, Write};
+use std::time::Duration;
+
+/// How long to wait for input each frame, which also caps the frame rate
+const FRAME_WAIT_MS: u64 = 16;
+
+/// A single character cell in the terminal
+#[derive(PartialEq, Copy, Clone)]
+struct Cell {
+    glyph: u16,
+    fg: RGB,
+    bg: RGB,
+}
+
+impl Cell {
+    /// Draws a tile over the cell. Colors that aren't opaque are blended with what the cell
+    /// already shows, and a blank glyph over a see-through background keeps the glyph below.
+    fn draw(&mut self, glyph: u16, fg: RGBA, bg: RGBA, no_bg: bool) {
+        let blank = glyph == 0 || glyph == 32;
+        if !no_bg {
+            self.bg = bg.blend_onto(self.bg);
+            if blank {
+                if bg.a == 255 {
+                    self.glyph = glyph;
+                } else {
+                    self.fg = bg.blend_onto(self.fg);
+                }
+                return;
+            }
+        }
+        if !blank && fg.a > 0 {
+            self.glyph = glyph;
+            self.fg = fg.blend_onto(self.bg);
+        }
+    }
+}
+
+impl Default for Cell {
+    fn default() -> Cell {
+        Cell {
+            glyph: 32,
+            fg: color::WHITE,
+            bg: color::BLACK,
+        }
+    }
+}
+
+/// A copy of a simple console's tiles, taken when it was last rebuilt
+struct TileBacking {
+    width: u32,
+    height: u32,
+    tiles: Vec<Tile>,
+}
+
+/// A copy of a sparse console's tiles, taken when it was last rebuilt
+struct SparseBacking {
+    width: u32,
+    height: u32,
+    tiles: Vec<SparseTile>,
+}
+
+/// A copy of a fancy console's glyphs, taken when it was last rebuilt
+struct FancyBacking {
+    width: u32,
+    height: u32,
+    tiles: Vec<FancyTile>,
+}
+
+/// ANSI terminal rendering backend
+pub struct TerminalBackend {
+    /// Width of the context in pixels, used to report mouse positions the way a window would
+    pub width_pixels: u32,
+    /// Height of the context in pixels
+    pub height_pixels: u32,
+    grid_width: u32,
+    grid_height: u32,
+    terminal_size: (u16, u16),
+    cells: Vec<Cell>,
+    presented: Vec<Cell>,
+    backings: Vec<TileBacking>,
+    sparse_backings: Vec<SparseBacking>,
+    fancy_backings: Vec<FancyBacking>,
+}
+
+impl TerminalBackend {
+    /// Switches the terminal into raw mode on the alternate screen, ready to draw. The terminal
+    /// is restored when the backend is dropped, or straight away if it can't be set up.
+    pub fn new(width_pixels: u32, height_pixels: u32) -> Result<TerminalBackend, FractalError> {
+        terminal::enable_raw_mode().map_err(|e| FractalError::Window(e.to_string()))?;
+        // Built before the rest of the setup, so dropping it on failure leaves raw mode again
+        let backend = TerminalBackend {
+            width_pixels,
+            height_pixels,
+            grid_width: 0,
+            grid_height: 0,
+            terminal_size: terminal::size().unwrap_or((80, 25)),
+            cells: Vec::new(),
+            presented: Vec::new(),
+            backings: Vec::new(),
+            sparse_backings: Vec::new(),
+            fancy_backings: Vec::new(),
+        };
+
+        let mut out = stdout();
+        queue!(
+            out,
+            terminal::EnterAlternateScreen,
+            event::EnableMouseCapture,
+            cursor::Hide,
+            terminal::Clear(terminal::ClearType::All)
+        )
+        .map_err(|e| FractalError::Window(e.to_string()))?;
+        out.flush()?;
+
+        Ok(backend)
+    }
+
+    /// The first console that is created decides the terminal grid
+    fn claim_grid(&mut self, width: u32, height: u32) {
+        if self.grid_width == 0 || self.grid_height == 0 {
+            self.grid_width = width;
+            self.grid_height = height;
+            self.cells = vec![Cell::default(); (width * height) as usize];
+        }
+    }
+
+    /// Converts a terminal cell to the pixel position at its center
+    fn cell_to_pixels(&self, column: u16, row: u16) -> (i32, i32) {
+        if self.grid_width == 0 || self.grid_height == 0 {
+            return (0, 0);
+        }
+        let cell_w = self.width_pixels / self.grid_width;
+        let cell_h = self.height_pixels / self.grid_height;
+        (
+            (u32::from(column) * cell_w + cell_w / 2) as i32,
+            (u32::from(row) * cell_h + cell_h / 2) as i32,
+        )
+    }
+
+    /// Writes every cell that changed since the last frame
+    fn present(&mut self) -> crossterm::Result<()> {
+        let mut out = stdout();
+        let visible_w = u32::min(self.grid_width, u32::from(self.terminal_size.0));
+        let visible_h = u32::min(self.grid_height, u32::from(self.terminal_size.1));
+        let mut last_colors: Option<(RGB, RGB)> = None;
+
+        for y in 0..visible_h {
+            let mut cursor_x: Option<u32> = None;
+            for x in 0..visible_w {
+                let idx = (y * self.grid_width + x) as usize;
+                let cell = self.cells[idx];
+                if self.presented.get(idx) == Some(&cell) {
+                    continue;
+                }
+
+                if cursor_x != Some(x) {
+                    queue!(out, cursor::MoveTo(x as u16, y as u16))?;
+                }
+                if last_colors != Some((cell.fg, cell.bg)) {
+                    queue!(
+                        out,
+                        SetForegroundColor(to_ansi(cell.fg)),
+                        SetBackgroundColor(to_ansi(cell.bg))
+                    )?;
+                    last_colors = Some((cell.fg, cell.bg));
+                }
+                let glyph = if cell.glyph == 0 {
+                    ' '
+                } else {
+                    u8::try_from(cell.glyph).map(to_char).unwrap_or('?')
+                };
+                queue!(out, Print(glyph))?;
+                cursor_x = Some(x + 1);
+            }
+        }
+
+        out.flush()?;
+        self.presented = self.cells.clone();
+        Ok(())
+    }
+}
+
+impl Drop for TerminalBackend {
+    fn drop(&mut self) {
+        let mut out = stdout();
+        let _ = queue!(
+            out,
+            ResetColor,
+            cursor::Show,
+            event::DisableMouseCapture,
+            terminal::LeaveAlternateScreen
+        );
+        let _ = out.flush();
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Converts a color to its 24-bit ANSI equivalent
+fn to_ansi(col: RGB) -> Color {
+    Color::Rgb {
+        r: col.r,
+        g: col.g,
+        b: col.b,
+    }
+}
+
+/// Translates a terminal key press into the key a window would have reported, along with
+/// whether it implies shift.
+fn translate_key(code: KeyCode) -> Option<(VirtualKeyCode, bool)> {
+    let key = match code {
+        KeyCode::Backspace => VirtualKeyCode::Back,
+        KeyCode::Enter => VirtualKeyCode::Return,
+        KeyCode::Left => VirtualKeyCode::Left,
+        KeyCode::Right => VirtualKeyCode::Right,
+        KeyCode::Up => VirtualKeyCode::Up,
+        KeyCode::Down => VirtualKeyCode::Down,
+        KeyCode::Home => VirtualKeyCode::Home,
+        KeyCode::End => VirtualKeyCode::End,
+        KeyCode::PageUp => VirtualKeyCode::PageUp,
+        KeyCode::PageDown => VirtualKeyCode::PageDown,
+        KeyCode::Tab => VirtualKeyCode::Tab,
+        KeyCode::BackTab => return Some((VirtualKeyCode::Tab, true)),
+        KeyCode::Delete => VirtualKeyCode::Delete,
+        KeyCode::Insert => VirtualKeyCode::Insert,
+        KeyCode::Esc => VirtualKeyCode::Escape,
+        KeyCode::F(n) => match n {
+            1 => VirtualKeyCode::F1,
+            2 => VirtualKeyCode::F2,
+            3 => VirtualKeyCode::F3,
+            4 => VirtualKeyCode::F4,
+            5 => VirtualKeyCode::F5,
+            6 => VirtualKeyCode::F6,
+            7 => VirtualKeyCode::F7,
+            8 => VirtualKeyCode::F8,
+            9 => VirtualKeyCode::F9,
+            10 => VirtualKeyCode::F10,
+            11 => VirtualKeyCode::F11,
+            12 => VirtualKeyCode::F12,
+            _ => return None,
+        },
+        KeyCode::Char(c) => return translate_char(c),
+        KeyCode::Null => return None,
+    };
+    Some((key, false))
+}
+
+/// Translates a crossterm mouse button into glutin's
+fn translate_button(button: event::MouseButton) -> MouseButton {
+    match button {
+        event::MouseButton::Left => MouseButton::Left,
+        event::MouseButton::Right => MouseButton::Right,
+        event::MouseButton::Middle => MouseButton::Middle,
+    }
+}
+
+/// Translates a typed character into the key that produces it on a US keyboard
+fn translate_char(c: char) -> Option<(VirtualKeyCode, bool)> {
+    let shift = c.is_ascii_uppercase();
+    let key = match c.to_ascii_lowercase() {
+        'a' => VirtualKeyCode::A,
+        'b' => VirtualKeyCode::B,
+        'c' => VirtualKeyCode::C,
+        'd' => VirtualKeyCode::D,
+        'e' => VirtualKeyCode::E,
+        'f' => VirtualKeyCode::F,
+        'g' => VirtualKeyCode::G,
+        'h' => VirtualKeyCode::H,
+        'i' => VirtualKeyCode::I,
+        'j' => VirtualKeyCode::J,
+        'k' => VirtualKeyCode::K,
+        'l' => VirtualKeyCode::L,
+        'm' => VirtualKeyCode::M,
+        'n' => VirtualKeyCode::N,
+        'o' => VirtualKeyCode::O,
+        'p' => VirtualKeyCode::P,
+        'q' => VirtualKeyCode::Q,
+        'r' => VirtualKeyCode::R,
+        's' => VirtualKeyCode::S,
+        't' => VirtualKeyCode::T,
+        'u' => VirtualKeyCode::U,
+        'v' => VirtualKeyCode::V,
+        'w' => VirtualKeyCode::W,
+        'x' => VirtualKeyCode::X,
+        'y' => VirtualKeyCode::Y,
+        'z' => VirtualKeyCode::Z,
+        '0' => VirtualKeyCode::Key0,
+        '1' => VirtualKeyCode::Key1,
+        '2' => VirtualKeyCode::Key2,
+        '3' => VirtualKeyCode::Key3,
+        '4' => VirtualKeyCode::Key4,
+        '5' => VirtualKeyCode::Key5,
+        '6' => VirtualKeyCode::Key6,
+        '7' => VirtualKeyCode::Key7,
+        '8' => VirtualKeyCode::Key8,
+        '9' => VirtualKeyCode::Key9,
+        ' ' => VirtualKeyCode::Space,
+        '\'' => VirtualKeyCode::Apostrophe,
+        ',' => VirtualKeyCode::Comma,
+        '-' => VirtualKeyCode::Minus,
+        '.' => VirtualKeyCode::Period,
+        '/' => VirtualKeyCode::Slash,
+        ';' => VirtualKeyCode::Semicolon,
+        '=' => VirtualKeyCode::Equals,
+        '[' => VirtualKeyCode::LBracket,
+        '\\' => VirtualKeyCode::Backslash,
+        ']' => VirtualKeyCode::RBracket,
+        '`' => VirtualKeyCode::Grave,
+        '"' => return Some((VirtualKeyCode::Apostrophe, true)),
+        '<' => return Some((VirtualKeyCode::Comma, true)),
+        '_' => return Some((VirtualKeyCode::Minus, true)),
+        '>' => return Some((VirtualKeyCode::Period, true)),
+        '?' => return Some((VirtualKeyCode::Slash, true)),
+        ':' => return Some((VirtualKeyCode::Semicolon, true)),
+        '+' => return Some((VirtualKeyCode::Equals, true)),
+        _ => return None,
+    };
+    Some((key, shift))
+}
+
+impl Backend for TerminalBackend {
+    /// Fonts aren't used by the terminal, glyphs are mapped back to unicode instead.
+    fn setup_font(&mut self, _font: &mut Font) -> Result<(), FractalError> {
+        Ok(())
+    }
+
+    fn reload_font(&mut self, _font: &Font) -> Result<(), FractalError> {
+        Ok(())
+    }
+
+    fn create_tile_backing(&mut self, width: u32, height: u32) -> usize {
+        self.claim_grid(width, height);
+        self.backings.push(TileBacking {
+            width,
+            height,
+            tiles: Vec::new(),
+        });
+        self.backings.len() - 1
+    }
+
+    fn rebuild_tiles(
+        &mut self,
+        backing: usize,
+        _font: &Font,
+        width: u32,
+        height: u32,
+        tiles: &[Tile],
+        _offset_x: f32,
+        _offset_y: f32,
+    ) {
+        let target = &mut self.backings[backing];
+        target.width = width;
+        target.height = height;
+        target.tiles.clear();
+        target.tiles.extend_from_slice(tiles);
+    }
+
+    fn draw_tiles(&mut self, backing: usize, _font: &Font, shader_index: usize) {
+        let target = &self.backings[backing];
+        if target.tiles.is_empty() {
+            return;
+        }
+
+        for y in 0..self.grid_height {
+            for x in 0..self.grid_width {
+                let tx = x * target.width / self.grid_width;
+                let ty = y * target.height / self.grid_height;
+                // Tiles are stored bottom row first
+                let tile = &target.tiles[((target.height - 1 - ty) * target.width + tx) as usize];
+                let cell = &mut self.cells[(y * self.grid_width + x) as usize];
+
+                cell.draw(tile.glyph, tile.fg, tile.bg, shader_index == SHADER_NO_BG);
+            }
+        }
+    }
+
+    fn create_sparse_backing(&mut self, width: u32, height: u32) -> usize {
+        self.claim_grid(width, height);
+        self.sparse_backings.push(SparseBacking {
+            width,
+            height,
+            tiles: Vec::new(),
+        });
+        self.sparse_backings.len() - 1
+    }
+
+    fn rebuild_sparse(
+        &mut self,
+        backing: usize,
+        _font: &Font,
+        width: u32,
+        height: u32,
+        tiles: &[SparseTile],
+        _offset_x: f32,
+        _offset_y: f32,
+    ) {
+        let target = &mut self.sparse_backings[backing];
+        target.width = width;
+        target.height = height;
+        target.tiles.clear();
+        target.tiles.extend_from_slice(tiles);
+    }
+
+    fn draw_sparse(&mut self, backing: usize, _font: &Font, shader_index: usize) {
+        let target = &self.sparse_backings[backing];
+
+        for tile in &target.tiles {
+            // Tiles are stored bottom row first
+            let tx = tile.idx as u32 % target.width;
+            let ty = target.height - 1 - tile.idx as u32 / target.width;
+
+            // A console cell may cover several terminal cells, if it is coarser than the grid
+            let x_range = (tx * self.grid_width / target.width)
+                ..u32::max(
+                    (tx + 1) * self.grid_width / target.width,
+                    tx * self.grid_width / target.width + 1,
+                );
+            let y_range = (ty * self.grid_height / target.height)
+                ..u32::max(
+                    (ty + 1) * self.grid_height / target.height,
+                    ty * self.grid_height / target.height + 1,
+                );
+
+            for y in y_range {
+                for x in x_range.clone() {
+                    if x >= self.grid_width || y >= self.grid_height {
+                        continue;
+                    }
+                    let cell = &mut self.cells[(y * self.grid_width + x) as usize];
+                    cell.draw(tile.glyph, tile.fg, tile.bg, shader_index == SHADER_NO_BG);
+                }
+            }
+        }
+    }
+
+    fn create_fancy_backing(&mut self, width: u32, height: u32) -> usize {
+        self.claim_grid(width, height);
+        self.fancy_backings.push(FancyBacking {
+            width,
+            height,
+            tiles: Vec::new(),
+        });
+        self.fancy_backings.len() - 1
+    }
+
+    fn rebuild_fancy(
+        &mut self,
+        backing: usize,
+        _font: &Font,
+        width: u32,
+        height: u32,
+        tiles: &[FancyTile],
+        _offset_x: f32,
+        _offset_y: f32,
+    ) {
+        let target = &mut self.fancy_backings[backing];
+        target.width = width;
+        target.height = height;
+        target.tiles.clear();
+        target.tiles.extend_from_slice(tiles);
+    }
+
+    fn draw_fancy(&mut self, backing: usize, _font: &Font, shader_index: usize) {
+        let target = &self.fancy_backings[backing];
+
+        for tile in &target.tiles {
+            let x = (tile.x.round() * self.grid_width as f32 / target.width as f32) as i32;
+            let y = (tile.y.round() * self.grid_height as f32 / target.height as f32) as i32;
+            if x < 0 || y < 0 || x >= self.grid_width as i32 || y >= self.grid_height as i32 {
+                continue;
+            }
+
+            let cell = &mut self.cells[(y as u32 * self.grid_width + x as u32) as usize];
+            cell.draw(tile.glyph, tile.fg, tile.bg, shader_index == SHADER_NO_BG);
+        }
+    }
+
+    /// Sprites can't be shown in a terminal, so there is nothing to load.
+    fn setup_sprite_sheet(&mut self, _sheet: &mut SpriteSheet) -> Result<(), FractalError> {
+        Ok(())
+    }
+
+    fn create_sprite_backing(&mut self) -> usize {
+        0
+    }
+
+    fn rebuild_sprites(
+        &mut self,
+        _backing: usize,
+        _width: u32,
+        _height: u32,
+        _sheet: &SpriteSheet,
+        _sprites: &[RenderSprite],
+    ) {
+    }
+
+    fn draw_sprites(&mut self, _backing: usize, _sheet: &SpriteSheet) {}
+
+    fn poll_events(&mut self) -> Vec<BackendEvent> {
+        let mut events = Vec::new();
+        let mut wait = Duration::from_millis(FRAME_WAIT_MS);
+
+        while let Ok(true) = event::poll(wait) {
+            wait = Duration::from_millis(0);
+            let ev = match event::read() {
+                Ok(ev) => ev,
+                Err(_) => break,
+            };
+            match ev {
+                Event::Key(KeyEvent { code, modifiers }) => {
+                    if let Some((key, shift)) = translate_key(code) {
+                        // Terminals don't report releases, so every key is pressed and
+                        // released straight away
+                        for pressed in [true, false].iter() {
+                            events.push(BackendEvent::KeyboardInput {
+                                key,
+                                pressed: *pressed,
+                                shift: shift || modifiers.contains(KeyModifiers::SHIFT),
+                                control: modifiers.contains(KeyModifiers::CONTROL),
+                                alt: modifiers.contains(KeyModifiers::ALT),
+                            });
+                        }
+                    }
+                    if let KeyCode::Char(c) = code {
+                        events.push(BackendEvent::ReceivedCharacter(c));
+                    }
+                }
+                Event::Mouse(mouse) => match mouse {
+                    MouseEvent::Down(button, column, row, _) => {
+                        let (x, y) = self.cell_to_pixels(column, row);
+                        events.push(BackendEvent::CursorMoved { x, y });
+                        events.push(BackendEvent::MouseInput {
+                            button: translate_button(button),
+                            pressed: true,
+                        });
+                    }
+                    MouseEvent::Up(button, column, row, _) => {
+                        let (x, y) = self.cell_to_pixels(column, row);
+                        events.push(BackendEvent::CursorMoved { x, y });
+                        events.push(BackendEvent::MouseInput {
+                            button: translate_button(button),
+                            pressed: false,
+                        });
+                    }
+                    MouseEvent::Drag(_, column, row, _) => {
+                        let (x, y) = self.cell_to_pixels(column, row);
+                        events.push(BackendEvent::CursorMoved { x, y });
+                    }
+                    MouseEvent::ScrollDown(column, row, _) => {
+                        let (x, y) = self.cell_to_pixels(column, row);
+                        events.push(BackendEvent::CursorMoved { x, y });
+                        events.push(BackendEvent::MouseWheel { x: 0.0, y: -1.0 });
+                    }
+                    MouseEvent::ScrollUp(column, row, _) => {
+                        let (x, y) = self.cell_to_pixels(column, row);
+                        events.push(BackendEvent::CursorMoved { x, y });
+                        events.push(BackendEvent::MouseWheel { x: 0.0, y: 1.0 });
+                    }
+                },
+                Event::Resize(columns, rows) => {
+                    self.terminal_size = (columns, rows);
+                    // Everything has to be redrawn after the terminal reflows
+                    self.presented.clear();
+                    let _ = queue!(stdout(), terminal::Clear(terminal::ClearType::All));
+                }
+            }
+        }
+
+        events
+    }
+
+    /// Terminals can't run shaders, so effects get a handle and are never drawn.
+    fn create_post_effect(&mut self, _fragment_source: &str) -> Result<usize, FractalError> {
+        Ok(0)
+    }
+
+    fn reload_post_effect(
+        &mut self,
+        _handle: usize,
+        _fragment_source: &str,
+    ) -> Result<(), FractalError> {
+        Ok(())
+    }
+
+    /// The terminal grid is fixed by the first console, so this only records the new size.
+    fn resize(&mut self, width: u32, height: u32) {
+        self.width_pixels = width;
+        self.height_pixels = height;
+    }
+
+    /// The terminal is always drawn in full, one cell per character.
+    fn set_viewport(&mut self, _viewport: Viewport) {}
+
+    fn begin_frame(&mut self, _post_process: bool) {
+        for cell in self.cells.iter_mut() {
+            *cell = Cell::default();
+        }
+    }
+
+    fn end_frame(
+        &mut self,
+        _effects: &[PostEffect],
+        _post_scanlines: bool,
+        _post_screenburn: bool,
+    ) {
+        self.present_frame();
+    }
+
+    fn present_frame(&mut self) {
+        // There is nowhere to report a failed write to; the next frame tries again.
+        if self.present().is_err() {
+            self.presented.clear();
+        }
+    }
+
+    /// The terminal has no pixels to read back, so this produces a picture of the grid with
+    /// each cell filled by its background color (or foreground, if it holds a visible glyph).
+    fn capture_frame(
+        &mut self,
+        _effects: &[PostEffect],
+        _post_scanlines: bool,
+        _post_screenburn: bool,
+    ) -> RgbaImage {
+        let mut image = RgbaImage::new(self.width_pixels, self.height_pixels);
+        if self.grid_width == 0 || self.grid_height == 0 {
+            return image;
+        }
+        for (x, y, pixel) in image.enumerate_pixels_mut() {
+            let cx = x * self.grid_width / self.width_pixels;
+            let cy = y * self.grid_height / self.height_pixels;
+            let cell = self.cells[(cy * self.grid_width + cx) as usize];
+            let col = if cell.glyph == 0 || cell.glyph == 32 {
+                cell.bg
+            } else {
+                cell.fg
+            };
+            *pixel = Rgba([col.r, col.g, col.b, 255]);
+        }
+        image
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,191 @@
+//! CPU-side vertex data for a grid of tiles, rebuilt only where the tiles changed
+
+use crate::backend::font::Font;
+use crate::color::RGBA;
+use crate::console::Tile;
+use std::ops::Range;
+
+/// Number of floats making up one vertex: position, foreground, background and texture coords
+pub const FLOATS_PER_VERTEX: usize = 13;
+/// Number of floats making up one tile's quad
+pub const FLOATS_PER_TILE: usize = FLOATS_PER_VERTEX * 4;
+
+/// Changed runs of tiles separated by fewer unchanged tiles than this are uploaded together,
+/// since one slightly bigger upload is cheaper than two small ones.
+const MERGE_GAP: usize = 16;
+
+/// What has to be sent to the GPU after an update
+#[derive(Clone, Debug, PartialEq)]
+pub enum VertexUpdate {
+    /// Nothing changed
+    None,
+    /// The whole buffer has to be uploaded
+    Full,
+    /// Only these ranges of tiles changed
+    Partial(Vec<Range<usize>>),
+}
+
+/// Vertices for a grid of tiles, along with the tiles and settings they were built from so
+/// that later updates can rebuild just the cells that changed.
+pub struct TileVertices {
+    /// Vertex data, `FLOATS_PER_TILE` floats per tile, in the same order as the tiles
+    pub vertex_buffer: Vec<f32>,
+    /// Index data; it only depends on the number of tiles, so it only changes with the size
+    pub index_buffer: Vec<i32>,
+    tiles: Vec<Tile>,
+    font: Option<(Option<u32>, u32, u32)>,
+    offset: (f32, f32),
+}
+
+impl TileVertices {
+    /// Allocates the buffers for a width x height grid
+    pub fn new(width: usize, height: usize) -> TileVertices {
+        let num_tiles = width * height;
+        TileVertices {
+            vertex_buffer: vec![0.0; FLOATS_PER_TILE * num_tiles],
+            index_buffer: quad_indices(num_tiles),
+            tiles: Vec::new(),
+            font: None,
+            offset: (0.0, 0.0),
+        }
+    }
+
+    /// Brings the vertices up to date with the tiles, rebuilding only the cells that changed
+    /// since the last update. A new font or offset rebuilds everything.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update(
+        &mut self,
+        font: &Font,
+        height: u32,
+        width: u32,
+        tiles: &[Tile],
+        offset_x: f32,
+        offset_y: f32,
+    ) -> VertexUpdate {
+        let num_tiles = (width * height) as usize;
+        let font_key = (font.texture_id, font.columns(), font.rows());
+        let full = self.tiles.len() != num_tiles
+            || self.font != Some(font_key)
+            || (self.offset.0 - offset_x).abs() > f32::EPSILON
+            || (self.offset.1 - offset_y).abs() > f32::EPSILON;
+
+        if full {
+            if self.index_buffer.len() != 6 * num_tiles {
+                self.index_buffer = quad_indices(num_tiles);
+            }
+            self.vertex_buffer.resize(FLOATS_PER_TILE * num_tiles, 0.0);
+            for (idx, tile) in tiles.iter().enumerate().take(num_tiles) {
+                self.build_tile(font, width, height, idx, tile, offset_x, offset_y);
+            }
+            self.tiles.clear();
+            self.tiles.extend_from_slice(&tiles[..num_tiles]);
+            self.font = Some(font_key);
+            self.offset = (offset_x, offset_y);
+            return VertexUpdate::Full;
+        }
+
+        let ranges = changed_ranges(&self.tiles, &tiles[..num_tiles]);
+        if ranges.is_empty() {
+            return VertexUpdate::None;
+        }
+        for range in &ranges {
+            for idx in range.clone() {
+                self.build_tile(font, width, height, idx, &tiles[idx], offset_x, offset_y);
+            }
+            self.tiles[range.clone()].copy_from_slice(&tiles[range.clone()]);
+        }
+        VertexUpdate::Partial(ranges)
+    }
+
+    /// Writes the four vertices of one tile's quad
+    #[allow(clippy::too_many_arguments)]
+    fn build_tile(
+        &mut self,
+        font: &Font,
+        width: u32,
+        height: u32,
+        idx: usize,
+        tile: &Tile,
+        offset_x: f32,
+        offset_y: f32,
+    ) {
+        let step_x: f32 = 2.0f32 / width as f32;
+        let step_y: f32 = 2.0f32 / height as f32;
+        let screen_x = (step_x * (idx as u32 % width) as f32) - 1.0 + offset_x;
+        let screen_y = (step_y * (idx as u32 / width) as f32) - 1.0 + offset_y;
+        let (glyph_left, glyph_top, glyph_right, glyph_bottom) = font.glyph_uv(tile.glyph);
+
+        let corners = [
+            (screen_x + step_x, screen_y + step_y, glyph_right, glyph_top),
+            (screen_x + step_x, screen_y, glyph_right, glyph_bottom),
+            (screen_x, screen_y, glyph_left, glyph_bottom),
+            (screen_x, screen_y + step_y, glyph_left, glyph_top),
+        ];
+
+        let start = idx * FLOATS_PER_TILE;
+        let quad = &mut self.vertex_buffer[start..start + FLOATS_PER_TILE];
+        for (vertex, (x, y, ux, uy)) in quad.chunks_mut(FLOATS_PER_VERTEX).zip(corners.iter()) {
+            vertex.copy_from_slice(&vertex_data(*x, *y, tile.fg, tile.bg, *ux, *uy));
+        }
+    }
+}
+
+/// Two triangles per tile, covering its four vertices
+fn quad_indices(num_tiles: usize) -> Vec<i32> {
+    let mut index_buffer = Vec::with_capacity(6 * num_tiles);
+    for i in 0..num_tiles as i32 {
+        let index_count = i * 4;
+        index_buffer.extend_from_slice(&[
+            index_count,
+            1 + index_count,
+            3 + index_count,
+            1 + index_count,
+            2 + index_count,
+            3 + index_count,
+        ]);
+    }
+    index_buffer
+}
+
+/// Lays out all the elements required by the shader for a given point. Every tile-drawing
+/// backing builds its vertices with this, so they share the `FLOATS_PER_VERTEX` layout.
+pub fn vertex_data(
+    x: f32,
+    y: f32,
+    fg: RGBA,
+    bg: RGBA,
+    ux: f32,
+    uy: f32,
+) -> [f32; FLOATS_PER_VERTEX] {
+    [
+        x,
+        y,
+        0.0f32,
+        fg.rf(),
+        fg.gf(),
+        fg.bf(),
+        fg.af(),
+        bg.rf(),
+        bg.gf(),
+        bg.bf(),
+        bg.af(),
+        ux,
+        uy,
+    ]
+}
+
+/// Finds the runs of tiles that differ between two equally sized slices, merging runs that are
+/// close together.
+pub fn changed_ranges(old: &[Tile], new: &[Tile]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for (idx, (before, after)) in old.iter().zip(new.iter()).enumerate() {
+        if before == after {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some(last) if idx - last.end <= MERGE_GAP => last.end = idx + 1,
+            _ => ranges.push(idx..idx + 1),
+        }
+    }
+    ranges
+}
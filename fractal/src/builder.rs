@@ -0,0 +1,306 @@
+//! Declarative construction of a Fractal context: window, fonts, consoles and post-processing
+//! in one chain.
+
+use crate::backend::font::Font;
+use crate::backend::init::{init_with_hints, InitHints};
+use crate::embedding;
+use crate::error::FractalError;
+use crate::fractal::Fractal;
+use crate::simple_console::SimpleConsole;
+use crate::sparse_console::SparseConsole;
+use crate::viewport::ResizePolicy;
+use std::path::Path;
+
+/// How a font added to the builder gets loaded
+enum FontSource {
+    Bitmap,
+    TrueType(Vec<char>),
+}
+
+struct FontEntry {
+    path: String,
+    tile_size: (u32, u32),
+    source: FontSource,
+}
+
+enum ConsoleKind {
+    Simple,
+    SimpleNoBg,
+    Sparse,
+    SparseNoBg,
+}
+
+struct ConsoleEntry {
+    kind: ConsoleKind,
+    width: u32,
+    height: u32,
+    font: String,
+}
+
+/// Collects everything needed to start a game, then opens the window and sets it all up with
+/// `build`. Fonts are referred to by path; bitmap fonts are looked up in the embedded
+/// resources first, like `Font::load`.
+pub struct FractalBuilder {
+    title: String,
+    width_chars: u32,
+    height_chars: u32,
+    tile_size: Option<(u32, u32)>,
+    hints: InitHints,
+    fonts: Vec<FontEntry>,
+    consoles: Vec<ConsoleEntry>,
+    post_scanlines: Option<bool>,
+    post_effects: Vec<(String, String)>,
+    fps_cap: Option<f32>,
+    resize_policy: ResizePolicy,
+    hot_reload: bool,
+}
+
+impl Default for FractalBuilder {
+    fn default() -> Self {
+        FractalBuilder::new()
+    }
+}
+
+impl FractalBuilder {
+    /// An 80x50 window with no fonts or consoles yet
+    pub fn new() -> FractalBuilder {
+        FractalBuilder {
+            title: "Fractal".to_string(),
+            width_chars: 80,
+            height_chars: 50,
+            tile_size: None,
+            hints: InitHints::default(),
+            fonts: Vec::new(),
+            consoles: Vec::new(),
+            post_scanlines: None,
+            post_effects: Vec::new(),
+            fps_cap: None,
+            resize_policy: ResizePolicy::default(),
+            hot_reload: false,
+        }
+    }
+
+    /// A console of the given size using the embedded 8x8 terminal font
+    pub fn simple8x8(width_chars: u32, height_chars: u32) -> Self {
+        FractalBuilder::new()
+            .with_dimensions(width_chars, height_chars)
+            .with_font("resources/terminal8x8.png", 8, 8)
+            .with_simple_console(width_chars, height_chars, "resources/terminal8x8.png")
+    }
+
+    /// A console of the given size using the embedded 8x16 VGA font
+    pub fn simple8x16(width_chars: u32, height_chars: u32) -> Self {
+        FractalBuilder::new()
+            .with_dimensions(width_chars, height_chars)
+            .with_font("resources/vga8x16.png", 8, 16)
+            .with_simple_console(width_chars, height_chars, "resources/vga8x16.png")
+    }
+
+    /// Sets the window title
+    pub fn with_title<S: ToString>(mut self, title: S) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    /// Sets the window size in characters. It is multiplied by the tile size to get pixels.
+    pub fn with_dimensions(mut self, width_chars: u32, height_chars: u32) -> Self {
+        self.width_chars = width_chars;
+        self.height_chars = height_chars;
+        self
+    }
+
+    /// Sets the size of a character in pixels. Defaults to the first font's tile size.
+    pub fn with_tile_dimensions(mut self, width: u32, height: u32) -> Self {
+        self.tile_size = Some((width, height));
+        self
+    }
+
+    /// Opens a borderless window covering the primary monitor
+    pub fn with_fullscreen(mut self, fullscreen: bool) -> Self {
+        self.hints.fullscreen = fullscreen;
+        self
+    }
+
+    /// Turns vertical sync on or off; it is on by default
+    pub fn with_vsync(mut self, vsync: bool) -> Self {
+        self.hints.vsync = vsync;
+        self
+    }
+
+    /// Replaces the window and context settings wholesale
+    pub fn with_hints(mut self, hints: InitHints) -> Self {
+        self.hints = hints;
+        self
+    }
+
+    /// Adds a bitmap font with glyphs of width x height pixels, from an embedded resource or a
+    /// file.
+    pub fn with_font<S: ToString>(mut self, path: S, width: u32, height: u32) -> Self {
+        self.fonts.push(FontEntry {
+            path: path.to_string(),
+            tile_size: (width, height),
+            source: FontSource::Bitmap,
+        });
+        self
+    }
+
+    /// Adds a TrueType/OpenType font, rasterised into width x height cells. See
+    /// `Font::load_ttf`.
+    pub fn with_ttf_font<S: ToString, I: IntoIterator<Item = char>>(
+        mut self,
+        path: S,
+        width: u32,
+        height: u32,
+        extra_chars: I,
+    ) -> Self {
+        self.fonts.push(FontEntry {
+            path: path.to_string(),
+            tile_size: (width, height),
+            source: FontSource::TrueType(extra_chars.into_iter().collect()),
+        });
+        self
+    }
+
+    /// Adds a simple console on top of the ones added so far, drawn with the named font
+    pub fn with_simple_console<S: ToString>(self, width: u32, height: u32, font: S) -> Self {
+        self.with_console(ConsoleKind::Simple, width, height, font)
+    }
+
+    /// Adds a simple console that doesn't draw backgrounds, so the ones below show through
+    pub fn with_simple_console_no_bg<S: ToString>(self, width: u32, height: u32, font: S) -> Self {
+        self.with_console(ConsoleKind::SimpleNoBg, width, height, font)
+    }
+
+    /// Adds a sparse console on top of the ones added so far, drawn with the named font
+    pub fn with_sparse_console<S: ToString>(self, width: u32, height: u32, font: S) -> Self {
+        self.with_console(ConsoleKind::Sparse, width, height, font)
+    }
+
+    /// Adds a sparse console that doesn't draw backgrounds, so the ones below show through
+    pub fn with_sparse_console_no_bg<S: ToString>(self, width: u32, height: u32, font: S) -> Self {
+        self.with_console(ConsoleKind::SparseNoBg, width, height, font)
+    }
+
+    fn with_console<S: ToString>(
+        mut self,
+        kind: ConsoleKind,
+        width: u32,
+        height: u32,
+        font: S,
+    ) -> Self {
+        self.consoles.push(ConsoleEntry {
+            kind,
+            width,
+            height,
+            font: font.to_string(),
+        });
+        self
+    }
+
+    /// Turns on the scanlines post-processing effect, optionally with screen burn
+    pub fn with_post_scanlines(mut self, with_burn: bool) -> Self {
+        self.post_scanlines = Some(with_burn);
+        self
+    }
+
+    /// Adds a post-processing effect; see `Fractal::register_post_effect`
+    pub fn with_post_effect<S: ToString>(mut self, name: S, fragment_shader: &str) -> Self {
+        self.post_effects
+            .push((name.to_string(), fragment_shader.to_string()));
+        self
+    }
+
+    /// Caps the frame rate; see `Fractal::with_fps_cap`
+    pub fn with_fps_cap(mut self, fps: f32) -> Self {
+        self.fps_cap = Some(fps);
+        self
+    }
+
+    /// Chooses what happens to the consoles when the window is resized
+    pub fn with_resize_policy(mut self, policy: ResizePolicy) -> Self {
+        self.resize_policy = policy;
+        self
+    }
+
+    /// Reloads fonts loaded from disk when their files change; see `Fractal::with_hot_reload`
+    pub fn with_hot_reload(mut self, enabled: bool) -> Self {
+        self.hot_reload = enabled;
+        self
+    }
+
+    /// Checks the configuration, opens the window, then loads the fonts and creates the
+    /// consoles in the order they were added. The first console is made active.
+    pub fn build(self) -> Result<Fractal, FractalError> {
+        if self.consoles.is_empty() {
+            return Err(FractalError::NoConsoles);
+        }
+        for console in &self.consoles {
+            if !self.fonts.iter().any(|f| f.path == console.font) {
+                return Err(FractalError::UnknownFont(console.font.clone()));
+            }
+        }
+        for font in &self.fonts {
+            if !embedding::is_embedded(&font.path) && !Path::new(&font.path).exists() {
+                return Err(FractalError::MissingResource(font.path.clone()));
+            }
+        }
+
+        let tile_size = self.tile_size.unwrap_or(self.fonts[0].tile_size);
+        let mut context = init_with_hints(
+            self.width_chars * tile_size.0,
+            self.height_chars * tile_size.1,
+            &self.title,
+            &self.hints,
+        )?;
+
+        let mut font_indices = Vec::with_capacity(self.fonts.len());
+        for entry in self.fonts {
+            let font = match entry.source {
+                FontSource::Bitmap => Font::load(&entry.path, entry.tile_size)?,
+                FontSource::TrueType(chars) => Font::load_ttf(&entry.path, entry.tile_size, chars)?,
+            };
+            font_indices.push((entry.path, context.register_font(font)?));
+        }
+
+        for entry in self.consoles {
+            let font = font_indices
+                .iter()
+                .find(|(path, _)| *path == entry.font)
+                .map(|(_, index)| *index)
+                .unwrap();
+            let backend = context.backend.as_mut();
+            match entry.kind {
+                ConsoleKind::Simple => {
+                    let console = SimpleConsole::init(entry.width, entry.height, backend);
+                    context.register_console(console, font);
+                }
+                ConsoleKind::SimpleNoBg => {
+                    let console = SimpleConsole::init(entry.width, entry.height, backend);
+                    context.register_console_no_bg(console, font);
+                }
+                ConsoleKind::Sparse => {
+                    let console = SparseConsole::init(entry.width, entry.height, backend);
+                    context.register_console(console, font);
+                }
+                ConsoleKind::SparseNoBg => {
+                    let console = SparseConsole::init(entry.width, entry.height, backend);
+                    context.register_console_no_bg(console, font);
+                }
+            }
+        }
+
+        if let Some(with_burn) = self.post_scanlines {
+            context.with_post_scanlines(with_burn);
+        }
+        for (name, fragment_shader) in &self.post_effects {
+            context.register_post_effect(name, fragment_shader)?;
+        }
+        if let Some(fps) = self.fps_cap {
+            context.with_fps_cap(fps);
+        }
+        context.with_resize_policy(self.resize_policy);
+        context.with_hot_reload(self.hot_reload);
+
+        Ok(context)
+    }
+}
@@ -0,0 +1,142 @@
+//! Mapping a world larger than the screen onto a region of a console. The camera shows a
+//! window-sized piece of the world, usually centred on the player; draw each visible world
+//! position at the screen cell the camera gives for it, and turn the mouse's cell back into a
+//! world position when it is clicked.
+
+use crate::fractal::Fractal;
+use crate::geometry::Point;
+
+/// A view of part of the world, drawn into a rectangle of console cells
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    /// Console cell the view's top-left corner is drawn at
+    pub screen_origin: Point,
+    /// Width of the view, in cells
+    pub width: i32,
+    /// Height of the view, in cells
+    pub height: i32,
+    /// World position shown in the view's top-left cell
+    pub world_origin: Point,
+    /// Size of the world, if the view should be kept inside it
+    pub world_size: Option<(i32, i32)>,
+}
+
+impl Camera {
+    /// A camera showing width x height cells from the top-left of the console, starting at
+    /// world position 0, 0
+    pub fn new(width: i32, height: i32) -> Camera {
+        Camera {
+            screen_origin: Point::zero(),
+            width,
+            height,
+            world_origin: Point::zero(),
+            world_size: None,
+        }
+    }
+
+    /// Draws the view starting at a console cell other than the top-left one
+    pub fn with_screen_origin(mut self, x: i32, y: i32) -> Self {
+        self.screen_origin = Point::new(x, y);
+        self
+    }
+
+    /// Keeps the view inside a world of width x height positions when it moves. A world
+    /// smaller than the view is centred in it.
+    pub fn with_world_size(mut self, width: i32, height: i32) -> Self {
+        self.world_size = Some((width, height));
+        self.clamp();
+        self
+    }
+
+    /// Moves the view so its top-left cell shows a world position, then clamps it
+    pub fn move_to(&mut self, world_origin: Point) {
+        self.world_origin = world_origin;
+        self.clamp();
+    }
+
+    /// Moves the view so a world position is in its middle, or as near as the world's edges
+    /// allow
+    pub fn center_on(&mut self, target: Point) {
+        self.move_to(Point::new(
+            target.x - self.width / 2,
+            target.y - self.height / 2,
+        ));
+    }
+
+    /// The console cell a world position is drawn at, or None if it is outside the view
+    pub fn world_to_screen(&self, world: Point) -> Option<Point> {
+        let offset = world - self.world_origin;
+        if self.contains_offset(offset) {
+            Some(self.screen_origin + offset)
+        } else {
+            None
+        }
+    }
+
+    /// The world position drawn at a console cell, or None if the cell is outside the view
+    pub fn screen_to_world(&self, screen: Point) -> Option<Point> {
+        let offset = screen - self.screen_origin;
+        if self.contains_offset(offset) {
+            Some(self.world_origin + offset)
+        } else {
+            None
+        }
+    }
+
+    /// The world position under the mouse, or None if the mouse is outside the view or over
+    /// the bars around the viewport. Uses the active console's cells, so make the console the
+    /// camera draws on active first.
+    pub fn mouse_world_pos(&self, ctx: &Fractal) -> Option<Point> {
+        ctx.mouse_cell()
+            .and_then(|cell| self.screen_to_world(Point::from_tuple(cell)))
+    }
+
+    /// True if a world position is inside the view
+    pub fn is_visible(&self, world: Point) -> bool {
+        self.world_to_screen(world).is_some()
+    }
+
+    /// Calls f with every world position in the view and the console cell it is drawn at,
+    /// row by row. Positions outside the world are skipped if the world size is set.
+    pub fn for_each<F: FnMut(Point, Point)>(&self, mut f: F) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let offset = Point::new(x, y);
+                let world = self.world_origin + offset;
+                if self.in_world(world) {
+                    f(world, self.screen_origin + offset);
+                }
+            }
+        }
+    }
+
+    fn contains_offset(&self, offset: Point) -> bool {
+        offset.x >= 0 && offset.y >= 0 && offset.x < self.width && offset.y < self.height
+    }
+
+    fn in_world(&self, world: Point) -> bool {
+        match self.world_size {
+            Some((width, height)) => {
+                world.x >= 0 && world.y >= 0 && world.x < width && world.y < height
+            }
+            None => true,
+        }
+    }
+
+    fn clamp(&mut self) {
+        if let Some((width, height)) = self.world_size {
+            self.world_origin.x = clamp_axis(self.world_origin.x, self.width, width);
+            self.world_origin.y = clamp_axis(self.world_origin.y, self.height, height);
+        }
+    }
+}
+
+/// Keeps a view of `view` cells starting at `origin` inside a world of `world` cells
+fn clamp_axis(origin: i32, view: i32, world: i32) -> i32 {
+    if world <= view {
+        // Negative, so the world sits in the middle of the view
+        (world - view) / 2
+    } else {
+        i32::max(0, i32::min(origin, world - view))
+    }
+}
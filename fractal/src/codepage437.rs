@@ -2,7 +2,7 @@
 //! Info: https://en.wikipedia.org/wiki/Code_page_437
 
 /// Converts a unicode character to a CP437 equivalent, returning 0 if it didn't have a match
-pub fn to_cp437(c: char) -> u8 {
+pub fn to_cp437(c: char) -> u16 {
     match c {
         '☺' => 1,
         '☻' => 2,
@@ -555,7 +555,7 @@ pub fn to_char(c: u8) -> char {
     }
 }
 
-/// Converts a string into a vector of u8, CP437 representations of the string
-pub fn string_to_cp437<S: AsRef<str>>(input: S) -> Vec<u8> {
+/// Converts a string into a vector of u16, CP437 representations of the string
+pub fn string_to_cp437<S: AsRef<str>>(input: S) -> Vec<u16> {
     input.as_ref().chars().map(to_cp437).collect()
 }
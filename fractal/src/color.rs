@@ -3,9 +3,27 @@
 #![allow(missing_docs)]
 
 use crate::rex::XpColor;
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Error returned when a string is neither a hex color nor a color name
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorParseError(pub String);
+
+impl fmt::Display for ColorParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid color: {}", self.0)
+    }
+}
+
+impl error::Error for ColorParseError {}
 
 #[derive(PartialEq, Copy, Clone, Default, Debug, Serialize, Deserialize)]
-/// Represents an R/G/B triplet
+#[serde(try_from = "ColorDef")]
+/// Represents an R/G/B triplet. In data files it can be written as its fields, as "#rrggbb" or
+/// as a color name such as "dark_goldenrod".
 pub struct RGB {
     /// Red component. 0..255
     pub r: u8,
@@ -36,12 +54,101 @@ impl RGB {
         XpColor::new(self.r, self.g, self.b)
     }
 
+    /// Constructs a new RGB color, from 3 floats in the range 0..1
+    pub fn from_f32(r: f32, g: f32, b: f32) -> RGB {
+        RGB::from_u8(to_u8(r), to_u8(g), to_u8(b))
+    }
+
+    /// Parses "#rrggbb" or the short form "#rgb"; the '#' is optional
+    pub fn from_hex<S: AsRef<str>>(hex: S) -> Result<RGB, ColorParseError> {
+        let hex = hex.as_ref();
+        match parse_hex(hex) {
+            Some((r, g, b, None)) => Ok(RGB::from_u8(r, g, b)),
+            _ => Err(ColorParseError(hex.to_string())),
+        }
+    }
+
+    /// Formats the color as "#rrggbb"
+    pub fn to_hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    /// Looks up one of the X11/CSS named colors. Case, spaces, dashes and underscores are
+    /// ignored, so "dark_goldenrod", "DarkGoldenrod" and "dark goldenrod" are the same color.
+    pub fn named(name: &str) -> Option<RGB> {
+        NAMED_COLORS
+            .iter()
+            .find(|(n, _)| simplify(n).eq(simplify(name)))
+            .map(|(_, color)| *color)
+    }
+
     /// Applies a quick grayscale conversion to the color
     pub fn to_greyscale(&self) -> RGB {
-        let linear = ((self.r as f32 * 0.2126 * 255.0) + (self.g as f32 * 0.7152 * 255.0) + (self.b as f32 * 0.0722 * 255.0)) as u8;
+        let linear = (self.r as f32 * 0.2126 + self.g as f32 * 0.7152 + self.b as f32 * 0.0722)
+            .round() as u8;
         RGB::from_u8(linear, linear, linear)
     }
 
+    /// Mixes two colors, t (0..1) of the way from this one to the other
+    pub fn lerp(&self, to: RGB, t: f32) -> RGB {
+        RGB::from_u8(
+            lerp_u8(self.r, to.r, t),
+            lerp_u8(self.g, to.g, t),
+            lerp_u8(self.b, to.b, t),
+        )
+    }
+
+    /// Converts to hue, saturation and value
+    pub fn to_hsv(&self) -> HSV {
+        let (max, min) = self.extremes();
+        let delta = max - min;
+        HSV {
+            h: self.hue(delta),
+            s: if max > 0.0 { delta / max } else { 0.0 },
+            v: max,
+        }
+    }
+
+    /// Converts to hue, saturation and lightness
+    pub fn to_hsl(&self) -> HSL {
+        let (max, min) = self.extremes();
+        let delta = max - min;
+        let l = (max + min) / 2.0;
+        HSL {
+            h: self.hue(delta),
+            s: if delta > 0.0 {
+                delta / (1.0 - (2.0 * l - 1.0).abs())
+            } else {
+                0.0
+            },
+            l,
+        }
+    }
+
+    /// The largest and smallest components, 0..1
+    fn extremes(&self) -> (f32, f32) {
+        let max = u8::max(self.r, u8::max(self.g, self.b));
+        let min = u8::min(self.r, u8::min(self.g, self.b));
+        (f32::from(max) / 255.0, f32::from(min) / 255.0)
+    }
+
+    /// Hue in degrees, given the spread between the largest and smallest components
+    fn hue(&self, delta: f32) -> f32 {
+        if delta <= 0.0 {
+            return 0.0;
+        }
+        let (r, g, b) = (self.rf(), self.gf(), self.bf());
+        let max_byte = u8::max(self.r, u8::max(self.g, self.b));
+        let h = if self.r == max_byte {
+            ((g - b) / delta) % 6.0
+        } else if self.g == max_byte {
+            (b - r) / delta + 2.0
+        } else {
+            (r - g) / delta + 4.0
+        };
+        (h * 60.0 + 360.0) % 360.0
+    }
+
     /// Gets the red component as a f32
     /// range 0..1
     pub fn rf(&self) -> f32 {
@@ -59,21 +166,671 @@ impl RGB {
     pub fn bf(&self) -> f32 {
         self.b as f32 / 255.0
     }
+
+    /// Converts to an RGBA color with the given alpha, 0 (transparent) to 255 (opaque)
+    pub const fn to_rgba(&self, alpha: u8) -> RGBA {
+        RGBA::from_u8(self.r, self.g, self.b, alpha)
+    }
 }
 
-pub const WHITE: RGB = RGB::from_u8(255, 255, 255);
-pub const GRAY: RGB = RGB::from_u8(128, 128, 128);
-pub const BLACK: RGB = RGB::from_u8(0, 0, 0);
+#[derive(PartialEq, Copy, Clone, Default, Debug, Serialize, Deserialize)]
+#[serde(try_from = "ColorDef")]
+/// Represents an R/G/B/A quad. Alpha runs from 0 (transparent) to 255 (opaque), and is used to
+/// blend with whatever was drawn underneath, including lower consoles. In data files it can be
+/// written as its fields (alpha defaults to opaque), as "#rrggbbaa" or "#rrggbb", or as a color
+/// name.
+pub struct RGBA {
+    /// Red component. 0..255
+    pub r: u8,
+    /// Green component. 0..255
+    pub g: u8,
+    /// Blue component. 0..255
+    pub b: u8,
+    /// Alpha component. 0..255
+    pub a: u8,
+}
 
-pub const RED: RGB = RGB::from_u8(255, 0, 0);
-pub const GREEN: RGB = RGB::from_u8(0, 255, 0);
-pub const BLUE: RGB = RGB::from_u8(0, 0, 255);
+impl RGBA {
+    /// Constructs a new, zeroed (transparent black) RGBA quad.
+    pub const fn new() -> RGBA {
+        RGBA {
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 0,
+        }
+    }
 
-pub const AQUA: RGB = RGB::from_u8(0, 255, 255);
-pub const YELLOW: RGB = RGB::from_u8(255, 255, 0);
-pub const PURPLE: RGB = RGB::from_u8(128, 0, 128);
+    /// Constructs a new RGBA color, from 4 bytes in the range 0..255
+    pub const fn from_u8(r: u8, g: u8, b: u8, a: u8) -> RGBA {
+        RGBA { r, g, b, a }
+    }
 
-pub const ORANGE: RGB = RGB::from_u8(255, 165, 0);
+    /// Drops the alpha channel
+    pub const fn to_rgb(&self) -> RGB {
+        RGB::from_u8(self.r, self.g, self.b)
+    }
+
+    /// Returns the same color with a different alpha
+    pub const fn with_alpha(&self, alpha: u8) -> RGBA {
+        RGBA::from_u8(self.r, self.g, self.b, alpha)
+    }
+
+    /// Parses "#rrggbbaa", or "#rrggbb" (or "#rgb") as an opaque color; the '#' is optional
+    pub fn from_hex<S: AsRef<str>>(hex: S) -> Result<RGBA, ColorParseError> {
+        let hex = hex.as_ref();
+        match parse_hex(hex) {
+            Some((r, g, b, a)) => Ok(RGBA::from_u8(r, g, b, a.unwrap_or(255))),
+            None => Err(ColorParseError(hex.to_string())),
+        }
+    }
+
+    /// Formats the color as "#rrggbbaa"
+    pub fn to_hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+    }
+
+    /// Looks up one of the X11/CSS named colors as an opaque color; see `RGB::named`
+    pub fn named(name: &str) -> Option<RGBA> {
+        RGB::named(name).map(RGBA::from)
+    }
+
+    /// Mixes two colors, alpha included, t (0..1) of the way from this one to the other
+    pub fn lerp(&self, to: RGBA, t: f32) -> RGBA {
+        RGBA::from_u8(
+            lerp_u8(self.r, to.r, t),
+            lerp_u8(self.g, to.g, t),
+            lerp_u8(self.b, to.b, t),
+            lerp_u8(self.a, to.a, t),
+        )
+    }
+
+    /// Converts an xp file color component to an RGBA. The xp transparent color (magenta)
+    /// becomes fully transparent.
+    pub fn from_xp(col: XpColor) -> RGBA {
+        if col.is_transparent() {
+            TRANSPARENT
+        } else {
+            RGBA::from_u8(col.r, col.g, col.b, 255)
+        }
+    }
+
+    /// Converts an RGBA to an xp file color component. Fully transparent colors become the xp
+    /// transparent color (magenta); other alpha values are dropped.
+    pub fn to_xp(&self) -> XpColor {
+        if self.a == 0 {
+            XpColor::TRANSPARENT
+        } else {
+            XpColor::new(self.r, self.g, self.b)
+        }
+    }
+
+    /// Gets the red component as a f32
+    /// range 0..1
+    pub fn rf(&self) -> f32 {
+        self.r as f32 / 255.0
+    }
+
+    /// Gets the green component as a f32
+    /// range 0..1
+    pub fn gf(&self) -> f32 {
+        self.g as f32 / 255.0
+    }
+
+    /// Gets the blue component as a f32
+    /// range 0..1
+    pub fn bf(&self) -> f32 {
+        self.b as f32 / 255.0
+    }
+
+    /// Gets the alpha component as a f32
+    /// range 0..1
+    pub fn af(&self) -> f32 {
+        self.a as f32 / 255.0
+    }
+
+    /// Blends this color over an opaque one, by its alpha
+    pub fn blend_onto(&self, under: RGB) -> RGB {
+        let a = u32::from(self.a);
+        let mix = |over: u8, under: u8| {
+            ((u32::from(over) * a + u32::from(under) * (255 - a)) / 255) as u8
+        };
+        RGB::from_u8(
+            mix(self.r, under.r),
+            mix(self.g, under.g),
+            mix(self.b, under.b),
+        )
+    }
+}
+
+impl From<RGB> for RGBA {
+    /// An opaque version of the color
+    fn from(rgb: RGB) -> RGBA {
+        rgb.to_rgba(255)
+    }
+}
+
+impl From<RGBA> for RGB {
+    /// Drops the alpha channel
+    fn from(rgba: RGBA) -> RGB {
+        rgba.to_rgb()
+    }
+}
+
+impl FromStr for RGB {
+    type Err = ColorParseError;
+
+    /// Parses "#rrggbb", "#rgb" or a color name
+    fn from_str(s: &str) -> Result<RGB, ColorParseError> {
+        let s = s.trim();
+        if s.starts_with('#') {
+            RGB::from_hex(s)
+        } else {
+            RGB::named(s).ok_or_else(|| ColorParseError(s.to_string()))
+        }
+    }
+}
+
+impl FromStr for RGBA {
+    type Err = ColorParseError;
+
+    /// Parses "#rrggbbaa", "#rrggbb", "#rgb" or a color name
+    fn from_str(s: &str) -> Result<RGBA, ColorParseError> {
+        let s = s.trim();
+        if s.starts_with('#') {
+            RGBA::from_hex(s)
+        } else {
+            RGBA::named(s).ok_or_else(|| ColorParseError(s.to_string()))
+        }
+    }
+}
+
+/// The ways a color can be written in a data file
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ColorDef {
+    Text(String),
+    Fields {
+        r: u8,
+        g: u8,
+        b: u8,
+        #[serde(default = "opaque")]
+        a: u8,
+    },
+}
+
+fn opaque() -> u8 {
+    255
+}
+
+impl TryFrom<ColorDef> for RGB {
+    type Error = ColorParseError;
+
+    fn try_from(def: ColorDef) -> Result<RGB, ColorParseError> {
+        match def {
+            ColorDef::Text(s) => s.parse(),
+            ColorDef::Fields { r, g, b, .. } => Ok(RGB::from_u8(r, g, b)),
+        }
+    }
+}
+
+impl TryFrom<ColorDef> for RGBA {
+    type Error = ColorParseError;
+
+    fn try_from(def: ColorDef) -> Result<RGBA, ColorParseError> {
+        match def {
+            ColorDef::Text(s) => s.parse(),
+            ColorDef::Fields { r, g, b, a } => Ok(RGBA::from_u8(r, g, b, a)),
+        }
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Default, Debug, Serialize, Deserialize)]
+/// A color as hue, saturation and value
+pub struct HSV {
+    /// Hue in degrees. 0..360
+    pub h: f32,
+    /// Saturation. 0..1
+    pub s: f32,
+    /// Value (brightness). 0..1
+    pub v: f32,
+}
+
+impl HSV {
+    /// Constructs a new HSV color
+    pub const fn new(h: f32, s: f32, v: f32) -> HSV {
+        HSV { h, s, v }
+    }
+
+    /// Converts to RGB
+    pub fn to_rgb(&self) -> RGB {
+        let v = clamp01(self.v);
+        let chroma = v * clamp01(self.s);
+        from_hue(self.h, chroma, v - chroma)
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Default, Debug, Serialize, Deserialize)]
+/// A color as hue, saturation and lightness
+pub struct HSL {
+    /// Hue in degrees. 0..360
+    pub h: f32,
+    /// Saturation. 0..1
+    pub s: f32,
+    /// Lightness. 0..1
+    pub l: f32,
+}
+
+impl HSL {
+    /// Constructs a new HSL color
+    pub const fn new(h: f32, s: f32, l: f32) -> HSL {
+        HSL { h, s, l }
+    }
+
+    /// Converts to RGB
+    pub fn to_rgb(&self) -> RGB {
+        let l = clamp01(self.l);
+        let chroma = (1.0 - (2.0 * l - 1.0).abs()) * clamp01(self.s);
+        from_hue(self.h, chroma, l - chroma / 2.0)
+    }
+}
+
+impl From<HSV> for RGB {
+    fn from(hsv: HSV) -> RGB {
+        hsv.to_rgb()
+    }
+}
+
+impl From<HSL> for RGB {
+    fn from(hsl: HSL) -> RGB {
+        hsl.to_rgb()
+    }
+}
+
+impl From<RGB> for HSV {
+    fn from(rgb: RGB) -> HSV {
+        rgb.to_hsv()
+    }
+}
+
+impl From<RGB> for HSL {
+    fn from(rgb: RGB) -> HSL {
+        rgb.to_hsl()
+    }
+}
+
+/// Evenly spaced colors running through each of the stops in turn, starting at the first and
+/// ending at the last. Fewer than two steps gives just the first stop.
+pub fn gradient<C: Into<RGBA> + Copy>(stops: &[C], steps: usize) -> Vec<RGBA> {
+    let stops: Vec<RGBA> = stops.iter().map(|c| (*c).into()).collect();
+    match (stops.len(), steps) {
+        (0, _) | (_, 0) => Vec::new(),
+        (1, _) | (_, 1) => vec![stops[0]; steps],
+        (n, _) => (0..steps)
+            .map(|i| {
+                let position = i as f32 / (steps - 1) as f32 * (n - 1) as f32;
+                let segment = usize::min(position as usize, n - 2);
+                stops[segment].lerp(stops[segment + 1], position - segment as f32)
+            })
+            .collect(),
+    }
+}
+
+/// An RGB color from a hue in degrees, a chroma and the amount added to every component
+fn from_hue(h: f32, chroma: f32, m: f32) -> RGB {
+    let sector = ((h % 360.0 + 360.0) % 360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    RGB::from_f32(r + m, g + m, b + m)
+}
+
+/// Red, green, blue and, if given, alpha from "#rgb", "#rrggbb" or "#rrggbbaa"
+fn parse_hex(hex: &str) -> Option<(u8, u8, u8, Option<u8>)> {
+    let nibbles = hex
+        .trim()
+        .trim_start_matches('#')
+        .chars()
+        .map(|c| c.to_digit(16).map(|d| d as u8))
+        .collect::<Option<Vec<u8>>>()?;
+    let byte = |i: usize| nibbles[i] * 16 + nibbles[i + 1];
+    match nibbles.len() {
+        3 => Some((nibbles[0] * 17, nibbles[1] * 17, nibbles[2] * 17, None)),
+        6 => Some((byte(0), byte(2), byte(4), None)),
+        8 => Some((byte(0), byte(2), byte(4), Some(byte(6)))),
+        _ => None,
+    }
+}
+
+/// A color name without case or separators
+fn simplify(name: &str) -> impl Iterator<Item = char> + '_ {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+}
+
+fn lerp_u8(from: u8, to: u8, t: f32) -> u8 {
+    let (from, to) = (f32::from(from), f32::from(to));
+    (from + (to - from) * t).round() as u8
+}
+
+/// Clamps to 0..1. Unlike f32::clamp, NaN comes out as 0 rather than NaN.
+#[allow(clippy::manual_clamp)]
+fn clamp01(value: f32) -> f32 {
+    f32::max(0.0, f32::min(value, 1.0))
+}
+
+fn to_u8(value: f32) -> u8 {
+    (clamp01(value) * 255.0).round() as u8
+}
+
+pub const ALICE_BLUE: RGB = RGB::from_u8(240, 248, 255);
+pub const ANTIQUE_WHITE: RGB = RGB::from_u8(250, 235, 215);
+pub const AQUA: RGB = RGB::from_u8(0, 255, 255);
+pub const AQUAMARINE: RGB = RGB::from_u8(127, 255, 212);
+pub const AZURE: RGB = RGB::from_u8(240, 255, 255);
+pub const BEIGE: RGB = RGB::from_u8(245, 245, 220);
+pub const BISQUE: RGB = RGB::from_u8(255, 228, 196);
+pub const BLACK: RGB = RGB::from_u8(0, 0, 0);
+pub const BLANCHED_ALMOND: RGB = RGB::from_u8(255, 235, 205);
+pub const BLUE: RGB = RGB::from_u8(0, 0, 255);
+pub const BLUE_VIOLET: RGB = RGB::from_u8(138, 43, 226);
+pub const BROWN: RGB = RGB::from_u8(165, 42, 42);
+pub const BURLY_WOOD: RGB = RGB::from_u8(222, 184, 135);
+pub const CADET_BLUE: RGB = RGB::from_u8(95, 158, 160);
+pub const CHARTREUSE: RGB = RGB::from_u8(127, 255, 0);
+pub const CHOCOLATE: RGB = RGB::from_u8(210, 105, 30);
+pub const CORAL: RGB = RGB::from_u8(255, 127, 80);
+pub const CORNFLOWER_BLUE: RGB = RGB::from_u8(100, 149, 237);
+pub const CORNSILK: RGB = RGB::from_u8(255, 248, 220);
+pub const CRIMSON: RGB = RGB::from_u8(220, 20, 60);
+pub const CYAN: RGB = RGB::from_u8(0, 255, 255);
+pub const DARK_BLUE: RGB = RGB::from_u8(0, 0, 139);
+pub const DARK_CYAN: RGB = RGB::from_u8(0, 139, 139);
+pub const DARK_GOLDENROD: RGB = RGB::from_u8(184, 134, 11);
+pub const DARK_GRAY: RGB = RGB::from_u8(169, 169, 169);
+pub const DARK_GREEN: RGB = RGB::from_u8(0, 100, 0);
+pub const DARK_GREY: RGB = RGB::from_u8(169, 169, 169);
+pub const DARK_KHAKI: RGB = RGB::from_u8(189, 183, 107);
+pub const DARK_MAGENTA: RGB = RGB::from_u8(139, 0, 139);
+pub const DARK_OLIVE_GREEN: RGB = RGB::from_u8(85, 107, 47);
+pub const DARK_ORANGE: RGB = RGB::from_u8(255, 140, 0);
+pub const DARK_ORCHID: RGB = RGB::from_u8(153, 50, 204);
+pub const DARK_RED: RGB = RGB::from_u8(139, 0, 0);
+pub const DARK_SALMON: RGB = RGB::from_u8(233, 150, 122);
+pub const DARK_SEA_GREEN: RGB = RGB::from_u8(143, 188, 143);
+pub const DARK_SLATE_BLUE: RGB = RGB::from_u8(72, 61, 139);
+pub const DARK_SLATE_GRAY: RGB = RGB::from_u8(47, 79, 79);
+pub const DARK_SLATE_GREY: RGB = RGB::from_u8(47, 79, 79);
+pub const DARK_TURQUOISE: RGB = RGB::from_u8(0, 206, 209);
+pub const DARK_VIOLET: RGB = RGB::from_u8(148, 0, 211);
+pub const DEEP_PINK: RGB = RGB::from_u8(255, 20, 147);
+pub const DEEP_SKY_BLUE: RGB = RGB::from_u8(0, 191, 255);
+pub const DIM_GRAY: RGB = RGB::from_u8(105, 105, 105);
+pub const DIM_GREY: RGB = RGB::from_u8(105, 105, 105);
+pub const DODGER_BLUE: RGB = RGB::from_u8(30, 144, 255);
+pub const FIRE_BRICK: RGB = RGB::from_u8(178, 34, 34);
+pub const FLORAL_WHITE: RGB = RGB::from_u8(255, 250, 240);
+pub const FOREST_GREEN: RGB = RGB::from_u8(34, 139, 34);
+pub const FUCHSIA: RGB = RGB::from_u8(255, 0, 255);
+pub const GAINSBORO: RGB = RGB::from_u8(220, 220, 220);
+pub const GHOST_WHITE: RGB = RGB::from_u8(248, 248, 255);
+pub const GOLD: RGB = RGB::from_u8(255, 215, 0);
+pub const GOLDENROD: RGB = RGB::from_u8(218, 165, 32);
+pub const GRAY: RGB = RGB::from_u8(128, 128, 128);
+pub const GREY: RGB = RGB::from_u8(128, 128, 128);
+/// Pure green, as it has always been here. The CSS color named "green" is darker; look it up
+/// with `RGB::named`.
+pub const GREEN: RGB = RGB::from_u8(0, 255, 0);
+pub const GREEN_YELLOW: RGB = RGB::from_u8(173, 255, 47);
+pub const HONEYDEW: RGB = RGB::from_u8(240, 255, 240);
+pub const HOT_PINK: RGB = RGB::from_u8(255, 105, 180);
+pub const INDIAN_RED: RGB = RGB::from_u8(205, 92, 92);
+pub const INDIGO: RGB = RGB::from_u8(75, 0, 130);
+pub const IVORY: RGB = RGB::from_u8(255, 255, 240);
+pub const KHAKI: RGB = RGB::from_u8(240, 230, 140);
+pub const LAVENDER: RGB = RGB::from_u8(230, 230, 250);
+pub const LAVENDER_BLUSH: RGB = RGB::from_u8(255, 240, 245);
+pub const LAWN_GREEN: RGB = RGB::from_u8(124, 252, 0);
+pub const LEMON_CHIFFON: RGB = RGB::from_u8(255, 250, 205);
+pub const LIGHT_BLUE: RGB = RGB::from_u8(173, 216, 230);
+pub const LIGHT_CORAL: RGB = RGB::from_u8(240, 128, 128);
+pub const LIGHT_CYAN: RGB = RGB::from_u8(224, 255, 255);
+pub const LIGHT_GOLDENROD_YELLOW: RGB = RGB::from_u8(250, 250, 210);
+pub const LIGHT_GRAY: RGB = RGB::from_u8(211, 211, 211);
+pub const LIGHT_GREEN: RGB = RGB::from_u8(144, 238, 144);
+pub const LIGHT_GREY: RGB = RGB::from_u8(211, 211, 211);
+pub const LIGHT_PINK: RGB = RGB::from_u8(255, 182, 193);
+pub const LIGHT_SALMON: RGB = RGB::from_u8(255, 160, 122);
+pub const LIGHT_SEA_GREEN: RGB = RGB::from_u8(32, 178, 170);
+pub const LIGHT_SKY_BLUE: RGB = RGB::from_u8(135, 206, 250);
+pub const LIGHT_SLATE_GRAY: RGB = RGB::from_u8(119, 136, 153);
+pub const LIGHT_SLATE_GREY: RGB = RGB::from_u8(119, 136, 153);
+pub const LIGHT_STEEL_BLUE: RGB = RGB::from_u8(176, 196, 222);
+pub const LIGHT_YELLOW: RGB = RGB::from_u8(255, 255, 224);
+pub const LIME: RGB = RGB::from_u8(0, 255, 0);
+pub const LIME_GREEN: RGB = RGB::from_u8(50, 205, 50);
+pub const LINEN: RGB = RGB::from_u8(250, 240, 230);
 pub const MAGENTA: RGB = RGB::from_u8(255, 0, 255);
-pub const CYAN: RGB = AQUA;
+pub const MAROON: RGB = RGB::from_u8(128, 0, 0);
+pub const MEDIUM_AQUAMARINE: RGB = RGB::from_u8(102, 205, 170);
+pub const MEDIUM_BLUE: RGB = RGB::from_u8(0, 0, 205);
+pub const MEDIUM_ORCHID: RGB = RGB::from_u8(186, 85, 211);
+pub const MEDIUM_PURPLE: RGB = RGB::from_u8(147, 112, 219);
+pub const MEDIUM_SEA_GREEN: RGB = RGB::from_u8(60, 179, 113);
+pub const MEDIUM_SLATE_BLUE: RGB = RGB::from_u8(123, 104, 238);
+pub const MEDIUM_SPRING_GREEN: RGB = RGB::from_u8(0, 250, 154);
+pub const MEDIUM_TURQUOISE: RGB = RGB::from_u8(72, 209, 204);
+pub const MEDIUM_VIOLET_RED: RGB = RGB::from_u8(199, 21, 133);
+pub const MIDNIGHT_BLUE: RGB = RGB::from_u8(25, 25, 112);
+pub const MINT_CREAM: RGB = RGB::from_u8(245, 255, 250);
+pub const MISTY_ROSE: RGB = RGB::from_u8(255, 228, 225);
+pub const MOCCASIN: RGB = RGB::from_u8(255, 228, 181);
+pub const NAVAJO_WHITE: RGB = RGB::from_u8(255, 222, 173);
+pub const NAVY: RGB = RGB::from_u8(0, 0, 128);
+pub const OLD_LACE: RGB = RGB::from_u8(253, 245, 230);
+pub const OLIVE: RGB = RGB::from_u8(128, 128, 0);
+pub const OLIVE_DRAB: RGB = RGB::from_u8(107, 142, 35);
+pub const ORANGE: RGB = RGB::from_u8(255, 165, 0);
+pub const ORANGE_RED: RGB = RGB::from_u8(255, 69, 0);
+pub const ORCHID: RGB = RGB::from_u8(218, 112, 214);
+pub const PALE_GOLDENROD: RGB = RGB::from_u8(238, 232, 170);
+pub const PALE_GREEN: RGB = RGB::from_u8(152, 251, 152);
+pub const PALE_TURQUOISE: RGB = RGB::from_u8(175, 238, 238);
+pub const PALE_VIOLET_RED: RGB = RGB::from_u8(219, 112, 147);
+pub const PAPAYA_WHIP: RGB = RGB::from_u8(255, 239, 213);
+pub const PEACH_PUFF: RGB = RGB::from_u8(255, 218, 185);
+pub const PERU: RGB = RGB::from_u8(205, 133, 63);
 pub const PINK: RGB = RGB::from_u8(255, 192, 203);
+pub const PLUM: RGB = RGB::from_u8(221, 160, 221);
+pub const POWDER_BLUE: RGB = RGB::from_u8(176, 224, 230);
+pub const PURPLE: RGB = RGB::from_u8(128, 0, 128);
+pub const REBECCA_PURPLE: RGB = RGB::from_u8(102, 51, 153);
+pub const RED: RGB = RGB::from_u8(255, 0, 0);
+pub const ROSY_BROWN: RGB = RGB::from_u8(188, 143, 143);
+pub const ROYAL_BLUE: RGB = RGB::from_u8(65, 105, 225);
+pub const SADDLE_BROWN: RGB = RGB::from_u8(139, 69, 19);
+pub const SALMON: RGB = RGB::from_u8(250, 128, 114);
+pub const SANDY_BROWN: RGB = RGB::from_u8(244, 164, 96);
+pub const SEA_GREEN: RGB = RGB::from_u8(46, 139, 87);
+pub const SEASHELL: RGB = RGB::from_u8(255, 245, 238);
+pub const SIENNA: RGB = RGB::from_u8(160, 82, 45);
+pub const SILVER: RGB = RGB::from_u8(192, 192, 192);
+pub const SKY_BLUE: RGB = RGB::from_u8(135, 206, 235);
+pub const SLATE_BLUE: RGB = RGB::from_u8(106, 90, 205);
+pub const SLATE_GRAY: RGB = RGB::from_u8(112, 128, 144);
+pub const SLATE_GREY: RGB = RGB::from_u8(112, 128, 144);
+pub const SNOW: RGB = RGB::from_u8(255, 250, 250);
+pub const SPRING_GREEN: RGB = RGB::from_u8(0, 255, 127);
+pub const STEEL_BLUE: RGB = RGB::from_u8(70, 130, 180);
+pub const TAN: RGB = RGB::from_u8(210, 180, 140);
+pub const TEAL: RGB = RGB::from_u8(0, 128, 128);
+pub const THISTLE: RGB = RGB::from_u8(216, 191, 216);
+pub const TOMATO: RGB = RGB::from_u8(255, 99, 71);
+pub const TURQUOISE: RGB = RGB::from_u8(64, 224, 208);
+pub const VIOLET: RGB = RGB::from_u8(238, 130, 238);
+pub const WHEAT: RGB = RGB::from_u8(245, 222, 179);
+pub const WHITE: RGB = RGB::from_u8(255, 255, 255);
+pub const WHITE_SMOKE: RGB = RGB::from_u8(245, 245, 245);
+pub const YELLOW: RGB = RGB::from_u8(255, 255, 0);
+pub const YELLOW_GREEN: RGB = RGB::from_u8(154, 205, 50);
+
+pub const TRANSPARENT: RGBA = RGBA::from_u8(0, 0, 0, 0);
+
+/// The X11/CSS named colors, by lower-case name with words separated by underscores
+pub const NAMED_COLORS: &[(&str, RGB)] = &[
+    ("alice_blue", ALICE_BLUE),
+    ("antique_white", ANTIQUE_WHITE),
+    ("aqua", AQUA),
+    ("aquamarine", AQUAMARINE),
+    ("azure", AZURE),
+    ("beige", BEIGE),
+    ("bisque", BISQUE),
+    ("black", BLACK),
+    ("blanched_almond", BLANCHED_ALMOND),
+    ("blue", BLUE),
+    ("blue_violet", BLUE_VIOLET),
+    ("brown", BROWN),
+    ("burly_wood", BURLY_WOOD),
+    ("cadet_blue", CADET_BLUE),
+    ("chartreuse", CHARTREUSE),
+    ("chocolate", CHOCOLATE),
+    ("coral", CORAL),
+    ("cornflower_blue", CORNFLOWER_BLUE),
+    ("cornsilk", CORNSILK),
+    ("crimson", CRIMSON),
+    ("cyan", CYAN),
+    ("dark_blue", DARK_BLUE),
+    ("dark_cyan", DARK_CYAN),
+    ("dark_goldenrod", DARK_GOLDENROD),
+    ("dark_gray", DARK_GRAY),
+    ("dark_green", DARK_GREEN),
+    ("dark_grey", DARK_GREY),
+    ("dark_khaki", DARK_KHAKI),
+    ("dark_magenta", DARK_MAGENTA),
+    ("dark_olive_green", DARK_OLIVE_GREEN),
+    ("dark_orange", DARK_ORANGE),
+    ("dark_orchid", DARK_ORCHID),
+    ("dark_red", DARK_RED),
+    ("dark_salmon", DARK_SALMON),
+    ("dark_sea_green", DARK_SEA_GREEN),
+    ("dark_slate_blue", DARK_SLATE_BLUE),
+    ("dark_slate_gray", DARK_SLATE_GRAY),
+    ("dark_slate_grey", DARK_SLATE_GREY),
+    ("dark_turquoise", DARK_TURQUOISE),
+    ("dark_violet", DARK_VIOLET),
+    ("deep_pink", DEEP_PINK),
+    ("deep_sky_blue", DEEP_SKY_BLUE),
+    ("dim_gray", DIM_GRAY),
+    ("dim_grey", DIM_GREY),
+    ("dodger_blue", DODGER_BLUE),
+    ("fire_brick", FIRE_BRICK),
+    ("floral_white", FLORAL_WHITE),
+    ("forest_green", FOREST_GREEN),
+    ("fuchsia", FUCHSIA),
+    ("gainsboro", GAINSBORO),
+    ("ghost_white", GHOST_WHITE),
+    ("gold", GOLD),
+    ("goldenrod", GOLDENROD),
+    ("gray", GRAY),
+    ("grey", GREY),
+    ("green", RGB::from_u8(0, 128, 0)),
+    ("green_yellow", GREEN_YELLOW),
+    ("honeydew", HONEYDEW),
+    ("hot_pink", HOT_PINK),
+    ("indian_red", INDIAN_RED),
+    ("indigo", INDIGO),
+    ("ivory", IVORY),
+    ("khaki", KHAKI),
+    ("lavender", LAVENDER),
+    ("lavender_blush", LAVENDER_BLUSH),
+    ("lawn_green", LAWN_GREEN),
+    ("lemon_chiffon", LEMON_CHIFFON),
+    ("light_blue", LIGHT_BLUE),
+    ("light_coral", LIGHT_CORAL),
+    ("light_cyan", LIGHT_CYAN),
+    ("light_goldenrod_yellow", LIGHT_GOLDENROD_YELLOW),
+    ("light_gray", LIGHT_GRAY),
+    ("light_green", LIGHT_GREEN),
+    ("light_grey", LIGHT_GREY),
+    ("light_pink", LIGHT_PINK),
+    ("light_salmon", LIGHT_SALMON),
+    ("light_sea_green", LIGHT_SEA_GREEN),
+    ("light_sky_blue", LIGHT_SKY_BLUE),
+    ("light_slate_gray", LIGHT_SLATE_GRAY),
+    ("light_slate_grey", LIGHT_SLATE_GREY),
+    ("light_steel_blue", LIGHT_STEEL_BLUE),
+    ("light_yellow", LIGHT_YELLOW),
+    ("lime", LIME),
+    ("lime_green", LIME_GREEN),
+    ("linen", LINEN),
+    ("magenta", MAGENTA),
+    ("maroon", MAROON),
+    ("medium_aquamarine", MEDIUM_AQUAMARINE),
+    ("medium_blue", MEDIUM_BLUE),
+    ("medium_orchid", MEDIUM_ORCHID),
+    ("medium_purple", MEDIUM_PURPLE),
+    ("medium_sea_green", MEDIUM_SEA_GREEN),
+    ("medium_slate_blue", MEDIUM_SLATE_BLUE),
+    ("medium_spring_green", MEDIUM_SPRING_GREEN),
+    ("medium_turquoise", MEDIUM_TURQUOISE),
+    ("medium_violet_red", MEDIUM_VIOLET_RED),
+    ("midnight_blue", MIDNIGHT_BLUE),
+    ("mint_cream", MINT_CREAM),
+    ("misty_rose", MISTY_ROSE),
+    ("moccasin", MOCCASIN),
+    ("navajo_white", NAVAJO_WHITE),
+    ("navy", NAVY),
+    ("old_lace", OLD_LACE),
+    ("olive", OLIVE),
+    ("olive_drab", OLIVE_DRAB),
+    ("orange", ORANGE),
+    ("orange_red", ORANGE_RED),
+    ("orchid", ORCHID),
+    ("pale_goldenrod", PALE_GOLDENROD),
+    ("pale_green", PALE_GREEN),
+    ("pale_turquoise", PALE_TURQUOISE),
+    ("pale_violet_red", PALE_VIOLET_RED),
+    ("papaya_whip", PAPAYA_WHIP),
+    ("peach_puff", PEACH_PUFF),
+    ("peru", PERU),
+    ("pink", PINK),
+    ("plum", PLUM),
+    ("powder_blue", POWDER_BLUE),
+    ("purple", PURPLE),
+    ("rebecca_purple", REBECCA_PURPLE),
+    ("red", RED),
+    ("rosy_brown", ROSY_BROWN),
+    ("royal_blue", ROYAL_BLUE),
+    ("saddle_brown", SADDLE_BROWN),
+    ("salmon", SALMON),
+    ("sandy_brown", SANDY_BROWN),
+    ("sea_green", SEA_GREEN),
+    ("seashell", SEASHELL),
+    ("sienna", SIENNA),
+    ("silver", SILVER),
+    ("sky_blue", SKY_BLUE),
+    ("slate_blue", SLATE_BLUE),
+    ("slate_gray", SLATE_GRAY),
+    ("slate_grey", SLATE_GREY),
+    ("snow", SNOW),
+    ("spring_green", SPRING_GREEN),
+    ("steel_blue", STEEL_BLUE),
+    ("tan", TAN),
+    ("teal", TEAL),
+    ("thistle", THISTLE),
+    ("tomato", TOMATO),
+    ("turquoise", TURQUOISE),
+    ("violet", VIOLET),
+    ("wheat", WHEAT),
+    ("white", WHITE),
+    ("white_smoke", WHITE_SMOKE),
+    ("yellow", YELLOW),
+    ("yellow_green", YELLOW_GREEN),
+];
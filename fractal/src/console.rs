@@ -2,9 +2,8 @@
 //! A console is the same as a display or screen
 
 use crate::backend::font::Font;
-use crate::backend::shader::Shader;
-use crate::backend::FractalPlatform;
-use crate::color::RGB;
+use crate::backend::Backend;
+use crate::color::RGBA;
 use crate::geometry::Rect;
 use crate::rex::XpLayer;
 use std::any::Any;
@@ -12,18 +11,20 @@ use std::any::Any;
 /// The internal storage type for tiles in a simple console.
 #[derive(PartialEq, Copy, Clone)]
 pub struct Tile {
-    /// The CP437 value to render the tile as
-    pub glyph: u8,
+    /// Index of the glyph in the font. The bundled fonts hold the 256 CP437 characters, but
+    /// bigger fonts and tilesets can go past that.
+    pub glyph: u16,
     /// The Color of the glyph
-    pub fg: RGB,
+    pub fg: RGBA,
     /// The Color behind the glyph
-    pub bg: RGB,
+    pub bg: RGBA,
 }
 
 /// Trait that must be implemented by console types.
 pub trait Console {
-    /// Check to see if the internal OpenGL representation needs to be rebuilt, and do so if required.
-    fn rebuild_if_dirty(&mut self, platform: &FractalPlatform);
+    /// Check to see if the backend's representation needs to be rebuilt, and do so if required.
+    /// The font is the one the console is drawn with, which decides where each glyph is.
+    fn rebuild_if_dirty(&mut self, font: &Font, backend: &mut dyn Backend);
 
     /// Gets the dimensions of the console in characters
     fn get_char_size(&self) -> (u32, u32);
@@ -31,8 +32,11 @@ pub trait Console {
     /// Resizes the viewport
     fn resize_pixels(&mut self, width: u32, height: u32);
 
-    /// Tells the console to draw itself via OpenGL.
-    fn gl_draw(&mut self, font: &Font, shader: &Shader, platform: &FractalPlatform);
+    /// Changes the number of characters in the console. What was drawn on it is lost.
+    fn set_char_size(&mut self, width: u32, height: u32);
+
+    /// Tells the console to draw itself through the backend, with the given font and shader.
+    fn draw(&mut self, font: &Font, shader_index: usize, backend: &mut dyn Backend);
 
     /// Converts an x/y coordinate to a console index number.
     fn at(&self, x: i32, y: i32) -> usize;
@@ -41,39 +45,49 @@ pub trait Console {
     fn cls(&mut self);
 
     /// Clear the console to a set background color, if supported.
-    fn cls_bg(&mut self, background: RGB);
+    fn cls_bg(&mut self, background: RGBA);
 
     /// Print a string at the specified x/y coordinate.
     fn print(&mut self, x: i32, y: i32, output: &str);
 
     /// Print a string in color at the specified x/y coordinate, with specified foreground and background.
-    fn print_color(&mut self, x: i32, y: i32, fg: RGB, bg: RGB, output: &str);
+    /// Colors that aren't fully opaque blend with whatever is drawn underneath.
+    fn print_color(&mut self, x: i32, y: i32, fg: RGBA, bg: RGBA, output: &str);
 
-    /// Sets a single cell to a color/glyph combination.
-    fn set(&mut self, x: i32, y: i32, fg: RGB, bg: RGB, glyph: u8);
+    /// Sets a single cell to a color/glyph combination. Colors that aren't fully opaque blend
+    /// with whatever is drawn underneath.
+    fn set(&mut self, x: i32, y: i32, fg: RGBA, bg: RGBA, glyph: u16);
 
     /// Sets a single cell's background color.
-    fn set_bg(&mut self, x: i32, y: i32, bg: RGB);
+    fn set_bg(&mut self, x: i32, y: i32, bg: RGBA);
 
     /// Draws a box, starting at x/y with the extents width/height using CP437 line characters
-    fn draw_box(&mut self, x: i32, y: i32, width: i32, height: i32, fg: RGB, bg: RGB);
+    fn draw_box(&mut self, x: i32, y: i32, width: i32, height: i32, fg: RGBA, bg: RGBA);
 
     /// Draws a box, starting at x/y with the extents width/height using CP437 line characters,
     /// without filling in the middle
-    fn draw_hollow_box(&mut self, x: i32, y: i32, width: i32, height: i32, fg: RGB, bg: RGB);
+    fn draw_hollow_box(&mut self, x: i32, y: i32, width: i32, height: i32, fg: RGBA, bg: RGBA);
 
     /// Draws a box, starting at x/y with the extents width/height using CP437 double line characters
-    fn draw_box_double(&mut self, x: i32, y: i32, width: i32, height: i32, fg: RGB, bg: RGB);
+    fn draw_box_double(&mut self, x: i32, y: i32, width: i32, height: i32, fg: RGBA, bg: RGBA);
 
     /// Draws a box, starting at x/y with the extents width/height using CP437 double line characters,
     /// without filling in the middle
-    fn draw_hollow_box_double(&mut self, x: i32, y: i32, width: i32, height: i32, fg: RGB, bg: RGB);
+    fn draw_hollow_box_double(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        fg: RGBA,
+        bg: RGBA,
+    );
 
     /// Fills a rectangle-defined region with a given glyph
-    fn fill_region(&mut self, target: Rect, glyph: u8, fg: RGB, bg: RGB);
+    fn fill_region(&mut self, target: Rect, glyph: u16, fg: RGBA, bg: RGBA);
 
     /// Retrieve a given cell in the console, if present
-    fn get(&self, x: i32, y: i32) -> Option<(&u8, &RGB, &RGB)>;
+    fn get(&self, x: i32, y: i32) -> Option<(&u16, &RGBA, &RGBA)>;
 
     /// Draws a horizontal progress bar.
     #[allow(clippy::too_many_arguments)]
@@ -84,8 +98,8 @@ pub trait Console {
         width: i32,
         n: i32,
         max: i32,
-        fg: RGB,
-        bg: RGB,
+        fg: RGBA,
+        bg: RGBA,
     );
 
     /// Draws a vertical progress bar.
@@ -97,15 +111,15 @@ pub trait Console {
         height: i32,
         n: i32,
         max: i32,
-        fg: RGB,
-        bg: RGB,
+        fg: RGBA,
+        bg: RGBA,
     );
 
     /// Prints text, centered to the whole console width, at vertical location y.
     fn print_centered(&mut self, y: i32, text: &str);
 
     /// Prints text in color, centered to the whole console width, at vertical location y.
-    fn print_color_centered(&mut self, y: i32, fg: RGB, bg: RGB, text: &str);
+    fn print_color_centered(&mut self, y: i32, fg: RGBA, bg: RGBA, text: &str);
 
     /// Serializes the console layer to an XpFile
     fn to_xp_layer(&self) -> XpLayer;
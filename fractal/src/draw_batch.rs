@@ -0,0 +1,454 @@
+//! Draw batches: command buffers that can be filled from any thread, without access to the
+//! Fractal context, and are drawn onto their consoles by the main loop. Batches are submitted to
+//! the context's `DrawBuffer`, a handle that can be cloned and sent to other threads.
+
+use crate::color::RGBA;
+use crate::console::Console;
+use crate::fractal::Fractal;
+use crate::geometry::{Point, Rect};
+use std::sync::{Arc, Mutex};
+
+/// Batches submitted since the last frame was drawn. Each context owns one; clones share it.
+#[derive(Clone, Default)]
+pub struct DrawBuffer {
+    batches: Arc<Mutex<Vec<SubmittedBatch>>>,
+}
+
+/// A batch waiting to be drawn
+struct SubmittedBatch {
+    z_order: i32,
+    console: usize,
+    commands: Vec<DrawCommand>,
+}
+
+/// A single drawing operation, mirroring a [Console] method
+#[derive(Clone, Debug, PartialEq)]
+pub enum DrawCommand {
+    /// Clears the console
+    ClearScreen,
+    /// Clears the console to a background color
+    ClearToColor {
+        /// Background color
+        color: RGBA,
+    },
+    /// Prints a string
+    Print {
+        /// Where the string starts
+        pos: Point,
+        /// The string
+        text: String,
+    },
+    /// Prints a string in color
+    PrintColor {
+        /// Where the string starts
+        pos: Point,
+        /// The string
+        text: String,
+        /// Foreground color
+        fg: RGBA,
+        /// Background color
+        bg: RGBA,
+    },
+    /// Prints a string centered on a line
+    PrintCentered {
+        /// The line to print on
+        y: i32,
+        /// The string
+        text: String,
+    },
+    /// Prints a string in color, centered on a line
+    PrintColorCentered {
+        /// The line to print on
+        y: i32,
+        /// The string
+        text: String,
+        /// Foreground color
+        fg: RGBA,
+        /// Background color
+        bg: RGBA,
+    },
+    /// Sets a single cell
+    Set {
+        /// The cell
+        pos: Point,
+        /// Foreground color
+        fg: RGBA,
+        /// Background color
+        bg: RGBA,
+        /// Glyph index
+        glyph: u16,
+    },
+    /// Sets a single cell's background
+    SetBackground {
+        /// The cell
+        pos: Point,
+        /// Background color
+        bg: RGBA,
+    },
+    /// Draws a filled box with single lines
+    Box {
+        /// Position and size of the box
+        pos: Rect,
+        /// Foreground color
+        fg: RGBA,
+        /// Background color
+        bg: RGBA,
+    },
+    /// Draws a hollow box with single lines
+    HollowBox {
+        /// Position and size of the box
+        pos: Rect,
+        /// Foreground color
+        fg: RGBA,
+        /// Background color
+        bg: RGBA,
+    },
+    /// Draws a filled box with double lines
+    DoubleBox {
+        /// Position and size of the box
+        pos: Rect,
+        /// Foreground color
+        fg: RGBA,
+        /// Background color
+        bg: RGBA,
+    },
+    /// Draws a hollow box with double lines
+    HollowDoubleBox {
+        /// Position and size of the box
+        pos: Rect,
+        /// Foreground color
+        fg: RGBA,
+        /// Background color
+        bg: RGBA,
+    },
+    /// Fills a region with a glyph
+    FillRegion {
+        /// The region
+        pos: Rect,
+        /// Glyph index
+        glyph: u16,
+        /// Foreground color
+        fg: RGBA,
+        /// Background color
+        bg: RGBA,
+    },
+    /// Draws a horizontal progress bar
+    BarHorizontal {
+        /// Where the bar starts
+        pos: Point,
+        /// Width of the bar
+        width: i32,
+        /// Current value
+        n: i32,
+        /// Maximum value
+        max: i32,
+        /// Foreground color
+        fg: RGBA,
+        /// Background color
+        bg: RGBA,
+    },
+    /// Draws a vertical progress bar
+    BarVertical {
+        /// Where the bar starts
+        pos: Point,
+        /// Height of the bar
+        height: i32,
+        /// Current value
+        n: i32,
+        /// Maximum value
+        max: i32,
+        /// Foreground color
+        fg: RGBA,
+        /// Background color
+        bg: RGBA,
+    },
+}
+
+impl DrawCommand {
+    /// Performs the command on a console
+    pub fn apply(&self, console: &mut dyn Console) {
+        match self {
+            DrawCommand::ClearScreen => console.cls(),
+            DrawCommand::ClearToColor { color } => console.cls_bg(*color),
+            DrawCommand::Print { pos, text } => console.print(pos.x, pos.y, text),
+            DrawCommand::PrintColor { pos, text, fg, bg } => {
+                console.print_color(pos.x, pos.y, *fg, *bg, text)
+            }
+            DrawCommand::PrintCentered { y, text } => console.print_centered(*y, text),
+            DrawCommand::PrintColorCentered { y, text, fg, bg } => {
+                console.print_color_centered(*y, *fg, *bg, text)
+            }
+            DrawCommand::Set { pos, fg, bg, glyph } => console.set(pos.x, pos.y, *fg, *bg, *glyph),
+            DrawCommand::SetBackground { pos, bg } => console.set_bg(pos.x, pos.y, *bg),
+            DrawCommand::Box { pos, fg, bg } => {
+                console.draw_box(pos.x1, pos.y1, pos.width(), pos.height(), *fg, *bg)
+            }
+            DrawCommand::HollowBox { pos, fg, bg } => {
+                console.draw_hollow_box(pos.x1, pos.y1, pos.width(), pos.height(), *fg, *bg)
+            }
+            DrawCommand::DoubleBox { pos, fg, bg } => {
+                console.draw_box_double(pos.x1, pos.y1, pos.width(), pos.height(), *fg, *bg)
+            }
+            DrawCommand::HollowDoubleBox { pos, fg, bg } => {
+                console.draw_hollow_box_double(pos.x1, pos.y1, pos.width(), pos.height(), *fg, *bg)
+            }
+            DrawCommand::FillRegion { pos, glyph, fg, bg } => {
+                console.fill_region(*pos, *glyph, *fg, *bg)
+            }
+            DrawCommand::BarHorizontal {
+                pos,
+                width,
+                n,
+                max,
+                fg,
+                bg,
+            } => console.draw_bar_horizontal(pos.x, pos.y, *width, *n, *max, *fg, *bg),
+            DrawCommand::BarVertical {
+                pos,
+                height,
+                n,
+                max,
+                fg,
+                bg,
+            } => console.draw_bar_vertical(pos.x, pos.y, *height, *n, *max, *fg, *bg),
+        }
+    }
+}
+
+/// A list of drawing commands for one console. Batches don't need the Fractal context, only a
+/// clone of its `draw_buffer`, so they can be built inside specs systems or rayon jobs and
+/// submitted from there; the main loop draws everything submitted, lowest z-order first, before
+/// the consoles are rendered.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DrawBatch {
+    /// The console the commands are drawn on
+    pub console: usize,
+    /// The commands, in the order they were added
+    pub commands: Vec<DrawCommand>,
+}
+
+impl DrawBatch {
+    /// Creates an empty batch, targeting console 0
+    pub fn new() -> DrawBatch {
+        DrawBatch {
+            console: 0,
+            commands: Vec::new(),
+        }
+    }
+
+    /// Sets the console the batch is drawn on
+    pub fn target(&mut self, console: usize) -> &mut Self {
+        self.console = console;
+        self
+    }
+
+    /// Queues submission of the batch, to be drawn before the next frame. Batches with a lower
+    /// z-order are drawn first; batches with the same z-order are drawn in the order they were
+    /// submitted. The batch is left empty, targeting the same console, so it can be reused.
+    pub fn submit(&mut self, buffer: &DrawBuffer, z_order: i32) {
+        let commands = std::mem::take(&mut self.commands);
+        buffer.batches.lock().unwrap().push(SubmittedBatch {
+            z_order,
+            console: self.console,
+            commands,
+        });
+    }
+
+    /// Clears the console
+    pub fn cls(&mut self) -> &mut Self {
+        self.commands.push(DrawCommand::ClearScreen);
+        self
+    }
+
+    /// Clears the console to a background color
+    pub fn cls_color<C: Into<RGBA>>(&mut self, color: C) -> &mut Self {
+        self.commands.push(DrawCommand::ClearToColor {
+            color: color.into(),
+        });
+        self
+    }
+
+    /// Prints a string
+    pub fn print<S: ToString>(&mut self, pos: Point, text: S) -> &mut Self {
+        self.commands.push(DrawCommand::Print {
+            pos,
+            text: text.to_string(),
+        });
+        self
+    }
+
+    /// Prints a string in color
+    pub fn print_color<S: ToString, C: Into<RGBA>>(
+        &mut self,
+        pos: Point,
+        text: S,
+        fg: C,
+        bg: C,
+    ) -> &mut Self {
+        self.commands.push(DrawCommand::PrintColor {
+            pos,
+            text: text.to_string(),
+            fg: fg.into(),
+            bg: bg.into(),
+        });
+        self
+    }
+
+    /// Prints a string centered on a line
+    pub fn print_centered<S: ToString>(&mut self, y: i32, text: S) -> &mut Self {
+        self.commands.push(DrawCommand::PrintCentered {
+            y,
+            text: text.to_string(),
+        });
+        self
+    }
+
+    /// Prints a string in color, centered on a line
+    pub fn print_color_centered<S: ToString, C: Into<RGBA>>(
+        &mut self,
+        y: i32,
+        text: S,
+        fg: C,
+        bg: C,
+    ) -> &mut Self {
+        self.commands.push(DrawCommand::PrintColorCentered {
+            y,
+            text: text.to_string(),
+            fg: fg.into(),
+            bg: bg.into(),
+        });
+        self
+    }
+
+    /// Sets a single cell
+    pub fn set<C: Into<RGBA>>(&mut self, pos: Point, fg: C, bg: C, glyph: u16) -> &mut Self {
+        self.commands.push(DrawCommand::Set {
+            pos,
+            fg: fg.into(),
+            bg: bg.into(),
+            glyph,
+        });
+        self
+    }
+
+    /// Sets a single cell's background
+    pub fn set_bg<C: Into<RGBA>>(&mut self, pos: Point, bg: C) -> &mut Self {
+        self.commands
+            .push(DrawCommand::SetBackground { pos, bg: bg.into() });
+        self
+    }
+
+    /// Draws a filled box with single lines
+    pub fn draw_box<C: Into<RGBA>>(&mut self, pos: Rect, fg: C, bg: C) -> &mut Self {
+        self.commands.push(DrawCommand::Box {
+            pos,
+            fg: fg.into(),
+            bg: bg.into(),
+        });
+        self
+    }
+
+    /// Draws a hollow box with single lines
+    pub fn draw_hollow_box<C: Into<RGBA>>(&mut self, pos: Rect, fg: C, bg: C) -> &mut Self {
+        self.commands.push(DrawCommand::HollowBox {
+            pos,
+            fg: fg.into(),
+            bg: bg.into(),
+        });
+        self
+    }
+
+    /// Draws a filled box with double lines
+    pub fn draw_double_box<C: Into<RGBA>>(&mut self, pos: Rect, fg: C, bg: C) -> &mut Self {
+        self.commands.push(DrawCommand::DoubleBox {
+            pos,
+            fg: fg.into(),
+            bg: bg.into(),
+        });
+        self
+    }
+
+    /// Draws a hollow box with double lines
+    pub fn draw_hollow_double_box<C: Into<RGBA>>(&mut self, pos: Rect, fg: C, bg: C) -> &mut Self {
+        self.commands.push(DrawCommand::HollowDoubleBox {
+            pos,
+            fg: fg.into(),
+            bg: bg.into(),
+        });
+        self
+    }
+
+    /// Fills a region with a glyph
+    pub fn fill_region<C: Into<RGBA>>(&mut self, pos: Rect, glyph: u16, fg: C, bg: C) -> &mut Self {
+        self.commands.push(DrawCommand::FillRegion {
+            pos,
+            glyph,
+            fg: fg.into(),
+            bg: bg.into(),
+        });
+        self
+    }
+
+    /// Draws a horizontal progress bar
+    pub fn bar_horizontal<C: Into<RGBA>>(
+        &mut self,
+        pos: Point,
+        width: i32,
+        n: i32,
+        max: i32,
+        fg: C,
+        bg: C,
+    ) -> &mut Self {
+        self.commands.push(DrawCommand::BarHorizontal {
+            pos,
+            width,
+            n,
+            max,
+            fg: fg.into(),
+            bg: bg.into(),
+        });
+        self
+    }
+
+    /// Draws a vertical progress bar
+    pub fn bar_vertical<C: Into<RGBA>>(
+        &mut self,
+        pos: Point,
+        height: i32,
+        n: i32,
+        max: i32,
+        fg: C,
+        bg: C,
+    ) -> &mut Self {
+        self.commands.push(DrawCommand::BarVertical {
+            pos,
+            height,
+            n,
+            max,
+            fg: fg.into(),
+            bg: bg.into(),
+        });
+        self
+    }
+}
+
+impl Default for DrawBatch {
+    fn default() -> Self {
+        DrawBatch::new()
+    }
+}
+
+/// Draws every submitted batch onto its console, lowest z-order first, and empties the buffer.
+/// Batches aimed at consoles that don't exist are dropped.
+pub(crate) fn render_draw_buffer(fractal: &mut Fractal) {
+    let mut batches = std::mem::take(&mut *fractal.draw_buffer.batches.lock().unwrap());
+    batches.sort_by_key(|batch| batch.z_order);
+
+    for batch in batches {
+        if let Some(target) = fractal.consoles.get_mut(batch.console) {
+            for command in &batch.commands {
+                command.apply(target.console.as_mut());
+            }
+        }
+    }
+}
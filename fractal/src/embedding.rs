@@ -32,7 +32,7 @@ impl Dictionary {
     /// Retrieves a font from the dictionary
     pub fn get_resource(&self, path: String) -> Option<&'static [u8]> {
         if self.entries.contains_key(&path) {
-            return Some(&self.entries[&path]);
+            return Some(self.entries[&path]);
         }
         None
     }
@@ -42,3 +42,12 @@ impl Dictionary {
         self.entries.insert(path, bytes);
     }
 }
+
+/// True if a resource has been embedded under this path
+pub fn is_embedded(path: &str) -> bool {
+    EMBED
+        .lock()
+        .unwrap()
+        .get_resource(path.to_string())
+        .is_some()
+}
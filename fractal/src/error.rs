@@ -0,0 +1,72 @@
+//! The error type returned when the context or its assets can't be set up
+
+use std::error;
+use std::fmt;
+use std::io;
+
+/// Something that went wrong while opening the window or loading an asset
+#[derive(Debug)]
+pub enum FractalError {
+    /// The window or OpenGL context couldn't be created
+    Window(String),
+    /// A shader failed to compile or link
+    ShaderCompile {
+        /// Which stage failed: "vertex", "fragment" or "link"
+        stage: &'static str,
+        /// The driver's info log
+        log: String,
+    },
+    /// An image file couldn't be decoded
+    ImageDecode {
+        /// Path or embedded resource name of the image
+        path: String,
+        /// What the decoder reported
+        message: String,
+    },
+    /// A TrueType/OpenType font file couldn't be parsed
+    FontDecode(String),
+    /// A resource is neither embedded nor on disk
+    MissingResource(String),
+    /// A console asked for a font that was never added to the builder
+    UnknownFont(String),
+    /// No consoles were added, so there would be nothing to draw on
+    NoConsoles,
+    /// Any other I/O failure
+    Io(io::Error),
+}
+
+impl fmt::Display for FractalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FractalError::Window(e) => write!(f, "Unable to create the window: {}", e),
+            FractalError::ShaderCompile { stage, log } => {
+                write!(f, "Unable to compile {} shader: {}", stage, log)
+            }
+            FractalError::ImageDecode { path, message } => {
+                write!(f, "Unable to decode image {}: {}", path, message)
+            }
+            FractalError::FontDecode(path) => write!(f, "Unable to parse font {}", path),
+            FractalError::MissingResource(path) => write!(f, "Resource not found: {}", path),
+            FractalError::UnknownFont(path) => {
+                write!(f, "Console uses a font that wasn't added: {}", path)
+            }
+            FractalError::NoConsoles => write!(f, "No consoles were added"),
+            FractalError::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl error::Error for FractalError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            FractalError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FractalError {
+    fn from(e: io::Error) -> FractalError {
+        FractalError::Io(e)
+    }
+}
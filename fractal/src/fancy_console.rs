@@ -0,0 +1,373 @@
+//! A console whose glyphs can sit anywhere, rotate and scale
+
+use crate::backend::font::Font;
+use crate::backend::Backend;
+use crate::codepage437::string_to_cp437;
+use crate::color;
+use crate::color::RGBA;
+use crate::console::Console;
+use crate::geometry::Rect;
+use crate::gui_helpers;
+use crate::rex::XpColor;
+use crate::rex::XpLayer;
+use std::any::Any;
+
+/// Internal storage structure for fancy tiles.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct FancyTile {
+    /// X position of the glyph's left edge, in characters
+    pub x: f32,
+    /// Y position of the glyph's top edge, in characters
+    pub y: f32,
+    /// Glyphs with a higher z-order are drawn on top of lower ones
+    pub z_order: i32,
+    /// Rotation around the glyph's center, in radians. Positive values turn clockwise.
+    pub rotation: f32,
+    /// Horizontal and vertical scale, around the glyph's center
+    pub scale: (f32, f32),
+    /// The CP437 value to render the tile as
+    pub glyph: u16,
+    /// The Color of the glyph
+    pub fg: RGBA,
+    /// The Color behind the glyph
+    pub bg: RGBA,
+}
+
+impl FancyTile {
+    /// The tile's center, in pixels from the top-left of the output.
+    pub fn center_pixels(&self, cell_width: f32, cell_height: f32) -> (f32, f32) {
+        ((self.x + 0.5) * cell_width, (self.y + 0.5) * cell_height)
+    }
+
+    /// The four corners of the tile in pixels from the top-left of the output, after scaling
+    /// and rotation. They come in the order top-right, bottom-right, bottom-left, top-left.
+    pub fn corners_pixels(&self, cell_width: f32, cell_height: f32) -> [(f32, f32); 4] {
+        let (cx, cy) = self.center_pixels(cell_width, cell_height);
+        let hw = cell_width * self.scale.0 / 2.0;
+        let hh = cell_height * self.scale.1 / 2.0;
+        let (sin, cos) = self.rotation.sin_cos();
+        let corner = |dx: f32, dy: f32| (cx + dx * cos - dy * sin, cy + dx * sin + dy * cos);
+
+        [
+            corner(hw, -hh),
+            corner(hw, hh),
+            corner(-hw, hh),
+            corner(-hw, -hh),
+        ]
+    }
+}
+
+/// A fancy console. Every glyph has its own floating-point position, rotation, scale and
+/// z-order, which allows smooth movement between cells.
+pub struct FancyConsole {
+    /// Width of the console in characters
+    pub width: u32,
+    /// Height of the console in characters
+    pub height: u32,
+    /// The tiles that have been set
+    pub tiles: Vec<FancyTile>,
+    /// Boolean that tells the engine if the console needs to be redrawn
+    pub is_dirty: bool,
+
+    // To handle offset tiles for people who want thin walls between tiles
+    offset_x: f32,
+    offset_y: f32,
+
+    backing: usize,
+}
+
+impl FancyConsole {
+    /// Initializes the console, ready to add to FRACTAL's console list.
+    pub fn init(width: u32, height: u32, backend: &mut dyn Backend) -> Box<FancyConsole> {
+        let new_console = FancyConsole {
+            width,
+            height,
+            tiles: Vec::new(),
+            is_dirty: true,
+            offset_x: 0.0,
+            offset_y: 0.0,
+            backing: backend.create_fancy_backing(width, height),
+        };
+
+        Box::new(new_console)
+    }
+
+    /// Adds a glyph at a floating-point position, with rotation (in radians), scale and
+    /// z-order.
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_fancy(
+        &mut self,
+        x: f32,
+        y: f32,
+        z_order: i32,
+        rotation: f32,
+        scale: (f32, f32),
+        fg: RGBA,
+        bg: RGBA,
+        glyph: u16,
+    ) {
+        self.is_dirty = true;
+        self.tiles.push(FancyTile {
+            x,
+            y,
+            z_order,
+            rotation,
+            scale,
+            glyph,
+            fg,
+            bg,
+        });
+    }
+
+    /// Returns true if the tile is an untransformed glyph sitting exactly on x/y, as placed by
+    /// the regular Console methods.
+    fn is_plain_at(tile: &FancyTile, x: i32, y: i32) -> bool {
+        (tile.x - x as f32).abs() < f32::EPSILON
+            && (tile.y - y as f32).abs() < f32::EPSILON
+            && tile.z_order == 0
+            && tile.rotation.abs() < f32::EPSILON
+            && (tile.scale.0 - 1.0).abs() < f32::EPSILON
+            && (tile.scale.1 - 1.0).abs() < f32::EPSILON
+    }
+
+    fn rebuild_vertices(&mut self, font: &Font, backend: &mut dyn Backend) {
+        // Stable, so glyphs with the same z-order keep the order they were added in
+        self.tiles.sort_by_key(|t| t.z_order);
+        backend.rebuild_fancy(
+            self.backing,
+            font,
+            self.width,
+            self.height,
+            &self.tiles,
+            self.offset_x,
+            self.offset_y,
+        );
+    }
+}
+
+impl Console for FancyConsole {
+    /// Check if the console has changed, and if it has rebuild the backing buffer.
+    fn rebuild_if_dirty(&mut self, font: &Font, backend: &mut dyn Backend) {
+        if self.is_dirty {
+            self.rebuild_vertices(font, backend);
+            self.is_dirty = false;
+        }
+    }
+
+    fn get_char_size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Glyphs are positioned in pixels, so they have to be rebuilt for the new size.
+    fn resize_pixels(&mut self, _width: u32, _height: u32) {
+        self.is_dirty = true;
+    }
+
+    fn set_char_size(&mut self, width: u32, height: u32) {
+        if (width, height) == (self.width, self.height) {
+            return;
+        }
+        self.width = width;
+        self.height = height;
+        self.cls();
+    }
+
+    /// Sends the console to the backend.
+    fn draw(&mut self, font: &Font, shader_index: usize, backend: &mut dyn Backend) {
+        backend.draw_fancy(self.backing, font, shader_index);
+        self.is_dirty = false;
+    }
+
+    /// Translate an x/y into an array index.
+    fn at(&self, x: i32, y: i32) -> usize {
+        (((self.height - 1 - y as u32) * self.width) + x as u32) as usize
+    }
+
+    /// Clears the screen.
+    fn cls(&mut self) {
+        self.is_dirty = true;
+        self.tiles.clear();
+    }
+
+    /// Clears the screen. A fancy console has no background to fill, so the color is ignored.
+    fn cls_bg(&mut self, _background: RGBA) {
+        self.is_dirty = true;
+        self.tiles.clear();
+    }
+
+    /// Prints a string at x/y, in white on black.
+    fn print(&mut self, x: i32, y: i32, output: &str) {
+        self.print_color(x, y, color::WHITE.into(), color::BLACK.into(), output);
+    }
+
+    /// Prints a string at x/y, with foreground and background colors.
+    fn print_color(&mut self, x: i32, y: i32, fg: RGBA, bg: RGBA, output: &str) {
+        let bytes = string_to_cp437(output);
+        for (i, glyph) in bytes.into_iter().enumerate() {
+            self.set(x + i as i32, y, fg, bg, glyph);
+        }
+    }
+
+    /// Sets a single cell in the console. This replaces an untransformed glyph already placed
+    /// at x/y, but leaves any fancy glyphs in the cell alone.
+    fn set(&mut self, x: i32, y: i32, fg: RGBA, bg: RGBA, glyph: u16) {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return;
+        }
+        self.is_dirty = true;
+        if let Some(tile) = self
+            .tiles
+            .iter_mut()
+            .find(|t| FancyConsole::is_plain_at(t, x, y))
+        {
+            tile.glyph = glyph;
+            tile.fg = fg;
+            tile.bg = bg;
+        } else {
+            self.set_fancy(x as f32, y as f32, 0, 0.0, (1.0, 1.0), fg, bg, glyph);
+        }
+    }
+
+    /// Sets the background of the untransformed glyph at x/y, if there is one.
+    fn set_bg(&mut self, x: i32, y: i32, bg: RGBA) {
+        if let Some(tile) = self
+            .tiles
+            .iter_mut()
+            .find(|t| FancyConsole::is_plain_at(t, x, y))
+        {
+            tile.bg = bg;
+            self.is_dirty = true;
+        }
+    }
+
+    /// Draws a box, starting at x/y with the extents width/height using CP437 line characters
+    fn draw_box(&mut self, sx: i32, sy: i32, width: i32, height: i32, fg: RGBA, bg: RGBA) {
+        gui_helpers::draw_box(self, sx, sy, width, height, fg, bg);
+    }
+
+    /// Draws a box, starting at x/y with the extents width/height using CP437 line characters
+    fn draw_hollow_box(&mut self, sx: i32, sy: i32, width: i32, height: i32, fg: RGBA, bg: RGBA) {
+        gui_helpers::draw_hollow_box(self, sx, sy, width, height, fg, bg);
+    }
+
+    /// Draws a box, starting at x/y with the extents width/height using CP437 double line characters
+    fn draw_box_double(&mut self, sx: i32, sy: i32, width: i32, height: i32, fg: RGBA, bg: RGBA) {
+        gui_helpers::draw_box_double(self, sx, sy, width, height, fg, bg);
+    }
+
+    /// Draws a box, starting at x/y with the extents width/height using CP437 double line characters
+    fn draw_hollow_box_double(
+        &mut self,
+        sx: i32,
+        sy: i32,
+        width: i32,
+        height: i32,
+        fg: RGBA,
+        bg: RGBA,
+    ) {
+        gui_helpers::draw_hollow_box_double(self, sx, sy, width, height, fg, bg);
+    }
+
+    /// Fills a rectangle with the specified rendering information
+    fn fill_region(&mut self, target: Rect, glyph: u16, fg: RGBA, bg: RGBA) {
+        target.for_each(|point| {
+            self.set(point.x, point.y, fg, bg, glyph);
+        });
+    }
+
+    /// Gets the topmost glyph whose position falls in the cell at x/y, if any
+    fn get(&self, x: i32, y: i32) -> Option<(&u16, &RGBA, &RGBA)> {
+        self.tiles
+            .iter()
+            .filter(|t| t.x.floor() as i32 == x && t.y.floor() as i32 == y)
+            .max_by_key(|t| t.z_order)
+            .map(|t| (&t.glyph, &t.fg, &t.bg))
+    }
+
+    /// Draws a horizontal progress bar
+    fn draw_bar_horizontal(
+        &mut self,
+        sx: i32,
+        sy: i32,
+        width: i32,
+        n: i32,
+        max: i32,
+        fg: RGBA,
+        bg: RGBA,
+    ) {
+        gui_helpers::draw_bar_horizontal(self, sx, sy, width, n, max, fg, bg);
+    }
+
+    /// Draws a vertical progress bar
+    fn draw_bar_vertical(
+        &mut self,
+        sx: i32,
+        sy: i32,
+        height: i32,
+        n: i32,
+        max: i32,
+        fg: RGBA,
+        bg: RGBA,
+    ) {
+        gui_helpers::draw_bar_vertical(self, sx, sy, height, n, max, fg, bg);
+    }
+
+    /// Prints text, centered to the whole console width, at vertical location y.
+    fn print_centered(&mut self, y: i32, text: &str) {
+        self.print(
+            (self.width as i32 / 2) - (text.to_string().len() as i32 / 2),
+            y,
+            text,
+        );
+    }
+
+    /// Prints text in color, centered to the whole console width, at vertical location y.
+    fn print_color_centered(&mut self, y: i32, fg: RGBA, bg: RGBA, text: &str) {
+        self.print_color(
+            (self.width as i32 / 2) - (text.to_string().len() as i32 / 2),
+            y,
+            fg,
+            bg,
+            text,
+        );
+    }
+
+    /// Saves the layer to an XpFile structure. Glyphs are snapped to the cell they start in,
+    /// and rotation and scale are lost; cells without a glyph get REX Paint's transparent
+    /// background.
+    fn to_xp_layer(&self) -> XpLayer {
+        let mut layer = XpLayer::new(self.width as usize, self.height as usize);
+        for cell in layer.cells.iter_mut() {
+            cell.bg = XpColor::TRANSPARENT;
+        }
+
+        let mut tiles = self.tiles.clone();
+        tiles.sort_by_key(|t| t.z_order);
+        for tile in &tiles {
+            let x = tile.x.floor() as i32;
+            let y = tile.y.floor() as i32;
+            if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+                continue;
+            }
+            let cell = layer.get_mut(x as usize, y as usize).unwrap();
+            cell.ch = u32::from(tile.glyph);
+            cell.fg = tile.fg.to_xp();
+            cell.bg = tile.bg.to_xp();
+        }
+
+        layer
+    }
+
+    /// Sets an offset to total console rendering, useful for layers that
+    /// draw between tiles. Offsets are specified as a percentage of total
+    /// character size; so -0.5 will offset half a character to the left/top.
+    fn set_offset(&mut self, x: f32, y: f32) {
+        self.is_dirty = true;
+        self.offset_x = x * (2.0 / self.width as f32);
+        self.offset_y = y * (2.0 / self.height as f32);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -2734,6 +2734,7 @@ impl FastNoise {
     #[allow(clippy::many_single_char_names)]
     #[allow(clippy::collapsible_if)]
     #[allow(clippy::suspicious_else_formatting)]
+    #[allow(clippy::needless_late_init)]
     fn single_simplex3d(&self, offset: u8, x: f32, y: f32, z: f32) -> f32 {
         let mut t: f32 = (x + y + z) * F3;
         let i = fast_floor(x + t);
@@ -2967,6 +2968,7 @@ impl FastNoise {
     }
 
     #[allow(clippy::many_single_char_names)]
+    #[allow(clippy::needless_late_init)]
     fn single_simplex(&self, offset: u8, x: f32, y: f32) -> f32 {
         let mut t: f32 = (x + y) * F2;
         let i = fast_floor(x + t);
@@ -3039,6 +3041,7 @@ impl FastNoise {
 
     #[allow(dead_code)]
     #[allow(clippy::many_single_char_names)]
+    #[allow(clippy::needless_late_init)]
     fn single_simplex4d(&self, offset: u8, x: f32, y: f32, z: f32, w: f32) -> f32 {
         let n0: f32;
         let n1: f32;
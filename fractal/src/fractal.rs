@@ -1,19 +1,35 @@
 //! The Actual core of the fractal engine
 
+use crate::animation::{Animation, AnimationId, Animator};
 use crate::backend::font::Font;
+use crate::backend::headless::HeadlessBackend;
 use crate::backend::init::init_raw;
 use crate::backend::mainloop;
-use crate::backend::shader::Shader;
-use crate::backend::FractalPlatform;
-use crate::color::RGB;
+use crate::backend::post_process::{PostEffect, Uniform};
+use crate::backend::sprite_sheet::SpriteSheet;
+use crate::backend::{Backend, SHADER_NO_BG, SHADER_WITH_BG};
+use crate::color::RGBA;
 use crate::console::Console;
+use crate::draw_batch::DrawBuffer;
+use crate::error::FractalError;
 use crate::geometry::Rect;
+use crate::hot_reload::{Asset, HotReload};
+use crate::input::Input;
+use crate::input_map::InputMap;
+use crate::particles::{Emitter, Particle, ParticleSystem};
+use crate::perf::Perf;
+use crate::replay::Replay;
 use crate::rex::XpFile;
 use crate::rex::XpLayer;
 use crate::simple_console::SimpleConsole;
+use crate::viewport::{ResizePolicy, Viewport};
 use crate::GameState;
 use glutin::event::VirtualKeyCode;
+use image::RgbaImage;
 use std::any::Any;
+use std::fs;
+use std::io;
+use std::path::Path;
 
 /// A display console, used internally to provide console render support.
 /// Public in case you want to play with it, or access it directly.
@@ -24,39 +40,79 @@ pub struct DisplayConsole {
     pub shader_index: usize,
     /// font index
     pub font_index: usize,
+    /// Size in characters the console was registered with, which `ResizePolicy::GrowConsoles`
+    /// scales from
+    pub base_char_size: (u32, u32),
 }
 
 /// An FRACTAL context.
 pub struct Fractal {
-    /// A OpenGL backend context
-    pub backend: FractalPlatform,
+    /// The rendering backend
+    pub backend: Box<dyn Backend>,
     /// Width of the window in pixels
     pub width_pixels: u32,
     /// Height of the window in pixels
     pub height_pixels: u32,
+    /// Size of the window the consoles were laid out for, in pixels
+    pub base_size_pixels: (u32, u32),
+    /// What happens to the consoles when the window is resized
+    pub resize_policy: ResizePolicy,
+    /// The part of the window the consoles are drawn into
+    pub viewport: Viewport,
     /// A Vector of fonts the game will use
     pub fonts: Vec<Font>,
-    /// A Vector of shaders the game will use
-    pub shaders: Vec<Shader>,
+    /// A Vector of sprite sheets the game will use
+    pub sprite_sheets: Vec<SpriteSheet>,
+    /// REX Paint files loaded with `load_xp`, which are reloaded along with the fonts
+    pub xp_files: Vec<XpFile>,
     /// A Vector of Consoles the game can display
     pub consoles: Vec<DisplayConsole>,
     /// The current frames per second
     pub fps: f32,
     /// The time in milliseconds it took to process the last frame
     pub frame_time_ms: f32,
+    /// Timings of recent frames, and the overlay that shows them
+    pub perf: Perf,
+    /// Running tile and console animations
+    pub animations: Animator,
+    /// Live glyph particles and their emitters
+    pub particles: ParticleSystem,
+    /// Draw batches submitted since the last frame. Clone it to submit from other threads.
+    pub draw_buffer: DrawBuffer,
+    /// True if the next frame drawn should be captured; see `request_screenshot`
+    pub screenshot_requested: bool,
+    /// The last frame captured, until it is taken
+    pub screenshot: Option<RgbaImage>,
+    /// Frame rate the main loop is held to, if any. The loop sleeps away the rest of each frame.
+    pub target_fps: Option<f32>,
+    /// Seconds between calls to `GameState::update`, if fixed-timestep updates are on
+    pub fixed_timestep: Option<f32>,
+    /// How far the frame being drawn is between the last fixed update and the next, 0..1. Use it
+    /// to interpolate positions so movement stays smooth at any frame rate. Always 0 without a
+    /// fixed timestep.
+    pub frame_alpha: f32,
     /// Index to the current console being rendered
     pub active_console: usize,
-    /// Contains a keycode if a key was pressed
+    /// Keyboard and mouse input for this frame, in full
+    pub input: Input,
+    /// The game's actions and the keys and buttons bound to them
+    pub input_map: InputMap,
+    /// Whether input is being recorded to, or played back from, a file
+    pub replay: Replay,
+    /// Watches the files behind fonts, shaders and xp files, if switched on
+    pub hot_reload: HotReload,
+    /// Contains a keycode if a key was pressed. If several were, it holds the last one;
+    /// `input` has them all.
     pub key: Option<VirtualKeyCode>,
     /// The position of the mouse
     pub mouse_pos: (i32, i32),
-    /// True if the mouse was left clicked
+    /// True if the left mouse button was pressed this frame
     pub left_click: bool,
-    /// True if a shift was held down
+    /// True if a shift was held down when `key` was pressed
     pub shift: bool,
-    /// True if Control was held down
+    /// True if Control was held down when `key` was pressed
     pub control: bool,
-    /// True if alt was held down
+    /// True if alt was held down when `key` was pressed
     pub alt: bool,
     /// Link to a website ? TODO
     pub web_button: Option<String>,
@@ -66,6 +122,8 @@ pub struct Fractal {
     pub post_scanlines: bool,
     /// Should console use included screenburn shader
     pub post_screenburn: bool,
+    /// User-defined post-processing effects, applied in order before scanlines
+    pub post_effects: Vec<PostEffect>,
 }
 
 impl Fractal {
@@ -74,25 +132,79 @@ impl Fractal {
         width_pixels: u32,
         height_pixels: u32,
         window_title: S,
-    ) -> Fractal {
+    ) -> Result<Fractal, FractalError> {
         init_raw(width_pixels, height_pixels, window_title)
     }
 
+    /// Creates a context that renders through the provided backend.
+    pub fn init_with_backend(
+        backend: Box<dyn Backend>,
+        width_pixels: u32,
+        height_pixels: u32,
+    ) -> Fractal {
+        Fractal {
+            backend,
+            width_pixels,
+            height_pixels,
+            base_size_pixels: (width_pixels, height_pixels),
+            resize_policy: ResizePolicy::default(),
+            viewport: Viewport::full(width_pixels, height_pixels),
+            fonts: Vec::new(),
+            sprite_sheets: Vec::new(),
+            xp_files: Vec::new(),
+            consoles: Vec::new(),
+            fps: 0.0,
+            frame_time_ms: 0.0,
+            perf: Perf::new(),
+            animations: Animator::new(),
+            particles: ParticleSystem::new(),
+            draw_buffer: DrawBuffer::default(),
+            screenshot_requested: false,
+            screenshot: None,
+            target_fps: None,
+            fixed_timestep: None,
+            frame_alpha: 0.0,
+            active_console: 0,
+            input: Input::new(),
+            input_map: InputMap::new(),
+            replay: Replay::Off,
+            hot_reload: HotReload::new(),
+            key: None,
+            mouse_pos: (0, 0),
+            left_click: false,
+            shift: false,
+            control: false,
+            alt: false,
+            web_button: None,
+            quitting: false,
+            post_scanlines: false,
+            post_screenburn: false,
+            post_effects: Vec::new(),
+        }
+    }
+
+    /// Creates a context without a window, that renders into memory. See [HeadlessBackend].
+    pub fn init_headless(width_pixels: u32, height_pixels: u32) -> Fractal {
+        Fractal::init_with_backend(
+            Box::new(HeadlessBackend::new(width_pixels, height_pixels)),
+            width_pixels,
+            height_pixels,
+        )
+    }
+
     /// Quick initialization for when you just want an 8x8 font terminal
     pub fn init_simple8x8<S: ToString>(
         width_chars: u32,
         height_chars: u32,
         window_title: S,
         path_to_shaders: S,
-    ) -> Fractal {
+    ) -> Result<Fractal, FractalError> {
         let font_path = format!("{}/terminal8x8.png", &path_to_shaders.to_string());
-        let mut context = Fractal::init_raw(width_chars * 8, height_chars * 8, window_title);
-        let font = context.register_font(Font::load(&font_path, (8, 8)));
-        context.register_console(
-            SimpleConsole::init(width_chars, height_chars, &context.backend),
-            font,
-        );
-        context
+        let mut context = Fractal::init_raw(width_chars * 8, height_chars * 8, window_title)?;
+        let font = context.register_font(Font::load(&font_path, (8, 8))?)?;
+        let console = SimpleConsole::init(width_chars, height_chars, context.backend.as_mut());
+        context.register_console(console, font);
+        Ok(context)
     }
 
     /// Quick initialization for when you just want an 8x16 VGA font terminal
@@ -101,33 +213,35 @@ impl Fractal {
         height_chars: u32,
         window_title: S,
         path_to_shaders: S,
-    ) -> Fractal {
+    ) -> Result<Fractal, FractalError> {
         let font_path = format!("{}/vga8x16.png", &path_to_shaders.to_string());
-        let mut context = Fractal::init_raw(width_chars * 8, height_chars * 16, window_title);
-        let font = context.register_font(Font::load(&font_path, (8, 16)));
-        context.register_console(
-            SimpleConsole::init(width_chars, height_chars, &context.backend),
-            font,
-        );
-        context
-    }
-
-    /// Registers a font, and returns its handle number. Also loads it into OpenGL.
-    pub fn register_font(&mut self, mut font: Font) -> usize {
-        font.setup_gl_texture(&self.backend);
-        font.bind_texture(&self.backend);
+        let mut context = Fractal::init_raw(width_chars * 8, height_chars * 16, window_title)?;
+        let font = context.register_font(Font::load(&font_path, (8, 16))?)?;
+        let console = SimpleConsole::init(width_chars, height_chars, context.backend.as_mut());
+        context.register_console(console, font);
+        Ok(context)
+    }
+
+    /// Registers a font, and returns its handle number. Also loads it into the backend.
+    pub fn register_font(&mut self, mut font: Font) -> Result<usize, FractalError> {
+        self.backend.setup_font(&mut font)?;
+        let handle = self.fonts.len();
+        self.hot_reload
+            .watch(&font.bitmap_file, Asset::Font(handle));
         self.fonts.push(font);
-        self.fonts.len() - 1
+        Ok(handle)
+    }
+
+    /// Registers a sprite sheet, and returns its handle number. Also loads it into the backend.
+    pub fn register_sprite_sheet(&mut self, mut sheet: SpriteSheet) -> Result<usize, FractalError> {
+        self.backend.setup_sprite_sheet(&mut sheet)?;
+        self.sprite_sheets.push(sheet);
+        Ok(self.sprite_sheets.len() - 1)
     }
 
     /// Registers a new console terminal for output, and returns its handle number.
     pub fn register_console(&mut self, new_console: Box<dyn Console>, font_index: usize) -> usize {
-        self.consoles.push(DisplayConsole {
-            console: new_console,
-            font_index,
-            shader_index: 0,
-        });
-        self.consoles.len() - 1
+        self.add_console(new_console, font_index, SHADER_WITH_BG)
     }
 
     /// Registers a new console terminal for output, and returns its handle number. This variant requests
@@ -137,11 +251,25 @@ impl Fractal {
         new_console: Box<dyn Console>,
         font_index: usize,
     ) -> usize {
+        self.add_console(new_console, font_index, SHADER_NO_BG)
+    }
+
+    fn add_console(
+        &mut self,
+        new_console: Box<dyn Console>,
+        font_index: usize,
+        shader_index: usize,
+    ) -> usize {
+        let base_char_size = new_console.get_char_size();
         self.consoles.push(DisplayConsole {
             console: new_console,
             font_index,
-            shader_index: 1,
+            shader_index,
+            base_char_size,
         });
+        if self.resize_policy == ResizePolicy::GrowConsoles {
+            self.apply_resize_policy();
+        }
         self.consoles.len() - 1
     }
 
@@ -151,21 +279,17 @@ impl Fractal {
     }
 
     /// Applies the current physical mouse position to the active console, and translates the coordinates into that console's coordinate space.
+    /// Positions over the bars around a letterboxed viewport are clamped to the nearest cell.
     pub fn mouse_pos(&self) -> (i32, i32) {
         let max_sizes = self.consoles[self.active_console].console.get_char_size();
+        self.viewport.to_cell(self.mouse_pos, max_sizes)
+    }
 
-        (
-            iclamp(
-                self.mouse_pos.0 * max_sizes.0 as i32 / self.width_pixels as i32,
-                0,
-                max_sizes.0 as i32 - 1,
-            ),
-            iclamp(
-                self.mouse_pos.1 * max_sizes.1 as i32 / self.height_pixels as i32,
-                0,
-                max_sizes.1 as i32 - 1,
-            ),
-        )
+    /// The active console's cell under the mouse, or None if the mouse is over the bars around
+    /// a letterboxed or keep-aspect viewport.
+    pub fn mouse_cell(&self) -> Option<(i32, i32)> {
+        let max_sizes = self.consoles[self.active_console].console.get_char_size();
+        self.viewport.cell_at(self.mouse_pos, max_sizes)
     }
 
     /// Tells the game to quit
@@ -173,9 +297,9 @@ impl Fractal {
         self.quitting = true;
     }
 
-    /// Render a REX Paint (https://www.gridsagegames.com/rexpaint/) file as a sprite.
-    /// The sprite will be offset by offset_x and offset_y.
-    /// Transparent cells will not be rendered.
+    // Render a REX Paint (https://www.gridsagegames.com/rexpaint/) file as a sprite.
+    // The sprite will be offset by offset_x and offset_y.
+    // Transparent cells will not be rendered.
     // pub fn render_xp_sprite(&mut self, xp: &XpFile, x: i32, y: i32) {
     //     rex::xp_to_console(xp, &mut self.consoles[self.active_console].console, x, y);
     // }
@@ -198,6 +322,214 @@ impl Fractal {
         xp
     }
 
+    /// Asks for the next frame to be captured as it is presented: after post-processing, and
+    /// with draw batches, animations and particles applied. Collect it with `take_screenshot`
+    /// once the frame has been drawn.
+    pub fn request_screenshot(&mut self) {
+        self.screenshot_requested = true;
+    }
+
+    /// The frame captured for the last `request_screenshot`, if it has been drawn
+    pub fn take_screenshot(&mut self) -> Option<RgbaImage> {
+        self.screenshot.take()
+    }
+
+    /// Draws and presents a frame straight away, and returns it as captured for
+    /// `request_screenshot`. Meant for tests and tools that drive rendering themselves; from
+    /// inside a tick, request a screenshot instead so the frame isn't drawn twice.
+    pub fn screenshot(&mut self) -> RgbaImage {
+        self.request_screenshot();
+        mainloop::render(self);
+        self.take_screenshot()
+            .expect("render captures a requested frame")
+    }
+
+    /// Captures a single console as an image, without post-processing. The console is drawn as
+    /// it stands, so draw batches, animations and particles that haven't been applied to it by
+    /// a frame yet are left out.
+    pub fn screenshot_console(&mut self, console: usize) -> RgbaImage {
+        mainloop::capture_console(self, console)
+    }
+
+    /// Draws a frame like `screenshot`, and saves it as a PNG file.
+    pub fn save_screenshot<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        self.screenshot().save(path)
+    }
+
+    /// Replaces the action bindings; see [InputMap]
+    pub fn with_input_map(&mut self, input_map: InputMap) {
+        self.input_map = input_map;
+    }
+
+    /// True if any key or button bound to the action was pressed this frame
+    pub fn action_pressed<A: ToString>(&self, action: A) -> bool {
+        self.input_map.pressed(&self.input, action)
+    }
+
+    /// True while any key or button bound to the action is held down
+    pub fn action_held<A: ToString>(&self, action: A) -> bool {
+        self.input_map.held(&self.input, action)
+    }
+
+    /// Starts recording every frame's input to a replay file, along with the seed the game's
+    /// random number generator was created with (see `RandomNumberGenerator::seeded`). Particle
+    /// effects are reseeded with it too.
+    pub fn record_input<P: AsRef<Path>>(&mut self, path: P, seed: u64) -> io::Result<()> {
+        self.replay = Replay::record(path, seed)?;
+        self.particles.seed(seed);
+        Ok(())
+    }
+
+    /// Plays back a replay file, one recorded frame per frame, instead of the player's input.
+    /// Returns the seed to create the game's random number generator with, so the run plays
+    /// out the same way.
+    pub fn play_input<P: AsRef<Path>>(&mut self, path: P) -> io::Result<u64> {
+        let (replay, seed) = Replay::play(path)?;
+        self.replay = replay;
+        self.particles.seed(seed);
+        Ok(seed)
+    }
+
+    /// Compiles a post-processing fragment shader and appends it to the effect chain, returning
+    /// its handle number. See [PostEffect] for what the shader receives. Fails with the
+    /// compiler's log if the shader doesn't compile.
+    pub fn register_post_effect<S: ToString>(
+        &mut self,
+        name: S,
+        fragment_shader: &str,
+    ) -> Result<usize, FractalError> {
+        let handle = self.backend.create_post_effect(fragment_shader)?;
+        self.post_effects.push(PostEffect::new(name, handle));
+        Ok(self.post_effects.len() - 1)
+    }
+
+    /// Like register_post_effect, but reads the fragment shader from a file, which is watched
+    /// for changes when hot reloading is on.
+    pub fn register_post_effect_file<S: ToString, P: AsRef<Path>>(
+        &mut self,
+        name: S,
+        path: P,
+    ) -> Result<usize, FractalError> {
+        let source = fs::read_to_string(&path)?;
+        let index = self.register_post_effect(name, &source)?;
+        let handle = self.post_effects[index].handle;
+        self.hot_reload.watch(path, Asset::PostEffect(handle));
+        Ok(index)
+    }
+
+    /// Looks up a post-processing effect's handle number by name.
+    pub fn post_effect_index(&self, name: &str) -> Option<usize> {
+        self.post_effects.iter().position(|e| e.name == name)
+    }
+
+    /// Sets a uniform on a post-processing effect. It keeps its value until set again.
+    pub fn set_post_uniform<S: ToString>(&mut self, effect: usize, name: S, value: Uniform) {
+        self.post_effects[effect]
+            .uniforms
+            .insert(name.to_string(), value);
+    }
+
+    /// Turns a post-processing effect on or off, without removing it from the chain.
+    pub fn set_post_effect_enabled(&mut self, effect: usize, enabled: bool) {
+        self.post_effects[effect].enabled = enabled;
+    }
+
+    /// Prints Unicode text on the active console, using its font's character mapping. Characters
+    /// rasterised into a TrueType font print as themselves; anything else falls back to CP437.
+    pub fn print_unicode<C: Into<RGBA>>(&mut self, x: i32, y: i32, fg: C, bg: C, text: &str) {
+        let (fg, bg) = (fg.into(), bg.into());
+        let active = &mut self.consoles[self.active_console];
+        let font = &self.fonts[active.font_index];
+        for (i, glyph) in font.string_to_glyphs(text).into_iter().enumerate() {
+            active.console.set(x + i as i32, y, fg, bg, glyph);
+        }
+    }
+
+    /// Loads a REX Paint file into `xp_files`, and returns its handle number. Files loaded from
+    /// disk are watched for changes when hot reloading is on.
+    pub fn load_xp(&mut self, path: &str) -> Result<usize, FractalError> {
+        let handle = self.xp_files.len();
+        self.xp_files.push(XpFile::load(path)?);
+        self.hot_reload.watch(path, Asset::Xp(handle));
+        Ok(handle)
+    }
+
+    /// Turns hot reloading on or off. While it is on, fonts, post-processing shaders and xp
+    /// files that were loaded from disk are reloaded between frames when their files change;
+    /// a file that fails to load is logged and the old version kept. Meant for development, so
+    /// a typical call is `with_hot_reload(cfg!(debug_assertions))`.
+    pub fn with_hot_reload(&mut self, enabled: bool) {
+        self.hot_reload.enabled = enabled;
+    }
+
+    /// Starts an animation, on the active console unless it names another one. Returns a
+    /// handle to cancel it with (`animations.cancel`), which looping animations need.
+    pub fn animate(&mut self, mut animation: Animation) -> AnimationId {
+        if animation.console.is_none() {
+            animation.console = Some(self.active_console);
+        }
+        self.animations.start(animation)
+    }
+
+    /// Adds a particle, on the active console unless it names another one
+    pub fn spawn_particle(&mut self, mut particle: Particle) {
+        if particle.console.is_none() {
+            particle.console = Some(self.active_console);
+        }
+        self.particles.spawn(particle);
+    }
+
+    /// Starts a particle emitter, on the active console unless it names another one
+    pub fn emit_particles(&mut self, mut emitter: Emitter) {
+        if emitter.console.is_none() {
+            emitter.console = Some(self.active_console);
+        }
+        self.particles.emit(emitter);
+    }
+
+    /// Shows or hides the performance overlay; see [Perf]
+    pub fn with_perf_overlay(&mut self, visible: bool) {
+        self.perf.show_overlay = visible;
+    }
+
+    /// Caps the frame rate, so the main loop doesn't use a whole CPU core when the game doesn't
+    /// need it.
+    pub fn with_fps_cap(&mut self, fps: f32) {
+        self.target_fps = Some(fps);
+    }
+
+    /// Calls `GameState::update` the given number of times per second, however fast frames are
+    /// being drawn, so game logic and animations run at the same speed on any display.
+    pub fn with_fixed_timestep(&mut self, updates_per_second: f32) {
+        self.fixed_timestep = Some(1.0 / updates_per_second);
+    }
+
+    /// Chooses what happens to the consoles when the window is resized, and applies it to the
+    /// current window size straight away.
+    pub fn with_resize_policy(&mut self, policy: ResizePolicy) {
+        self.resize_policy = policy;
+        self.apply_resize_policy();
+    }
+
+    /// Works out the viewport for the current window size, and with `GrowConsoles` resizes the
+    /// consoles to fill it. Other policies leave the consoles at whatever size they are.
+    fn apply_resize_policy(&mut self) {
+        let window = (self.width_pixels, self.height_pixels);
+        let (base_width, base_height) = self.base_size_pixels;
+        if self.resize_policy == ResizePolicy::GrowConsoles {
+            for c in self.consoles.iter_mut() {
+                let (width, height) = c.base_char_size;
+                c.console.set_char_size(
+                    u32::max(1, width * window.0 / u32::max(1, base_width)),
+                    u32::max(1, height * window.1 / u32::max(1, base_height)),
+                );
+            }
+        }
+
+        self.viewport = Viewport::for_window(self.resize_policy, window, self.base_size_pixels);
+        self.backend.set_viewport(self.viewport);
+    }
+
     /// Enable scanlines post-processing effect.
     pub fn with_post_scanlines(&mut self, with_burn: bool) {
         self.post_scanlines = true;
@@ -205,10 +537,130 @@ impl Fractal {
     }
 }
 
+/// Drawing on the active console with either [RGB] or [RGBA] colors. These shadow the
+/// [Console] methods of the same name, which only take [RGBA].
+impl Fractal {
+    /// Clear the console to a set background color, if supported.
+    pub fn cls_bg<C: Into<RGBA>>(&mut self, background: C) {
+        Console::cls_bg(self, background.into());
+    }
+
+    /// Print a string in color at the specified x/y coordinate, with specified foreground and
+    /// background.
+    pub fn print_color<C: Into<RGBA>>(&mut self, x: i32, y: i32, fg: C, bg: C, output: &str) {
+        Console::print_color(self, x, y, fg.into(), bg.into(), output);
+    }
+
+    /// Sets a single cell to a color/glyph combination.
+    pub fn set<C: Into<RGBA>>(&mut self, x: i32, y: i32, fg: C, bg: C, glyph: u16) {
+        Console::set(self, x, y, fg.into(), bg.into(), glyph);
+    }
+
+    /// Sets a single cell's background color.
+    pub fn set_bg<C: Into<RGBA>>(&mut self, x: i32, y: i32, bg: C) {
+        Console::set_bg(self, x, y, bg.into());
+    }
+
+    /// Draws a box, starting at x/y with the extents width/height using CP437 line characters
+    pub fn draw_box<C: Into<RGBA>>(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        fg: C,
+        bg: C,
+    ) {
+        Console::draw_box(self, x, y, width, height, fg.into(), bg.into());
+    }
+
+    /// Draws a box, starting at x/y with the extents width/height using CP437 line characters,
+    /// without filling in the middle
+    pub fn draw_hollow_box<C: Into<RGBA>>(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        fg: C,
+        bg: C,
+    ) {
+        Console::draw_hollow_box(self, x, y, width, height, fg.into(), bg.into());
+    }
+
+    /// Draws a box, starting at x/y with the extents width/height using CP437 double line
+    /// characters
+    pub fn draw_box_double<C: Into<RGBA>>(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        fg: C,
+        bg: C,
+    ) {
+        Console::draw_box_double(self, x, y, width, height, fg.into(), bg.into());
+    }
+
+    /// Draws a box, starting at x/y with the extents width/height using CP437 double line
+    /// characters, without filling in the middle
+    pub fn draw_hollow_box_double<C: Into<RGBA>>(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        fg: C,
+        bg: C,
+    ) {
+        Console::draw_hollow_box_double(self, x, y, width, height, fg.into(), bg.into());
+    }
+
+    /// Fills a rectangle-defined region with a given glyph
+    pub fn fill_region<C: Into<RGBA>>(&mut self, target: Rect, glyph: u16, fg: C, bg: C) {
+        Console::fill_region(self, target, glyph, fg.into(), bg.into());
+    }
+
+    /// Draws a horizontal progress bar.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_bar_horizontal<C: Into<RGBA>>(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: i32,
+        n: i32,
+        max: i32,
+        fg: C,
+        bg: C,
+    ) {
+        Console::draw_bar_horizontal(self, x, y, width, n, max, fg.into(), bg.into());
+    }
+
+    /// Draws a vertical progress bar.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_bar_vertical<C: Into<RGBA>>(
+        &mut self,
+        x: i32,
+        y: i32,
+        height: i32,
+        n: i32,
+        max: i32,
+        fg: C,
+        bg: C,
+    ) {
+        Console::draw_bar_vertical(self, x, y, height, n, max, fg.into(), bg.into());
+    }
+
+    /// Prints text in color, centered to the whole console width, at vertical location y.
+    pub fn print_color_centered<C: Into<RGBA>>(&mut self, y: i32, fg: C, bg: C, text: &str) {
+        Console::print_color_centered(self, y, fg.into(), bg.into(), text);
+    }
+}
+
 impl Console for Fractal {
     // A couple of ones we'll never use
-    fn rebuild_if_dirty(&mut self, _platform: &FractalPlatform) {}
-    fn gl_draw(&mut self, _font: &Font, _shader: &Shader, _platform: &FractalPlatform) {}
+    fn rebuild_if_dirty(&mut self, _font: &Font, _backend: &mut dyn Backend) {}
+    fn draw(&mut self, _font: &Font, _shader_index: usize, _backend: &mut dyn Backend) {}
 
     fn get_char_size(&self) -> (u32, u32) {
         self.consoles[self.active_console].console.get_char_size()
@@ -221,6 +673,13 @@ impl Console for Fractal {
         for c in self.consoles.iter_mut() {
             c.console.resize_pixels(width, height);
         }
+        self.apply_resize_policy();
+    }
+
+    fn set_char_size(&mut self, width: u32, height: u32) {
+        self.consoles[self.active_console]
+            .console
+            .set_char_size(width, height);
     }
 
     // Implement pass-through to active console
@@ -231,7 +690,7 @@ impl Console for Fractal {
     fn cls(&mut self) {
         self.consoles[self.active_console].console.cls();
     }
-    fn cls_bg(&mut self, background: RGB) {
+    fn cls_bg(&mut self, background: RGBA) {
         self.consoles[self.active_console]
             .console
             .cls_bg(background);
@@ -241,30 +700,30 @@ impl Console for Fractal {
             .console
             .print(x, y, output);
     }
-    fn print_color(&mut self, x: i32, y: i32, fg: RGB, bg: RGB, output: &str) {
+    fn print_color(&mut self, x: i32, y: i32, fg: RGBA, bg: RGBA, output: &str) {
         self.consoles[self.active_console]
             .console
             .print_color(x, y, fg, bg, output);
     }
-    fn set(&mut self, x: i32, y: i32, fg: RGB, bg: RGB, glyph: u8) {
+    fn set(&mut self, x: i32, y: i32, fg: RGBA, bg: RGBA, glyph: u16) {
         self.consoles[self.active_console]
             .console
             .set(x, y, fg, bg, glyph);
     }
-    fn set_bg(&mut self, x: i32, y: i32, bg: RGB) {
+    fn set_bg(&mut self, x: i32, y: i32, bg: RGBA) {
         self.consoles[self.active_console].console.set_bg(x, y, bg);
     }
-    fn draw_box(&mut self, x: i32, y: i32, width: i32, height: i32, fg: RGB, bg: RGB) {
+    fn draw_box(&mut self, x: i32, y: i32, width: i32, height: i32, fg: RGBA, bg: RGBA) {
         self.consoles[self.active_console]
             .console
             .draw_box(x, y, width, height, fg, bg);
     }
-    fn draw_box_double(&mut self, x: i32, y: i32, width: i32, height: i32, fg: RGB, bg: RGB) {
+    fn draw_box_double(&mut self, x: i32, y: i32, width: i32, height: i32, fg: RGBA, bg: RGBA) {
         self.consoles[self.active_console]
             .console
             .draw_box_double(x, y, width, height, fg, bg);
     }
-    fn draw_hollow_box(&mut self, x: i32, y: i32, width: i32, height: i32, fg: RGB, bg: RGB) {
+    fn draw_hollow_box(&mut self, x: i32, y: i32, width: i32, height: i32, fg: RGBA, bg: RGBA) {
         self.consoles[self.active_console]
             .console
             .draw_hollow_box(x, y, width, height, fg, bg);
@@ -275,8 +734,8 @@ impl Console for Fractal {
         y: i32,
         width: i32,
         height: i32,
-        fg: RGB,
-        bg: RGB,
+        fg: RGBA,
+        bg: RGBA,
     ) {
         self.consoles[self.active_console]
             .console
@@ -289,8 +748,8 @@ impl Console for Fractal {
         width: i32,
         n: i32,
         max: i32,
-        fg: RGB,
-        bg: RGB,
+        fg: RGBA,
+        bg: RGBA,
     ) {
         self.consoles[self.active_console]
             .console
@@ -303,19 +762,19 @@ impl Console for Fractal {
         height: i32,
         n: i32,
         max: i32,
-        fg: RGB,
-        bg: RGB,
+        fg: RGBA,
+        bg: RGBA,
     ) {
         self.consoles[self.active_console]
             .console
             .draw_bar_vertical(x, y, height, n, max, fg, bg);
     }
-    fn fill_region(&mut self, target: Rect, glyph: u8, fg: RGB, bg: RGB) {
+    fn fill_region(&mut self, target: Rect, glyph: u16, fg: RGBA, bg: RGBA) {
         self.consoles[self.active_console]
             .console
             .fill_region(target, glyph, fg, bg);
     }
-    fn get(&self, x: i32, y: i32) -> Option<(&u8, &RGB, &RGB)> {
+    fn get(&self, x: i32, y: i32) -> Option<(&u16, &RGBA, &RGBA)> {
         self.consoles[self.active_console].console.get(x, y)
     }
     fn print_centered(&mut self, y: i32, text: &str) {
@@ -323,7 +782,7 @@ impl Console for Fractal {
             .console
             .print_centered(y, text);
     }
-    fn print_color_centered(&mut self, y: i32, fg: RGB, bg: RGB, text: &str) {
+    fn print_color_centered(&mut self, y: i32, fg: RGBA, bg: RGBA, text: &str) {
         self.consoles[self.active_console]
             .console
             .print_color_centered(y, fg, bg, text);
@@ -376,8 +835,3 @@ pub fn letter_to_option(key: VirtualKeyCode) -> i32 {
         _ => -1,
     }
 }
-
-// Since num::clamp is still experimental, this is a simple integer clamper.
-fn iclamp(val: i32, min: i32, max: i32) -> i32 {
-    i32::max(min, i32::min(val, max))
-}
@@ -61,6 +61,7 @@ impl Octant {
     }
 
     #[inline]
+    #[allow(clippy::wrong_self_convention)]
     fn from_octant0(&self, p: Point) -> Point {
         match self.0 {
             0 => Point::new(p.x, p.y),
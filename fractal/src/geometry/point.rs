@@ -30,7 +30,7 @@ impl Point {
 }
 
 ///////////////////////////////////////////////////////////////////////////////////////
-/// Overloads: We support basic point math
+// Overloads: We support basic point math
 
 /// Support adding a point to a point
 impl ops::Add<Point> for Point {
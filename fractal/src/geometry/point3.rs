@@ -1,10 +1,6 @@
 use std::ops;
 
-#[cfg_attr(
-    feature = "serialization",
-    derive(serde::Serialize, serde::Deserialize)
-)]
-#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
 /// Helper struct defining a 2D point in space.
 pub struct Point3 {
     /// X Coordinate
@@ -23,7 +19,7 @@ impl Point3 {
 }
 
 ///////////////////////////////////////////////////////////////////////////////////////
-/// Overloads: We support basic point math
+// Overloads: We support basic point math
 
 /// Support adding a point to a point
 impl ops::Add<Point3> for Point3 {
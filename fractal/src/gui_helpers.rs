@@ -1,7 +1,7 @@
 //! Helper functions for drawing boxes and thick bars
 
 use crate::codepage437::to_cp437;
-use crate::color::RGB;
+use crate::color::RGBA;
 use crate::color;
 use crate::console::Console;
 
@@ -12,16 +12,16 @@ pub fn draw_box(
     sy: i32,
     width: i32,
     height: i32,
-    fg: RGB,
-    bg: RGB,
+    fg: RGBA,
+    bg: RGBA,
 ) {
     for y in sy..sy + height {
         for x in sx..sx + width {
             console.set(
                 x,
                 y,
-                color::WHITE,
-                color::BLACK,
+                color::WHITE.into(),
+                color::BLACK.into(),
                 32,
             );
         }
@@ -48,8 +48,8 @@ pub fn draw_hollow_box(
     sy: i32,
     width: i32,
     height: i32,
-    fg: RGB,
-    bg: RGB,
+    fg: RGBA,
+    bg: RGBA,
 ) {
     console.set(sx, sy, fg, bg, to_cp437('┌'));
     console.set(sx + width, sy, fg, bg, to_cp437('┐'));
@@ -72,16 +72,16 @@ pub fn draw_box_double(
     sy: i32,
     width: i32,
     height: i32,
-    fg: RGB,
-    bg: RGB,
+    fg: RGBA,
+    bg: RGBA,
 ) {
     for y in sy..sy + height {
         for x in sx..sx + width {
             console.set(
                 x,
                 y,
-                color::WHITE,
-                color::BLACK,
+                color::WHITE.into(),
+                color::BLACK.into(),
                 32,
             );
         }
@@ -108,8 +108,8 @@ pub fn draw_hollow_box_double(
     sy: i32,
     width: i32,
     height: i32,
-    fg: RGB,
-    bg: RGB,
+    fg: RGBA,
+    bg: RGBA,
 ) {
     console.set(sx, sy, fg, bg, to_cp437('╔'));
     console.set(sx + width, sy, fg, bg, to_cp437('╗'));
@@ -134,8 +134,8 @@ pub fn draw_bar_horizontal(
     width: i32,
     n: i32,
     max: i32,
-    fg: RGB,
-    bg: RGB,
+    fg: RGBA,
+    bg: RGBA,
 ) {
     let percent = n as f32 / max as f32;
     let fill_width = (percent * width as f32) as i32;
@@ -157,8 +157,8 @@ pub fn draw_bar_vertical(
     height: i32,
     n: i32,
     max: i32,
-    fg: RGB,
-    bg: RGB,
+    fg: RGBA,
+    bg: RGBA,
 ) {
     let percent = n as f32 / max as f32;
     let fill_height = height - ((percent * height as f32) as i32);
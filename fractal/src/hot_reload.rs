@@ -0,0 +1,102 @@
+//! Development aid: watches the files behind fonts, post-processing shaders and REX images that
+//! were loaded from disk, so the main loop can reload them when they change. Resources embedded
+//! with `link_resource!` are never watched.
+//!
+//! Files are polled for a new modification time, a couple of times a second, rather than
+//! watched through the operating system.
+
+use crate::embedding;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Seconds between checks of the watched files
+pub const DEFAULT_INTERVAL: f32 = 0.5;
+
+/// What a watched file was loaded into
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Asset {
+    /// A registered font, by handle
+    Font(usize),
+    /// A post-processing effect, by handle
+    PostEffect(usize),
+    /// A loaded REX Paint file, by handle
+    Xp(usize),
+}
+
+struct WatchedFile {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    asset: Asset,
+}
+
+/// The files being watched, and whether watching is switched on
+pub struct HotReload {
+    /// Files are only checked while this is set
+    pub enabled: bool,
+    /// Seconds between checks
+    pub interval: f32,
+    files: Vec<WatchedFile>,
+    since_check: f32,
+}
+
+impl Default for HotReload {
+    fn default() -> HotReload {
+        HotReload {
+            enabled: false,
+            interval: DEFAULT_INTERVAL,
+            files: Vec::new(),
+            since_check: 0.0,
+        }
+    }
+}
+
+impl HotReload {
+    /// Creates a watcher that is switched off and watching nothing
+    pub fn new() -> HotReload {
+        HotReload::default()
+    }
+
+    /// Starts watching the file an asset was loaded from. Embedded resources and paths that
+    /// aren't files on disk are ignored.
+    pub fn watch<P: AsRef<Path>>(&mut self, path: P, asset: Asset) {
+        let path = path.as_ref();
+        if path.to_str().is_some_and(embedding::is_embedded) || !path.is_file() {
+            return;
+        }
+        self.files.retain(|f| f.asset != asset);
+        self.files.push(WatchedFile {
+            path: path.to_path_buf(),
+            modified: modified(path),
+            asset,
+        });
+    }
+
+    /// The assets whose files changed since they were last checked, along with their paths.
+    /// Does nothing until the interval has passed, or while watching is switched off.
+    pub fn changed(&mut self, elapsed: f32) -> Vec<(Asset, PathBuf)> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        self.since_check += elapsed;
+        if self.since_check < self.interval {
+            return Vec::new();
+        }
+        self.since_check = 0.0;
+
+        let mut changed = Vec::new();
+        for file in self.files.iter_mut() {
+            let now = modified(&file.path);
+            if now.is_some() && now != file.modified {
+                file.modified = now;
+                changed.push((file.asset, file.path.clone()));
+            }
+        }
+        changed
+    }
+}
+
+/// When a file was last written to, if it can be read
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
@@ -0,0 +1,205 @@
+//! Keyboard and mouse input: an ordered queue of this frame's events, along with which keys and
+//! buttons are held down.
+
+pub use glutin::event::MouseButton;
+use glutin::event::VirtualKeyCode;
+use std::collections::{HashMap, HashSet};
+
+/// Which modifier keys are held down
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Modifiers {
+    /// Either shift key
+    #[serde(default)]
+    pub shift: bool,
+    /// Either control key
+    #[serde(default)]
+    pub control: bool,
+    /// Either alt key
+    #[serde(default)]
+    pub alt: bool,
+}
+
+impl Modifiers {
+    /// True if every modifier held in `required` is also held here
+    pub fn contains(&self, required: Modifiers) -> bool {
+        (self.shift || !required.shift)
+            && (self.control || !required.control)
+            && (self.alt || !required.alt)
+    }
+}
+
+/// Something the player did, in the order it happened during the frame
+#[derive(Clone, Debug, PartialEq)]
+pub enum InputEvent {
+    /// A key went down. Keys repeat while held, so this can arrive again without a KeyUp.
+    KeyDown(VirtualKeyCode),
+    /// A key was released
+    KeyUp(VirtualKeyCode),
+    /// A character was typed, after the keyboard layout and modifiers were applied
+    Character(char),
+    /// A mouse button went down
+    MouseDown(MouseButton),
+    /// A mouse button was released
+    MouseUp(MouseButton),
+    /// The mouse moved to a new position, in window pixels
+    MouseMoved {
+        /// X position in pixels
+        x: i32,
+        /// Y position in pixels
+        y: i32,
+    },
+    /// The mouse wheel turned, in lines. Positive y scrolls up, positive x scrolls right.
+    MouseWheel {
+        /// Horizontal scroll
+        x: f32,
+        /// Vertical scroll
+        y: f32,
+    },
+}
+
+/// Input state, kept up to date by the main loop. Events and the pressed/released sets only
+/// cover the current frame; held keys and buttons carry over until they are released.
+#[derive(Clone, Debug, Default)]
+pub struct Input {
+    /// This frame's events, oldest first
+    pub events: Vec<InputEvent>,
+    /// Mouse position in window pixels
+    pub mouse_pixels: (i32, i32),
+    /// Total scrolling this frame, in lines
+    pub scroll: (f32, f32),
+    /// True while a shift key is held down
+    pub shift: bool,
+    /// True while a control key is held down
+    pub control: bool,
+    /// True while an alt key is held down
+    pub alt: bool,
+    keys_held: HashSet<VirtualKeyCode>,
+    keys_pressed: HashMap<VirtualKeyCode, Modifiers>,
+    keys_released: HashSet<VirtualKeyCode>,
+    buttons_held: HashSet<MouseButton>,
+    buttons_pressed: HashSet<MouseButton>,
+    buttons_released: HashSet<MouseButton>,
+}
+
+impl Input {
+    /// Creates an empty input state, with nothing held
+    pub fn new() -> Input {
+        Input::default()
+    }
+
+    /// True while the key is held down
+    pub fn is_key_held(&self, key: VirtualKeyCode) -> bool {
+        self.keys_held.contains(&key)
+    }
+
+    /// True if the key went down this frame, including key repeats
+    pub fn key_pressed(&self, key: VirtualKeyCode) -> bool {
+        self.keys_pressed.contains_key(&key)
+    }
+
+    /// If the key went down this frame, the modifiers that were held when it did
+    pub fn pressed_modifiers(&self, key: VirtualKeyCode) -> Option<Modifiers> {
+        self.keys_pressed.get(&key).cloned()
+    }
+
+    /// The modifiers held as of the latest key event
+    pub fn modifiers(&self) -> Modifiers {
+        Modifiers {
+            shift: self.shift,
+            control: self.control,
+            alt: self.alt,
+        }
+    }
+
+    /// True if the key was released this frame
+    pub fn key_released(&self, key: VirtualKeyCode) -> bool {
+        self.keys_released.contains(&key)
+    }
+
+    /// Every key currently held down
+    pub fn keys_held(&self) -> impl Iterator<Item = &VirtualKeyCode> {
+        self.keys_held.iter()
+    }
+
+    /// True while the mouse button is held down
+    pub fn is_mouse_held(&self, button: MouseButton) -> bool {
+        self.buttons_held.contains(&button)
+    }
+
+    /// True if the mouse button went down this frame
+    pub fn mouse_pressed(&self, button: MouseButton) -> bool {
+        self.buttons_pressed.contains(&button)
+    }
+
+    /// True if the mouse button was released this frame
+    pub fn mouse_released(&self, button: MouseButton) -> bool {
+        self.buttons_released.contains(&button)
+    }
+
+    /// The text typed this frame, in order
+    pub fn text(&self) -> String {
+        self.events
+            .iter()
+            .filter_map(|e| match e {
+                InputEvent::Character(c) => Some(*c),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Forgets the last frame's events, ready for the next. Held keys and buttons are kept.
+    pub fn begin_frame(&mut self) {
+        self.events.clear();
+        self.scroll = (0.0, 0.0);
+        self.keys_pressed.clear();
+        self.keys_released.clear();
+        self.buttons_pressed.clear();
+        self.buttons_released.clear();
+    }
+
+    /// Queues an event and updates the state it affects
+    pub fn push(&mut self, event: InputEvent) {
+        match &event {
+            InputEvent::KeyDown(key) => {
+                self.keys_held.insert(*key);
+                let modifiers = self.modifiers();
+                self.keys_pressed.insert(*key, modifiers);
+            }
+            InputEvent::KeyUp(key) => {
+                self.keys_held.remove(key);
+                self.keys_released.insert(*key);
+            }
+            InputEvent::MouseDown(button) => {
+                self.buttons_held.insert(*button);
+                self.buttons_pressed.insert(*button);
+            }
+            InputEvent::MouseUp(button) => {
+                self.buttons_held.remove(button);
+                self.buttons_released.insert(*button);
+            }
+            InputEvent::MouseMoved { x, y } => self.mouse_pixels = (*x, *y),
+            InputEvent::MouseWheel { x, y } => {
+                self.scroll.0 += x;
+                self.scroll.1 += y;
+            }
+            InputEvent::Character(_) => {}
+        }
+        self.events.push(event);
+    }
+
+    /// Releases everything that is held, for when the window loses focus and the releases
+    /// would never arrive.
+    pub fn release_all(&mut self) {
+        let keys: Vec<VirtualKeyCode> = self.keys_held.iter().cloned().collect();
+        for key in keys {
+            self.push(InputEvent::KeyUp(key));
+        }
+        let buttons: Vec<MouseButton> = self.buttons_held.iter().cloned().collect();
+        for button in buttons {
+            self.push(InputEvent::MouseUp(button));
+        }
+        self.shift = false;
+        self.control = false;
+        self.alt = false;
+    }
+}
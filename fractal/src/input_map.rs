@@ -0,0 +1,171 @@
+//! Maps keys, key chords and mouse buttons to game-defined actions, so games can ask "was
+//! MoveNorth pressed?" instead of checking keys, and players can rebind them.
+//!
+//! Actions are anything that can be turned into a string: an enum implementing Display, or a
+//! plain &str. Bindings are saved under those names, as JSON or TOML.
+
+use crate::input::{Input, Modifiers, MouseButton};
+use glutin::event::VirtualKeyCode;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Something the player can press to trigger an action
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Binding {
+    /// A key, pressed while holding at least the given modifiers
+    Key {
+        /// The key
+        key: VirtualKeyCode,
+        /// Modifiers that have to be held down too
+        #[serde(default)]
+        modifiers: Modifiers,
+    },
+    /// A mouse button
+    Mouse {
+        /// The button
+        button: MouseButton,
+    },
+}
+
+impl Binding {
+    /// A key on its own
+    pub fn key(key: VirtualKeyCode) -> Binding {
+        Binding::Key {
+            key,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    /// A key pressed while holding modifiers
+    pub fn chord(key: VirtualKeyCode, modifiers: Modifiers) -> Binding {
+        Binding::Key { key, modifiers }
+    }
+
+    /// A mouse button
+    pub fn mouse(button: MouseButton) -> Binding {
+        Binding::Mouse { button }
+    }
+
+    /// True if the binding was triggered this frame
+    pub fn pressed(&self, input: &Input) -> bool {
+        match *self {
+            Binding::Key { key, modifiers } => input
+                .pressed_modifiers(key)
+                .is_some_and(|held| held.contains(modifiers)),
+            Binding::Mouse { button } => input.mouse_pressed(button),
+        }
+    }
+
+    /// True while the binding is held down
+    pub fn held(&self, input: &Input) -> bool {
+        match *self {
+            Binding::Key { key, modifiers } => {
+                input.is_key_held(key) && input.modifiers().contains(modifiers)
+            }
+            Binding::Mouse { button } => input.is_mouse_held(button),
+        }
+    }
+}
+
+/// The bindings for every action. An action can have any number of bindings, and a binding can
+/// trigger more than one action.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputMap {
+    /// Bindings by action name
+    pub actions: BTreeMap<String, Vec<Binding>>,
+}
+
+impl InputMap {
+    /// Creates a map with nothing bound
+    pub fn new() -> InputMap {
+        InputMap::default()
+    }
+
+    /// Adds a binding to an action, keeping the ones it already has
+    pub fn bind<A: ToString>(&mut self, action: A, binding: Binding) -> &mut Self {
+        let bindings = self.actions.entry(action.to_string()).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+        self
+    }
+
+    /// Replaces all of an action's bindings with this one
+    pub fn rebind<A: ToString>(&mut self, action: A, binding: Binding) -> &mut Self {
+        self.actions.insert(action.to_string(), vec![binding]);
+        self
+    }
+
+    /// Removes one binding from an action
+    pub fn unbind<A: ToString>(&mut self, action: A, binding: Binding) -> &mut Self {
+        if let Some(bindings) = self.actions.get_mut(&action.to_string()) {
+            bindings.retain(|b| *b != binding);
+        }
+        self
+    }
+
+    /// The bindings of an action; empty if it has none
+    pub fn bindings<A: ToString>(&self, action: A) -> &[Binding] {
+        self.actions
+            .get(&action.to_string())
+            .map(|b| b.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// True if any of the action's bindings was triggered this frame
+    pub fn pressed<A: ToString>(&self, input: &Input, action: A) -> bool {
+        self.bindings(action).iter().any(|b| b.pressed(input))
+    }
+
+    /// True while any of the action's bindings is held down
+    pub fn held<A: ToString>(&self, input: &Input, action: A) -> bool {
+        self.bindings(action).iter().any(|b| b.held(input))
+    }
+
+    /// Reads bindings from JSON
+    pub fn from_json(json: &str) -> serde_json::Result<InputMap> {
+        serde_json::from_str(json)
+    }
+
+    /// Writes the bindings as JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Reads bindings from TOML
+    pub fn from_toml(text: &str) -> Result<InputMap, toml::de::Error> {
+        toml::from_str(text)
+    }
+
+    /// Writes the bindings as TOML
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        toml::to_string_pretty(self)
+    }
+
+    /// Loads bindings from a file; TOML if its extension is .toml, JSON otherwise
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<InputMap> {
+        let text = fs::read_to_string(&path)?;
+        if is_toml(path.as_ref()) {
+            InputMap::from_toml(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        } else {
+            Ok(InputMap::from_json(&text)?)
+        }
+    }
+
+    /// Saves the bindings to a file; TOML if its extension is .toml, JSON otherwise
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let text = if is_toml(path.as_ref()) {
+            self.to_toml()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+        } else {
+            self.to_json()
+        };
+        fs::write(path, text)
+    }
+}
+
+fn is_toml(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e == "toml")
+}
@@ -17,22 +17,37 @@ extern crate serde_derive;
 
 use crate::fractal::Fractal;
 
+pub mod animation;
 pub mod backend;
+pub mod builder;
+pub mod camera;
 pub mod codepage437;
 pub mod color;
 pub mod console;
+pub mod draw_batch;
 pub mod embedding;
+pub mod error;
+pub mod fancy_console;
 pub mod fastnoise;
 pub mod fieldofview;
 pub mod fractal;
 pub mod geometry;
 pub mod gui_helpers;
+pub mod hot_reload;
+pub mod input;
+pub mod input_map;
 pub mod parsing;
+pub mod particles;
 pub mod pathfinding;
+pub mod perf;
 pub mod random;
+pub mod replay;
 pub mod rex;
 pub mod simple_console;
+pub mod sparse_console;
+pub mod sprite_console;
 pub mod textblock;
+pub mod viewport;
 
 /// Emedds a resource into a byte array
 #[macro_export]
@@ -59,6 +74,11 @@ pub use glutin::event::VirtualKeyCode;
 pub trait GameState: 'static {
     /// Called every frame
     fn tick(&mut self, ctx: &mut Fractal);
+
+    /// Called at a fixed rate, separately from drawing, once the context has a fixed timestep
+    /// (see `Fractal::with_fixed_timestep`). `dt` is the timestep in seconds. Does nothing by
+    /// default.
+    fn update(&mut self, _ctx: &mut Fractal, _dt: f32) {}
 }
 
 /// Helper for easily printing things to the console
@@ -40,7 +40,7 @@ impl Default for DiceType {
 #[derive(Debug, Clone)]
 pub struct DiceParseError;
 
-impl std::fmt::Display for DiceParseError {
+impl fmt::Display for DiceParseError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Invalid dice string")
     }
@@ -0,0 +1,412 @@
+//! Short-lived glyph effects: sparks, smoke, hit flashes. A particle is a glyph that moves,
+//! changes color along a ramp and can fade into the background, then disappears when its
+//! lifetime is up. Emitters spawn particles, all at once or over time.
+//!
+//! Like animations, particles are advanced by the frame time and drawn over whatever the game
+//! put on their console, after `GameState::tick` and before the consoles are rebuilt. Once the
+//! consoles have been drawn, the tiles the particles covered are put back, so a moving particle
+//! doesn't leave a trail on a console the game doesn't clear every frame. Positions are in tiles
+//! and velocities in tiles per second.
+//!
+//! Particles pick their glyphs and directions with their own random number generator. The main
+//! loop seeds it along with replays, so a replayed run shows the same effects.
+
+use crate::animation::{in_bounds, tile};
+use crate::color::{self, RGBA};
+use crate::fractal::DisplayConsole;
+use crate::random::RandomNumberGenerator;
+use std::f32::consts::PI;
+
+/// One glyph effect
+#[derive(Clone, Debug, PartialEq)]
+pub struct Particle {
+    /// Console to draw on; None means the console that is active when it is spawned
+    pub console: Option<usize>,
+    /// Position in tiles; the particle is drawn in the tile this falls in
+    pub position: (f32, f32),
+    /// Movement in tiles per second
+    pub velocity: (f32, f32),
+    /// Glyph to draw
+    pub glyph: u16,
+    /// Foreground colors, spread evenly over the particle's life
+    pub colors: Vec<RGBA>,
+    /// Background color; None keeps whatever is under the particle
+    pub bg: Option<RGBA>,
+    /// Blend the foreground into the background as the particle ages
+    pub fade: bool,
+    /// How long the particle lives, in milliseconds
+    pub lifetime_ms: f32,
+    /// How long it has lived so far, in milliseconds
+    pub age_ms: f32,
+}
+
+impl Particle {
+    /// A still particle in the middle of tile x, y
+    pub fn new<C: Into<RGBA>>(x: i32, y: i32, glyph: u16, color: C, lifetime_ms: f32) -> Self {
+        Particle {
+            console: None,
+            position: (x as f32 + 0.5, y as f32 + 0.5),
+            velocity: (0.0, 0.0),
+            glyph,
+            colors: vec![color.into()],
+            bg: None,
+            fade: false,
+            lifetime_ms,
+            age_ms: 0.0,
+        }
+    }
+
+    /// Sets the velocity, in tiles per second
+    pub fn with_velocity(mut self, x: f32, y: f32) -> Self {
+        self.velocity = (x, y);
+        self
+    }
+
+    /// Replaces the color with a ramp of colors, spread over the particle's life
+    pub fn with_colors<C: Into<RGBA> + Copy>(mut self, colors: &[C]) -> Self {
+        self.colors = colors.iter().map(|c| (*c).into()).collect();
+        self
+    }
+
+    /// Sets the background color
+    pub fn with_bg<C: Into<RGBA>>(mut self, bg: C) -> Self {
+        self.bg = Some(bg.into());
+        self
+    }
+
+    /// Fades the particle into the background as it ages
+    pub fn with_fade(mut self, fade: bool) -> Self {
+        self.fade = fade;
+        self
+    }
+
+    /// Draws on a particular console, rather than the active one
+    pub fn on_console(mut self, console: usize) -> Self {
+        self.console = Some(console);
+        self
+    }
+
+    /// True once the particle has outlived its lifetime
+    pub fn is_dead(&self) -> bool {
+        self.age_ms >= self.lifetime_ms
+    }
+
+    /// How far through its life the particle is, 0..1
+    pub fn age(&self) -> f32 {
+        if self.lifetime_ms <= 0.0 {
+            1.0
+        } else {
+            f32::min(self.age_ms / self.lifetime_ms, 1.0)
+        }
+    }
+
+    /// The foreground color for the particle's age, before fading
+    pub fn color(&self) -> RGBA {
+        let t = self.age();
+        match self.colors.len() {
+            0 => RGBA::from(color::WHITE),
+            1 => self.colors[0],
+            n => {
+                let position = t * (n - 1) as f32;
+                let i = usize::min(position as usize, n - 2);
+                self.colors[i].lerp(self.colors[i + 1], position - i as f32)
+            }
+        }
+    }
+
+    fn advance(&mut self, elapsed_ms: f32) {
+        self.age_ms += elapsed_ms;
+        self.position.0 += self.velocity.0 * elapsed_ms / 1000.0;
+        self.position.1 += self.velocity.1 * elapsed_ms / 1000.0;
+    }
+
+    /// Draws the particle, returning the tile it covered so it can be put back
+    fn draw(&self, console: &mut DisplayConsole) -> Option<CoveredTile> {
+        let console = console.console.as_mut();
+        let x = self.position.0.floor() as i32;
+        let y = self.position.1.floor() as i32;
+        if !in_bounds(console, x, y) {
+            return None;
+        }
+        let under = tile(console, x, y);
+        // Sparse consoles have nothing in unset tiles, so there is no background to keep
+        let bg = self
+            .bg
+            .or_else(|| under.map(|(_, _, bg)| bg))
+            .unwrap_or(color::TRANSPARENT);
+        let mut fg = self.color();
+        if self.fade {
+            fg = fg.lerp(bg, self.age());
+        }
+        console.set(x, y, fg, bg, self.glyph);
+        Some(CoveredTile {
+            console: self.console?,
+            x,
+            y,
+            tile: under,
+        })
+    }
+}
+
+/// Spawns particles around a tile: a burst straight away, then a steady rate for a while. Each
+/// particle gets a random glyph from the list, and a random speed, direction and lifetime
+/// from the ranges.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Emitter {
+    /// Console to draw on; None means the console that is active when it is started
+    pub console: Option<usize>,
+    /// Tile the particles start from
+    pub x: i32,
+    /// Tile the particles start from
+    pub y: i32,
+    /// Glyphs to pick from
+    pub glyphs: Vec<u16>,
+    /// Color ramp given to every particle
+    pub colors: Vec<RGBA>,
+    /// Background given to every particle; None keeps whatever is under it
+    pub bg: Option<RGBA>,
+    /// Fade particles into the background as they age
+    pub fade: bool,
+    /// Shortest and longest particle lifetimes, in milliseconds
+    pub lifetime_ms: (f32, f32),
+    /// Slowest and fastest particle speeds, in tiles per second
+    pub speed: (f32, f32),
+    /// Direction particles head in, in radians clockwise from the +x axis
+    pub direction: f32,
+    /// How far either side of the direction particles can head, in radians. PI sends them in
+    /// every direction.
+    pub spread: f32,
+    /// Particles spawned as soon as the emitter starts
+    pub burst: u32,
+    /// Particles spawned per second after the burst
+    pub rate: f32,
+    /// How long the emitter keeps spawning at its rate, in milliseconds
+    pub duration_ms: f32,
+    elapsed_ms: f32,
+    owed: f32,
+}
+
+impl Emitter {
+    /// An emitter at tile x, y that bursts one white '*' in place, lasting half a second
+    pub fn new(x: i32, y: i32) -> Self {
+        Emitter {
+            console: None,
+            x,
+            y,
+            glyphs: vec![42],
+            colors: vec![RGBA::from(color::WHITE)],
+            bg: None,
+            fade: false,
+            lifetime_ms: (500.0, 500.0),
+            speed: (0.0, 0.0),
+            direction: 0.0,
+            spread: PI,
+            burst: 1,
+            rate: 0.0,
+            duration_ms: 0.0,
+            elapsed_ms: 0.0,
+            owed: 0.0,
+        }
+    }
+
+    /// Sets the glyphs to pick from
+    pub fn with_glyphs(mut self, glyphs: &[u16]) -> Self {
+        self.glyphs = glyphs.to_vec();
+        self
+    }
+
+    /// Sets the color ramp
+    pub fn with_colors<C: Into<RGBA> + Copy>(mut self, colors: &[C]) -> Self {
+        self.colors = colors.iter().map(|c| (*c).into()).collect();
+        self
+    }
+
+    /// Sets the background color
+    pub fn with_bg<C: Into<RGBA>>(mut self, bg: C) -> Self {
+        self.bg = Some(bg.into());
+        self
+    }
+
+    /// Fades particles into the background as they age
+    pub fn with_fade(mut self, fade: bool) -> Self {
+        self.fade = fade;
+        self
+    }
+
+    /// Sets the range of lifetimes, in milliseconds
+    pub fn with_lifetime(mut self, min_ms: f32, max_ms: f32) -> Self {
+        self.lifetime_ms = (min_ms, max_ms);
+        self
+    }
+
+    /// Sets the range of speeds, in tiles per second
+    pub fn with_speed(mut self, min: f32, max: f32) -> Self {
+        self.speed = (min, max);
+        self
+    }
+
+    /// Sends particles in a direction (radians clockwise from +x), give or take spread
+    pub fn with_direction(mut self, direction: f32, spread: f32) -> Self {
+        self.direction = direction;
+        self.spread = spread;
+        self
+    }
+
+    /// Sets how many particles are spawned straight away
+    pub fn with_burst(mut self, count: u32) -> Self {
+        self.burst = count;
+        self
+    }
+
+    /// Keeps spawning particles per_second for duration_ms after the burst
+    pub fn with_rate(mut self, per_second: f32, duration_ms: f32) -> Self {
+        self.rate = per_second;
+        self.duration_ms = duration_ms;
+        self
+    }
+
+    /// Draws on a particular console, rather than the active one
+    pub fn on_console(mut self, console: usize) -> Self {
+        self.console = Some(console);
+        self
+    }
+
+    /// True once the emitter has nothing left to spawn
+    pub fn is_finished(&self) -> bool {
+        self.elapsed_ms >= self.duration_ms
+    }
+
+    fn spawn(&self, rng: &mut RandomNumberGenerator) -> Particle {
+        let glyph = rng.random_slice_entry(&self.glyphs).cloned().unwrap_or(42);
+        let angle = self.direction + between(rng, -self.spread, self.spread);
+        let speed = between(rng, self.speed.0, self.speed.1);
+        Particle {
+            console: self.console,
+            position: (self.x as f32 + 0.5, self.y as f32 + 0.5),
+            velocity: (angle.cos() * speed, angle.sin() * speed),
+            glyph,
+            colors: self.colors.clone(),
+            bg: self.bg,
+            fade: self.fade,
+            lifetime_ms: between(rng, self.lifetime_ms.0, self.lifetime_ms.1),
+            age_ms: 0.0,
+        }
+    }
+}
+
+/// A random number between min and max
+fn between(rng: &mut RandomNumberGenerator, min: f32, max: f32) -> f32 {
+    min + (max - min) * rng.rand::<f32>()
+}
+
+/// A tile a particle was drawn over, and what was there before
+struct CoveredTile {
+    console: usize,
+    x: i32,
+    y: i32,
+    tile: Option<(u16, RGBA, RGBA)>,
+}
+
+/// The live particles and running emitters
+pub struct ParticleSystem {
+    /// Particles being drawn, oldest first
+    pub particles: Vec<Particle>,
+    /// Emitters still spawning at their rate
+    pub emitters: Vec<Emitter>,
+    rng: RandomNumberGenerator,
+    covered: Vec<CoveredTile>,
+}
+
+impl Default for ParticleSystem {
+    fn default() -> ParticleSystem {
+        ParticleSystem::new()
+    }
+}
+
+impl ParticleSystem {
+    /// Creates an empty particle system
+    pub fn new() -> ParticleSystem {
+        ParticleSystem {
+            particles: Vec::new(),
+            emitters: Vec::new(),
+            rng: RandomNumberGenerator::new(),
+            covered: Vec::new(),
+        }
+    }
+
+    /// Reseeds the generator particles are randomised with, so the same emitters started on the
+    /// same frames give the same effects
+    pub fn seed(&mut self, seed: u64) {
+        self.rng = RandomNumberGenerator::seeded(seed);
+    }
+
+    /// Adds a particle. Its console has to be set, or it is never drawn; `Fractal::spawn_particle`
+    /// fills in the active one.
+    pub fn spawn(&mut self, particle: Particle) {
+        self.particles.push(particle);
+    }
+
+    /// Starts an emitter, spawning its burst straight away. Like `spawn`, its console has to be
+    /// set.
+    pub fn emit(&mut self, emitter: Emitter) {
+        for _ in 0..emitter.burst {
+            let particle = emitter.spawn(&mut self.rng);
+            self.particles.push(particle);
+        }
+        if !emitter.is_finished() {
+            self.emitters.push(emitter);
+        }
+    }
+
+    /// Removes every particle and emitter
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.emitters.clear();
+    }
+
+    /// Moves particles along, ages them and lets emitters spawn more. Particles that had
+    /// already been drawn at the end of their life are removed first.
+    pub fn advance(&mut self, elapsed_ms: f32) {
+        self.particles.retain(|p| !p.is_dead());
+        for particle in self.particles.iter_mut() {
+            particle.advance(elapsed_ms);
+        }
+
+        for emitter in self.emitters.iter_mut() {
+            let active_ms = f32::min(elapsed_ms, emitter.duration_ms - emitter.elapsed_ms);
+            emitter.elapsed_ms += elapsed_ms;
+            emitter.owed += emitter.rate * active_ms / 1000.0;
+            while emitter.owed >= 1.0 {
+                emitter.owed -= 1.0;
+                self.particles.push(emitter.spawn(&mut self.rng));
+            }
+        }
+        self.emitters.retain(|e| !e.is_finished());
+    }
+
+    /// Draws every particle onto its console, oldest first, so newer particles end up on top.
+    /// Call `restore` once the consoles have been drawn.
+    pub fn render(&mut self, consoles: &mut [DisplayConsole]) {
+        for particle in &self.particles {
+            if let Some(console) = particle.console.and_then(|c| consoles.get_mut(c)) {
+                if let Some(covered) = particle.draw(console) {
+                    self.covered.push(covered);
+                }
+            }
+        }
+    }
+
+    /// Puts back the tiles the last `render` drew over, newest first so overlapping particles
+    /// unwind in order. Tiles that were unset on a sparse console become transparent.
+    pub fn restore(&mut self, consoles: &mut [DisplayConsole]) {
+        while let Some(covered) = self.covered.pop() {
+            if let Some(console) = consoles.get_mut(covered.console) {
+                let (glyph, fg, bg) =
+                    covered
+                        .tile
+                        .unwrap_or((0, color::TRANSPARENT, color::TRANSPARENT));
+                console.console.set(covered.x, covered.y, fg, bg, glyph);
+            }
+        }
+    }
+}
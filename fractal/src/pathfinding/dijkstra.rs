@@ -6,8 +6,7 @@
 use crate::pathfinding::BaseMap;
 use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
 use rayon::slice::ParallelSliceMut;
-use std::f32::MAX;
-use std::mem;
+use std::mem::size_of;
 
 /// Representation of a Dijkstra flow map.
 /// map is a vector of floats, having a size equal to size_x * size_y (one per tile).
@@ -39,7 +38,7 @@ impl DijkstraMap {
         map: &dyn BaseMap,
         max_depth: f32,
     ) -> DijkstraMap {
-        let result: Vec<f32> = vec![MAX; (size_x * size_y) as usize];
+        let result: Vec<f32> = vec![f32::MAX; (size_x * size_y) as usize];
         let mut d = DijkstraMap {
             map: result,
             size_x,
@@ -52,7 +51,7 @@ impl DijkstraMap {
 
     /// Creates an empty Dijkstra map node.
     pub fn new_empty(size_x: i32, size_y: i32, max_depth: f32) -> DijkstraMap {
-        let result: Vec<f32> = vec![MAX; (size_x * size_y) as usize];
+        let result: Vec<f32> = vec![f32::MAX; (size_x * size_y) as usize];
         DijkstraMap {
             map: result,
             size_x,
@@ -67,7 +66,7 @@ impl DijkstraMap {
         max_depth: f32,
         idx: i32,
         open_list: &mut Vec<(i32, f32)>,
-        closed_list: &mut Vec<bool>,
+        closed_list: &mut [bool],
         new_depth: f32,
     ) {
         if new_depth > max_depth {
@@ -83,13 +82,12 @@ impl DijkstraMap {
 
     /// Clears the Dijkstra map. Uses a parallel for each for performance.
     pub fn clear(dm: &mut DijkstraMap) {
-        dm.map.par_iter_mut().for_each(|x| *x = MAX);
+        dm.map.par_iter_mut().for_each(|x| *x = f32::MAX);
     }
 
     fn build_helper(dm: &mut DijkstraMap, starts: &[i32], map: &dyn BaseMap) {
         if starts.len() > rayon::current_num_threads() {
             DijkstraMap::build_parallel(dm, starts, map);
-            return;
         }
     }
 
@@ -114,7 +112,7 @@ impl DijkstraMap {
                 std::ptr::write_bytes(
                     closed_list.as_mut_ptr(),
                     0,
-                    closed_list.len() * mem::size_of::<bool>(),
+                    closed_list.len() * size_of::<bool>(),
                 );
             }
             open_list.push((*start, 0.0));
@@ -152,7 +150,7 @@ impl DijkstraMap {
         let mut layers: Vec<ParallelDm> = Vec::with_capacity(starts.len());
         for start_chunk in starts.chunks(rayon::current_num_threads()) {
             let mut layer = ParallelDm {
-                map: vec![MAX; mapsize],
+                map: vec![f32::MAX; mapsize],
                 max_depth: dm.max_depth,
                 starts: Vec::new(),
             };
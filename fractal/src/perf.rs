@@ -0,0 +1,196 @@
+//! Frame timings, and an overlay that shows them on top of the game: frames per second, a
+//! histogram of recent frame times, and how long each phase of the last frame took.
+//!
+//! The main loop times `tick`, `rebuild` (consoles rebuilding their vertices), `draw` (consoles
+//! drawing themselves) and `post` (post-processing and presenting the frame). Times are CPU
+//! time spent issuing the work; the GPU may still be busy after a phase returns. Games can add
+//! their own phases with `record` or `measure`.
+
+use crate::backend::font::Font;
+use crate::backend::{Backend, SHADER_WITH_BG};
+use crate::color::{self, RGBA};
+use crate::console::Console;
+use crate::geometry::Rect;
+use crate::sparse_console::SparseConsole;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Phase name for `GameState::tick`
+pub const TICK: &str = "tick";
+/// Phase name for rebuilding dirty consoles
+pub const REBUILD: &str = "rebuild";
+/// Phase name for drawing the consoles
+pub const DRAW: &str = "draw";
+/// Phase name for post-processing and presenting the frame
+pub const POST: &str = "post";
+
+/// How many frame times are kept, one per histogram column
+pub const HISTORY_LENGTH: usize = 30;
+
+const PANEL_WIDTH: u32 = HISTORY_LENGTH as u32 + 2;
+const GRAPH_ROWS: u32 = 4;
+const FULL_BLOCK: u16 = 219;
+const LOWER_HALF_BLOCK: u16 = 220;
+/// Frames at or under this many milliseconds (60 fps) are drawn green
+const GOOD_FRAME_MS: f32 = 1000.0 / 60.0;
+/// Frames at or under this many milliseconds (30 fps) are drawn yellow, slower ones red
+const SLOW_FRAME_MS: f32 = 1000.0 / 30.0;
+
+/// Timings of recent frames, and the overlay console that displays them
+#[derive(Default)]
+pub struct Perf {
+    /// Draw the overlay on top of the consoles
+    pub show_overlay: bool,
+    frame_times: VecDeque<f32>,
+    current: Vec<(String, f32)>,
+    last: Vec<(String, f32)>,
+    frame_start: Option<Instant>,
+    overlay: Option<Box<dyn Console>>,
+}
+
+impl Perf {
+    /// Creates an empty set of timings, with the overlay hidden
+    pub fn new() -> Perf {
+        Perf::default()
+    }
+
+    /// Shows the overlay if it is hidden, hides it otherwise
+    pub fn toggle_overlay(&mut self) {
+        self.show_overlay = !self.show_overlay;
+    }
+
+    /// Adds time spent on a named phase this frame. Recording the same name again in a frame
+    /// adds to it.
+    pub fn record<S: ToString>(&mut self, name: S, duration: Duration) {
+        let name = name.to_string();
+        let ms = duration.as_secs_f32() * 1000.0;
+        match self.current.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, total)) => *total += ms,
+            None => self.current.push((name, ms)),
+        }
+    }
+
+    /// Runs a closure, recording how long it took under the given name
+    pub fn measure<S: ToString, R, F: FnOnce() -> R>(&mut self, name: S, f: F) -> R {
+        let start = Instant::now();
+        let result = f();
+        self.record(name, start.elapsed());
+        result
+    }
+
+    /// The phases of the last complete frame, in milliseconds, in the order they were first
+    /// recorded
+    pub fn timings(&self) -> &[(String, f32)] {
+        &self.last
+    }
+
+    /// The most recent frame times in milliseconds, oldest first
+    pub fn frame_times(&self) -> impl Iterator<Item = &f32> {
+        self.frame_times.iter()
+    }
+
+    /// Average frames per second over the kept frame times
+    pub fn average_fps(&self) -> f32 {
+        if self.frame_times.is_empty() {
+            return 0.0;
+        }
+        let total: f32 = self.frame_times.iter().sum();
+        1000.0 * self.frame_times.len() as f32 / f32::max(total, 0.001)
+    }
+
+    /// Starts timing a new frame: the time since the last call joins the history, and the
+    /// phases recorded since then become the last frame's timings.
+    pub(crate) fn begin_frame(&mut self) {
+        let now = Instant::now();
+        if let Some(start) = self.frame_start {
+            self.frame_times
+                .push_back(now.duration_since(start).as_secs_f32() * 1000.0);
+            if self.frame_times.len() > HISTORY_LENGTH {
+                self.frame_times.pop_front();
+            }
+        }
+        self.frame_start = Some(now);
+        self.last = std::mem::take(&mut self.current);
+    }
+
+    /// Draws the overlay in the top-right corner of a console of width x height characters,
+    /// creating or resizing the overlay's own console as needed.
+    pub(crate) fn draw_overlay(
+        &mut self,
+        width: u32,
+        height: u32,
+        font: &Font,
+        backend: &mut dyn Backend,
+    ) {
+        let fps = self.average_fps();
+        if self.overlay.is_none() {
+            self.overlay = Some(SparseConsole::init(width, height, backend));
+        }
+        let console = self.overlay.as_mut().unwrap();
+        if console.get_char_size() != (width, height) {
+            console.set_char_size(width, height);
+        }
+
+        console.cls();
+        let fg = RGBA::from(color::WHITE);
+        let bg = RGBA::from(color::BLACK);
+        let x = width.saturating_sub(PANEL_WIDTH) as i32;
+        let rows = u32::min(height, GRAPH_ROWS + 1 + self.last.len() as u32);
+        if rows == 0 {
+            return;
+        }
+        console.fill_region(
+            Rect::new(x, 0, PANEL_WIDTH as i32 - 1, rows as i32 - 1),
+            32,
+            fg,
+            bg,
+        );
+
+        let newest = self.frame_times.back().cloned().unwrap_or(0.0);
+        let mut lines = vec![format!("{:5.1} fps {:7.2} ms", fps, newest)];
+        for (name, ms) in &self.last {
+            lines.push(format!("{:<12.12}{:8.2} ms", name, ms));
+        }
+        for (row, line) in lines.iter().enumerate() {
+            // The histogram goes between the first line and the phases
+            let y = if row == 0 { 0 } else { row as u32 + GRAPH_ROWS };
+            if y < rows {
+                console.print_color(x + 1, y as i32, fg, bg, line);
+            }
+        }
+
+        // Taller than a 30 fps frame only if a frame actually took that long
+        let scale = self
+            .frame_times
+            .iter()
+            .cloned()
+            .fold(SLOW_FRAME_MS, f32::max);
+        let skipped = HISTORY_LENGTH - self.frame_times.len();
+        for (i, ms) in self.frame_times.iter().enumerate() {
+            let column = x + 1 + (skipped + i) as i32;
+            let bar_color = RGBA::from(if *ms <= GOOD_FRAME_MS {
+                color::GREEN
+            } else if *ms <= SLOW_FRAME_MS {
+                color::YELLOW
+            } else {
+                color::RED
+            });
+            // Bar height in half-rows, filled from the bottom of the graph up
+            let half_rows = (ms / scale * (GRAPH_ROWS * 2) as f32).round() as u32;
+            for level in 0..GRAPH_ROWS {
+                let y = GRAPH_ROWS - level;
+                let glyph = match half_rows.saturating_sub(level * 2) {
+                    0 => continue,
+                    1 => LOWER_HALF_BLOCK,
+                    _ => FULL_BLOCK,
+                };
+                if y < rows {
+                    console.set(column, y as i32, bar_color, bg, glyph);
+                }
+            }
+        }
+
+        console.rebuild_if_dirty(font, backend);
+        console.draw(font, SHADER_WITH_BG, backend);
+    }
+}
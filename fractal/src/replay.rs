@@ -0,0 +1,130 @@
+//! Recording the player's input to a file, and playing it back in place of the real input, so a
+//! run can be reproduced exactly.
+//!
+//! A replay file is JSON lines: a header holding the seed the game was started with, then one
+//! line per frame with the time that frame took and the input events it received. Lines are
+//! flushed as they are written, so the file survives a crash. Window events (resizes, closing)
+//! aren't recorded; during playback they still come from the real window, and input from the
+//! real window is ignored. Replays assume the window is the same size as when they were
+//! recorded.
+
+use crate::backend::BackendEvent;
+use crate::log;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// First line of a replay file
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReplayHeader {
+    /// Seed the game's random number generator was created with
+    pub seed: u64,
+}
+
+/// The input received during one frame
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    /// Seconds since the previous frame started
+    pub elapsed: f32,
+    /// The events, in the order they arrived
+    pub events: Vec<BackendEvent>,
+}
+
+/// Whether input is being recorded or played back
+#[derive(Default)]
+pub enum Replay {
+    /// Input comes from the backend, and isn't recorded
+    #[default]
+    Off,
+    /// Input comes from the backend, and each frame is written to a file
+    Recording(BufWriter<File>),
+    /// Input comes from these frames, until they run out
+    Playing(VecDeque<RecordedFrame>),
+}
+
+impl Replay {
+    /// Creates a replay file, writes its header and starts recording into it
+    pub fn record<P: AsRef<Path>>(path: P, seed: u64) -> io::Result<Replay> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        write_line(&mut writer, &ReplayHeader { seed })?;
+        Ok(Replay::Recording(writer))
+    }
+
+    /// Loads a replay file for playback, returning it along with the seed it was recorded with
+    pub fn play<P: AsRef<Path>>(path: P) -> io::Result<(Replay, u64)> {
+        let mut lines = BufReader::new(File::open(path)?).lines();
+        let header: ReplayHeader = match lines.next() {
+            Some(line) => serde_json::from_str(&line?)?,
+            None => return Err(io::Error::new(io::ErrorKind::InvalidData, "empty replay")),
+        };
+
+        let mut frames = VecDeque::new();
+        for line in lines {
+            frames.push_back(serde_json::from_str(&line?)?);
+        }
+        Ok((Replay::Playing(frames), header.seed))
+    }
+
+    /// True while a replay is being played back
+    pub fn is_playing(&self) -> bool {
+        matches!(self, Replay::Playing(_))
+    }
+
+    /// Decides what the main loop sees this frame: the live events and frame time when
+    /// recording (which are also written out) or not replaying, the next recorded frame plus
+    /// the live window events when playing. Playback turns itself off when it runs out.
+    pub fn next_frame(&mut self, live: Vec<BackendEvent>, elapsed: f32) -> RecordedFrame {
+        let mut frame = RecordedFrame {
+            elapsed,
+            events: live,
+        };
+
+        match self {
+            Replay::Off => {}
+            Replay::Recording(writer) => {
+                let recorded = RecordedFrame {
+                    elapsed,
+                    events: frame
+                        .events
+                        .iter()
+                        .filter(|e| is_input(e))
+                        .cloned()
+                        .collect(),
+                };
+                if let Err(e) = write_line(writer, &recorded) {
+                    log(format!("Stopped recording input: {}", e));
+                    *self = Replay::Off;
+                }
+            }
+            Replay::Playing(frames) => match frames.pop_front() {
+                Some(mut recorded) => {
+                    frame.events.retain(|e| !is_input(e));
+                    frame.events.append(&mut recorded.events);
+                    frame.elapsed = recorded.elapsed;
+                }
+                None => {
+                    log("Replay finished");
+                    *self = Replay::Off;
+                }
+            },
+        }
+
+        frame
+    }
+}
+
+/// True for events that come from the player, rather than the window
+fn is_input(event: &BackendEvent) -> bool {
+    !matches!(
+        event,
+        BackendEvent::Resized { .. } | BackendEvent::CloseRequested
+    )
+}
+
+/// Writes a value as one line of JSON, and flushes it to disk
+fn write_line<T: serde::Serialize>(writer: &mut BufWriter<File>, value: &T) -> io::Result<()> {
+    serde_json::to_writer(&mut *writer, value)?;
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
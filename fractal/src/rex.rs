@@ -5,10 +5,13 @@
 #![deny(non_snake_case)]
 #![deny(unused_mut)]
 
+use std::fs::File;
 use std::io;
 use std::io::prelude::*;
+use std::path::Path;
 
 use crate::embedding;
+use crate::error::FractalError;
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use flate2::read::GzDecoder;
 use flate2::write::GzEncoder;
@@ -145,36 +148,46 @@ impl XpFile {
         }
     }
 
-    /// Helper to read from an FRACTAL resource
-    pub fn from_resource(path: &str) -> io::Result<XpFile> {
+    /// Helper to read from an FRACTAL resource. Fails if nothing is embedded under that path,
+    /// or it isn't a valid xp file.
+    pub fn from_resource(path: &str) -> Result<XpFile, FractalError> {
         let res = embedding::EMBED
             .lock()
             .unwrap()
             .get_resource(path.to_string());
         match res {
-            None => panic!("Unable to open resource"),
+            None => Err(FractalError::MissingResource(path.to_string())),
             Some(r) => {
                 let buffer: Vec<u8> = Vec::from(r);
                 let mut bufslice = &*buffer;
-                XpFile::read(&mut bufslice)
+                Ok(XpFile::read(&mut bufslice)?)
             }
         }
     }
 
+    /// Loads an xp file, from the embedded resources if present or from disk otherwise
+    pub fn load(path: &str) -> Result<XpFile, FractalError> {
+        if embedding::is_embedded(path) {
+            return XpFile::from_resource(path);
+        }
+        if !Path::new(path).exists() {
+            return Err(FractalError::MissingResource(path.to_string()));
+        }
+        Ok(XpFile::read(&mut File::open(path)?)?)
+    }
+
     /// Read a xp image from a stream
     pub fn read<R: Read>(f: &mut R) -> io::Result<XpFile> {
         let mut rdr = GzDecoder::new(f);
         let version = rdr.read_i32::<LittleEndian>()?;
         let num_layers = rdr.read_u32::<LittleEndian>()?;
 
-        let mut layers = Vec::<XpLayer>::new();
-        layers.reserve(num_layers as usize);
+        let mut layers = Vec::<XpLayer>::with_capacity(num_layers as usize);
         for _ in 0..num_layers {
             let width = rdr.read_u32::<LittleEndian>()? as usize;
             let height = rdr.read_u32::<LittleEndian>()? as usize;
 
-            let mut cells = Vec::<XpCell>::new();
-            cells.reserve(width * height);
+            let mut cells = Vec::<XpCell>::with_capacity(width * height);
             for _ in 0..width {
                 // column-major order
                 for _ in 0..height {
@@ -1,12 +1,10 @@
 //! A simple implementation of a Console
 
 use crate::backend::font::Font;
-use crate::backend::shader::Shader;
-use crate::backend::simple_console_backing::SimpleConsoleBackend;
-use crate::backend::FractalPlatform;
+use crate::backend::Backend;
 use crate::codepage437::string_to_cp437;
 use crate::color;
-use crate::color::RGB;
+use crate::color::RGBA;
 use crate::console::Console;
 use crate::console::Tile;
 use crate::geometry::Rect;
@@ -29,20 +27,20 @@ pub struct SimpleConsole {
     offset_x: f32,
     offset_y: f32,
 
-    backend: SimpleConsoleBackend,
+    backing: usize,
 }
 
 impl SimpleConsole {
     /// Initializes a console, ready to add to FRACTAL's console list.
-    pub fn init(width: u32, height: u32, platform: &FractalPlatform) -> Box<SimpleConsole> {
+    pub fn init(width: u32, height: u32, backend: &mut dyn Backend) -> Box<SimpleConsole> {
         // Console backing init
         let num_tiles: usize = (width * height) as usize;
         let mut tiles: Vec<Tile> = Vec::with_capacity(num_tiles);
         for _ in 0..num_tiles {
             tiles.push(Tile {
                 glyph: 0,
-                fg: color::WHITE,
-                bg: color::BLACK,
+                fg: color::WHITE.into(),
+                bg: color::BLACK.into(),
             });
         }
 
@@ -53,17 +51,18 @@ impl SimpleConsole {
             is_dirty: true,
             offset_x: 0.0,
             offset_y: 0.0,
-            backend: SimpleConsoleBackend::new(platform, width as usize, height as usize),
+            backing: backend.create_tile_backing(width, height),
         };
 
         Box::new(new_console)
     }
 
-    fn rebuild_vertices(&mut self, platform: &FractalPlatform) {
-        self.backend.rebuild_vertices(
-            platform,
-            self.height,
+    fn rebuild_vertices(&mut self, font: &Font, backend: &mut dyn Backend) {
+        backend.rebuild_tiles(
+            self.backing,
+            font,
             self.width,
+            self.height,
             &self.tiles,
             self.offset_x,
             self.offset_y,
@@ -73,9 +72,9 @@ impl SimpleConsole {
 
 impl Console for SimpleConsole {
     /// Check if the console has changed, and if it has rebuild the backing buffer.
-    fn rebuild_if_dirty(&mut self, platform: &FractalPlatform) {
+    fn rebuild_if_dirty(&mut self, font: &Font, backend: &mut dyn Backend) {
         if self.is_dirty {
-            self.rebuild_vertices(platform);
+            self.rebuild_vertices(font, backend);
             self.is_dirty = false;
         }
     }
@@ -88,10 +87,26 @@ impl Console for SimpleConsole {
         self.is_dirty = true;
     }
 
-    /// Sends the console to OpenGL.
-    fn gl_draw(&mut self, font: &Font, shader: &Shader, platform: &FractalPlatform) {
-        self.backend
-            .gl_draw(font, shader, platform, self.width, self.height);
+    fn set_char_size(&mut self, width: u32, height: u32) {
+        if (width, height) == (self.width, self.height) {
+            return;
+        }
+        self.width = width;
+        self.height = height;
+        self.tiles = vec![
+            Tile {
+                glyph: 0,
+                fg: color::WHITE.into(),
+                bg: color::BLACK.into(),
+            };
+            (width * height) as usize
+        ];
+        self.is_dirty = true;
+    }
+
+    /// Sends the console to the backend.
+    fn draw(&mut self, font: &Font, shader_index: usize, backend: &mut dyn Backend) {
+        backend.draw_tiles(self.backing, font, shader_index);
         self.is_dirty = false;
     }
 
@@ -105,17 +120,17 @@ impl Console for SimpleConsole {
         self.is_dirty = true;
         for tile in &mut self.tiles {
             tile.glyph = 32;
-            tile.fg = color::WHITE;
-            tile.bg = color::BLACK;
+            tile.fg = color::WHITE.into();
+            tile.bg = color::BLACK.into();
         }
     }
 
     /// Clears the screen with a background color.
-    fn cls_bg(&mut self, background: RGB) {
+    fn cls_bg(&mut self, background: RGBA) {
         self.is_dirty = true;
         for tile in &mut self.tiles {
             tile.glyph = 32;
-            tile.fg = color::WHITE;
+            tile.fg = color::WHITE.into();
             tile.bg = background;
         }
     }
@@ -135,7 +150,7 @@ impl Console for SimpleConsole {
     }
 
     /// Prints a string at x/y, with foreground and background colors.
-    fn print_color(&mut self, x: i32, y: i32, fg: RGB, bg: RGB, output: &str) {
+    fn print_color(&mut self, x: i32, y: i32, fg: RGBA, bg: RGBA, output: &str) {
         self.is_dirty = true;
         let mut idx = self.at(x, y);
 
@@ -151,31 +166,33 @@ impl Console for SimpleConsole {
     }
 
     /// Sets a single cell in the console
-    fn set(&mut self, x: i32, y: i32, fg: RGB, bg: RGB, glyph: u8) {
+    fn set(&mut self, x: i32, y: i32, fg: RGBA, bg: RGBA, glyph: u16) {
         let idx = self.at(x, y);
         self.tiles[idx].glyph = glyph;
         self.tiles[idx].fg = fg;
         self.tiles[idx].bg = bg;
+        self.is_dirty = true;
     }
 
     /// Sets a single cell in the console's background
-    fn set_bg(&mut self, x: i32, y: i32, bg: RGB) {
+    fn set_bg(&mut self, x: i32, y: i32, bg: RGBA) {
         let idx = self.at(x, y);
         self.tiles[idx].bg = bg;
+        self.is_dirty = true;
     }
 
     /// Draws a box, starting at x/y with the extents width/height using CP437 line characters
-    fn draw_box(&mut self, sx: i32, sy: i32, width: i32, height: i32, fg: RGB, bg: RGB) {
+    fn draw_box(&mut self, sx: i32, sy: i32, width: i32, height: i32, fg: RGBA, bg: RGBA) {
         gui_helpers::draw_box(self, sx, sy, width, height, fg, bg);
     }
 
     /// Draws a box, starting at x/y with the extents width/height using CP437 line characters
-    fn draw_hollow_box(&mut self, sx: i32, sy: i32, width: i32, height: i32, fg: RGB, bg: RGB) {
+    fn draw_hollow_box(&mut self, sx: i32, sy: i32, width: i32, height: i32, fg: RGBA, bg: RGBA) {
         gui_helpers::draw_hollow_box(self, sx, sy, width, height, fg, bg);
     }
 
     /// Draws a box, starting at x/y with the extents width/height using CP437 double line characters
-    fn draw_box_double(&mut self, sx: i32, sy: i32, width: i32, height: i32, fg: RGB, bg: RGB) {
+    fn draw_box_double(&mut self, sx: i32, sy: i32, width: i32, height: i32, fg: RGBA, bg: RGBA) {
         gui_helpers::draw_box_double(self, sx, sy, width, height, fg, bg);
     }
 
@@ -186,21 +203,21 @@ impl Console for SimpleConsole {
         sy: i32,
         width: i32,
         height: i32,
-        fg: RGB,
-        bg: RGB,
+        fg: RGBA,
+        bg: RGBA,
     ) {
         gui_helpers::draw_hollow_box_double(self, sx, sy, width, height, fg, bg);
     }
 
     /// Fills a rectangle with the specified rendering information
-    fn fill_region(&mut self, target: Rect, glyph: u8, fg: RGB, bg: RGB) {
+    fn fill_region(&mut self, target: Rect, glyph: u16, fg: RGBA, bg: RGBA) {
         target.for_each(|point| {
             self.set(point.x, point.y, fg, bg, glyph);
         });
     }
 
     /// Gets the content of a cell
-    fn get(&self, x: i32, y: i32) -> Option<(&u8, &RGB, &RGB)> {
+    fn get(&self, x: i32, y: i32) -> Option<(&u16, &RGBA, &RGBA)> {
         if x < self.width as i32 && y < self.height as i32 {
             let idx = self.at(x, y);
             Some((
@@ -221,8 +238,8 @@ impl Console for SimpleConsole {
         width: i32,
         n: i32,
         max: i32,
-        fg: RGB,
-        bg: RGB,
+        fg: RGBA,
+        bg: RGBA,
     ) {
         gui_helpers::draw_bar_horizontal(self, sx, sy, width, n, max, fg, bg);
     }
@@ -235,8 +252,8 @@ impl Console for SimpleConsole {
         height: i32,
         n: i32,
         max: i32,
-        fg: RGB,
-        bg: RGB,
+        fg: RGBA,
+        bg: RGBA,
     ) {
         gui_helpers::draw_bar_vertical(self, sx, sy, height, n, max, fg, bg);
     }
@@ -252,7 +269,7 @@ impl Console for SimpleConsole {
     }
 
     /// Prints text in color, centered to the whole console width, at vertical location y.
-    fn print_color_centered(&mut self, y: i32, fg: RGB, bg: RGB, text: &str) {
+    fn print_color_centered(&mut self, y: i32, fg: RGBA, bg: RGBA, text: &str) {
         self.is_dirty = true;
         self.print_color(
             (self.width as i32 / 2) - (text.to_string().len() as i32 / 2),
@@ -286,6 +303,7 @@ impl Console for SimpleConsole {
     fn set_offset(&mut self, x: f32, y: f32) {
         self.offset_x = x * (2.0 / self.width as f32);
         self.offset_y = y * (2.0 / self.height as f32);
+        self.is_dirty = true;
     }
 
     fn as_any(&self) -> &dyn Any {
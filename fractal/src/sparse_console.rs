@@ -0,0 +1,300 @@
+//! A console that only stores the cells that were set
+
+use crate::backend::font::Font;
+use crate::backend::Backend;
+use crate::codepage437::string_to_cp437;
+use crate::color;
+use crate::color::RGBA;
+use crate::console::Console;
+use crate::geometry::Rect;
+use crate::gui_helpers;
+use crate::rex::XpColor;
+use crate::rex::XpLayer;
+use std::any::Any;
+
+/// Internal storage structure for sparse tiles.
+#[derive(PartialEq, Copy, Clone)]
+pub struct SparseTile {
+    /// Index of the cell, as given by Console::at
+    pub idx: usize,
+    /// The CP437 value to render the tile as
+    pub glyph: u16,
+    /// The Color of the glyph
+    pub fg: RGBA,
+    /// The Color behind the glyph
+    pub bg: RGBA,
+}
+
+/// A sparse console. Cells that were never set are transparent, and cost nothing to draw;
+/// this makes it a good fit for overlays and tooltips.
+pub struct SparseConsole {
+    /// Width of the console in characters
+    pub width: u32,
+    /// Height of the console in characters
+    pub height: u32,
+    /// The tiles that have been set, sorted by index
+    pub tiles: Vec<SparseTile>,
+    /// Boolean that tells the engine if the console needs to be redrawn
+    pub is_dirty: bool,
+
+    // To handle offset tiles for people who want thin walls between tiles
+    offset_x: f32,
+    offset_y: f32,
+
+    backing: usize,
+}
+
+impl SparseConsole {
+    /// Initializes the console, ready to add to FRACTAL's console list.
+    pub fn init(width: u32, height: u32, backend: &mut dyn Backend) -> Box<SparseConsole> {
+        let new_console = SparseConsole {
+            width,
+            height,
+            tiles: Vec::new(),
+            is_dirty: true,
+            offset_x: 0.0,
+            offset_y: 0.0,
+            backing: backend.create_sparse_backing(width, height),
+        };
+
+        Box::new(new_console)
+    }
+
+    /// Sets the tile at idx, replacing anything already there.
+    fn set_idx(&mut self, idx: usize, glyph: u16, fg: RGBA, bg: RGBA) {
+        if idx >= (self.width * self.height) as usize {
+            return;
+        }
+        self.is_dirty = true;
+        let tile = SparseTile { idx, glyph, fg, bg };
+        match self.find(idx) {
+            Ok(pos) => self.tiles[pos] = tile,
+            Err(pos) => self.tiles.insert(pos, tile),
+        }
+    }
+
+    /// Finds the tile at idx, or where it would go if it hasn't been set.
+    fn find(&self, idx: usize) -> Result<usize, usize> {
+        self.tiles.binary_search_by_key(&idx, |t| t.idx)
+    }
+
+    fn rebuild_vertices(&mut self, font: &Font, backend: &mut dyn Backend) {
+        backend.rebuild_sparse(
+            self.backing,
+            font,
+            self.width,
+            self.height,
+            &self.tiles,
+            self.offset_x,
+            self.offset_y,
+        );
+    }
+}
+
+impl Console for SparseConsole {
+    /// Check if the console has changed, and if it has rebuild the backing buffer.
+    fn rebuild_if_dirty(&mut self, font: &Font, backend: &mut dyn Backend) {
+        if self.is_dirty {
+            self.rebuild_vertices(font, backend);
+            self.is_dirty = false;
+        }
+    }
+
+    fn get_char_size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn resize_pixels(&mut self, _width: u32, _height: u32) {
+        self.is_dirty = true;
+    }
+
+    fn set_char_size(&mut self, width: u32, height: u32) {
+        if (width, height) == (self.width, self.height) {
+            return;
+        }
+        self.width = width;
+        self.height = height;
+        self.tiles.clear();
+        self.is_dirty = true;
+    }
+
+    /// Sends the console to the backend.
+    fn draw(&mut self, font: &Font, shader_index: usize, backend: &mut dyn Backend) {
+        backend.draw_sparse(self.backing, font, shader_index);
+        self.is_dirty = false;
+    }
+
+    /// Translate an x/y into an array index. Positions outside the console give an index past
+    /// the end, which setting a tile ignores.
+    fn at(&self, x: i32, y: i32) -> usize {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return (self.width * self.height) as usize;
+        }
+        (((self.height - 1 - y as u32) * self.width) + x as u32) as usize
+    }
+
+    /// Clears the screen.
+    fn cls(&mut self) {
+        self.is_dirty = true;
+        self.tiles.clear();
+    }
+
+    /// Clears the screen. A sparse console has no background to fill, so the color is ignored.
+    fn cls_bg(&mut self, _background: RGBA) {
+        self.is_dirty = true;
+        self.tiles.clear();
+    }
+
+    /// Prints a string at x/y, in white on black.
+    fn print(&mut self, x: i32, y: i32, output: &str) {
+        self.print_color(x, y, color::WHITE.into(), color::BLACK.into(), output);
+    }
+
+    /// Prints a string at x/y, with foreground and background colors.
+    fn print_color(&mut self, x: i32, y: i32, fg: RGBA, bg: RGBA, output: &str) {
+        let idx = self.at(x, y);
+
+        let bytes = string_to_cp437(output);
+        for (i, glyph) in bytes.into_iter().enumerate() {
+            self.set_idx(idx + i, glyph, fg, bg);
+        }
+    }
+
+    /// Sets a single cell in the console
+    fn set(&mut self, x: i32, y: i32, fg: RGBA, bg: RGBA, glyph: u16) {
+        let idx = self.at(x, y);
+        self.set_idx(idx, glyph, fg, bg);
+    }
+
+    /// Sets a single cell's background. Only cells that have already been set are changed.
+    fn set_bg(&mut self, x: i32, y: i32, bg: RGBA) {
+        if let Ok(pos) = self.find(self.at(x, y)) {
+            self.tiles[pos].bg = bg;
+            self.is_dirty = true;
+        }
+    }
+
+    /// Draws a box, starting at x/y with the extents width/height using CP437 line characters
+    fn draw_box(&mut self, sx: i32, sy: i32, width: i32, height: i32, fg: RGBA, bg: RGBA) {
+        gui_helpers::draw_box(self, sx, sy, width, height, fg, bg);
+    }
+
+    /// Draws a box, starting at x/y with the extents width/height using CP437 line characters
+    fn draw_hollow_box(&mut self, sx: i32, sy: i32, width: i32, height: i32, fg: RGBA, bg: RGBA) {
+        gui_helpers::draw_hollow_box(self, sx, sy, width, height, fg, bg);
+    }
+
+    /// Draws a box, starting at x/y with the extents width/height using CP437 double line characters
+    fn draw_box_double(&mut self, sx: i32, sy: i32, width: i32, height: i32, fg: RGBA, bg: RGBA) {
+        gui_helpers::draw_box_double(self, sx, sy, width, height, fg, bg);
+    }
+
+    /// Draws a box, starting at x/y with the extents width/height using CP437 double line characters
+    fn draw_hollow_box_double(
+        &mut self,
+        sx: i32,
+        sy: i32,
+        width: i32,
+        height: i32,
+        fg: RGBA,
+        bg: RGBA,
+    ) {
+        gui_helpers::draw_hollow_box_double(self, sx, sy, width, height, fg, bg);
+    }
+
+    /// Fills a rectangle with the specified rendering information
+    fn fill_region(&mut self, target: Rect, glyph: u16, fg: RGBA, bg: RGBA) {
+        target.for_each(|point| {
+            self.set(point.x, point.y, fg, bg, glyph);
+        });
+    }
+
+    /// Gets the content of a cell, if it has been set
+    fn get(&self, x: i32, y: i32) -> Option<(&u16, &RGBA, &RGBA)> {
+        self.find(self.at(x, y))
+            .ok()
+            .map(|pos| &self.tiles[pos])
+            .map(|t| (&t.glyph, &t.fg, &t.bg))
+    }
+
+    /// Draws a horizontal progress bar
+    fn draw_bar_horizontal(
+        &mut self,
+        sx: i32,
+        sy: i32,
+        width: i32,
+        n: i32,
+        max: i32,
+        fg: RGBA,
+        bg: RGBA,
+    ) {
+        gui_helpers::draw_bar_horizontal(self, sx, sy, width, n, max, fg, bg);
+    }
+
+    /// Draws a vertical progress bar
+    fn draw_bar_vertical(
+        &mut self,
+        sx: i32,
+        sy: i32,
+        height: i32,
+        n: i32,
+        max: i32,
+        fg: RGBA,
+        bg: RGBA,
+    ) {
+        gui_helpers::draw_bar_vertical(self, sx, sy, height, n, max, fg, bg);
+    }
+
+    /// Prints text, centered to the whole console width, at vertical location y.
+    fn print_centered(&mut self, y: i32, text: &str) {
+        self.print(
+            (self.width as i32 / 2) - (text.to_string().len() as i32 / 2),
+            y,
+            text,
+        );
+    }
+
+    /// Prints text in color, centered to the whole console width, at vertical location y.
+    fn print_color_centered(&mut self, y: i32, fg: RGBA, bg: RGBA, text: &str) {
+        self.print_color(
+            (self.width as i32 / 2) - (text.to_string().len() as i32 / 2),
+            y,
+            fg,
+            bg,
+            text,
+        );
+    }
+
+    /// Saves the layer to an XpFile structure. Cells that were never set are saved with
+    /// REX Paint's transparent background.
+    fn to_xp_layer(&self) -> XpLayer {
+        let mut layer = XpLayer::new(self.width as usize, self.height as usize);
+        for cell in layer.cells.iter_mut() {
+            cell.bg = XpColor::TRANSPARENT;
+        }
+
+        for tile in &self.tiles {
+            let x = tile.idx as u32 % self.width;
+            let y = self.height - 1 - (tile.idx as u32 / self.width);
+            let cell = layer.get_mut(x as usize, y as usize).unwrap();
+            cell.ch = u32::from(tile.glyph);
+            cell.fg = tile.fg.to_xp();
+            cell.bg = tile.bg.to_xp();
+        }
+
+        layer
+    }
+
+    /// Sets an offset to total console rendering, useful for layers that
+    /// draw between tiles. Offsets are specified as a percentage of total
+    /// character size; so -0.5 will offset half a character to the left/top.
+    fn set_offset(&mut self, x: f32, y: f32) {
+        self.is_dirty = true;
+        self.offset_x = x * (2.0 / self.width as f32);
+        self.offset_y = y * (2.0 / self.height as f32);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -0,0 +1,260 @@
+//! A console that draws sprites at pixel coordinates, rather than glyphs on a grid
+
+use crate::backend::font::Font;
+use crate::backend::sprite_sheet::SpriteSheet;
+use crate::backend::Backend;
+use crate::color::{RGB, RGBA};
+use crate::console::Console;
+use crate::geometry::Rect;
+use crate::rex::XpLayer;
+use std::any::Any;
+
+/// A sprite, placed on a sprite console
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct RenderSprite {
+    /// Index of the sprite on the console's sheet
+    pub sprite: usize,
+    /// Where to draw the sprite, in the console's pixels from the top-left
+    pub destination: Rect,
+    /// Sprites with a higher z-order are drawn on top of lower ones
+    pub z_order: i32,
+    /// Color the sprite is multiplied by; white leaves it unchanged
+    pub tint: RGB,
+}
+
+/// A sprite console. It covers the output with a canvas of width x height pixels, which is
+/// scaled to the window like any other console, and draws sprites from a sheet onto it with
+/// alpha transparency.
+///
+/// The text methods of Console do nothing here; put text on a regular console layered with it.
+/// Register it with register_console like any other console; the font is not used, but has to
+/// exist.
+pub struct SpriteConsole {
+    /// Width of the canvas in pixels
+    pub width: u32,
+    /// Height of the canvas in pixels
+    pub height: u32,
+    /// The sprites to draw
+    pub sprites: Vec<RenderSprite>,
+    /// Boolean that tells the engine if the console needs to be redrawn
+    pub is_dirty: bool,
+
+    sheet: SpriteSheet,
+    backing: usize,
+}
+
+impl SpriteConsole {
+    /// Initializes the console, ready to add to FRACTAL's console list. The sheet should have
+    /// been registered with Fractal::register_sprite_sheet first, so the backend has loaded it.
+    pub fn init(
+        width: u32,
+        height: u32,
+        sheet: &SpriteSheet,
+        backend: &mut dyn Backend,
+    ) -> Box<SpriteConsole> {
+        let new_console = SpriteConsole {
+            width,
+            height,
+            sprites: Vec::new(),
+            is_dirty: true,
+            sheet: sheet.clone(),
+            backing: backend.create_sprite_backing(),
+        };
+
+        Box::new(new_console)
+    }
+
+    /// The sprite sheet the console draws from
+    pub fn sheet(&self) -> &SpriteSheet {
+        &self.sheet
+    }
+
+    /// Draws a sprite at its natural size, with its top-left corner at x/y.
+    pub fn draw_sprite(&mut self, sprite: usize, x: i32, y: i32, z_order: i32, tint: RGB) {
+        let loc = self.sheet.sprites[sprite].sheet_location;
+        self.draw_sprite_scaled(
+            sprite,
+            Rect::new(x, y, loc.width(), loc.height()),
+            z_order,
+            tint,
+        );
+    }
+
+    /// Draws a sprite stretched to fill destination.
+    pub fn draw_sprite_scaled(
+        &mut self,
+        sprite: usize,
+        destination: Rect,
+        z_order: i32,
+        tint: RGB,
+    ) {
+        self.is_dirty = true;
+        self.sprites.push(RenderSprite {
+            sprite,
+            destination,
+            z_order,
+            tint,
+        });
+    }
+
+    /// Draws a sprite by name at its natural size. Unknown names are ignored.
+    pub fn draw_sprite_named(&mut self, name: &str, x: i32, y: i32, z_order: i32, tint: RGB) {
+        if let Some(sprite) = self.sheet.sprite_index(name) {
+            self.draw_sprite(sprite, x, y, z_order, tint);
+        }
+    }
+
+    fn rebuild_vertices(&mut self, backend: &mut dyn Backend) {
+        // Stable, so sprites with the same z-order keep the order they were added in
+        self.sprites.sort_by_key(|s| s.z_order);
+        backend.rebuild_sprites(
+            self.backing,
+            self.width,
+            self.height,
+            &self.sheet,
+            &self.sprites,
+        );
+    }
+}
+
+impl Console for SpriteConsole {
+    /// Check if the console has changed, and if it has rebuild the backing buffer.
+    fn rebuild_if_dirty(&mut self, _font: &Font, backend: &mut dyn Backend) {
+        if self.is_dirty {
+            self.rebuild_vertices(backend);
+            self.is_dirty = false;
+        }
+    }
+
+    /// The size of the canvas, in pixels
+    fn get_char_size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn resize_pixels(&mut self, _width: u32, _height: u32) {
+        self.is_dirty = true;
+    }
+
+    fn set_char_size(&mut self, width: u32, height: u32) {
+        if (width, height) == (self.width, self.height) {
+            return;
+        }
+        self.width = width;
+        self.height = height;
+        self.cls();
+    }
+
+    /// Sends the console to the backend. The font and shader are not used, sprites always
+    /// come from the console's sheet.
+    fn draw(&mut self, _font: &Font, _shader_index: usize, backend: &mut dyn Backend) {
+        backend.draw_sprites(self.backing, &self.sheet);
+        self.is_dirty = false;
+    }
+
+    /// Translate an x/y into an array index.
+    fn at(&self, x: i32, y: i32) -> usize {
+        ((y as u32 * self.width) + x as u32) as usize
+    }
+
+    /// Removes all sprites.
+    fn cls(&mut self) {
+        self.is_dirty = true;
+        self.sprites.clear();
+    }
+
+    /// Removes all sprites. The color is ignored.
+    fn cls_bg(&mut self, _background: RGBA) {
+        self.is_dirty = true;
+        self.sprites.clear();
+    }
+
+    fn print(&mut self, _x: i32, _y: i32, _output: &str) {}
+
+    fn print_color(&mut self, _x: i32, _y: i32, _fg: RGBA, _bg: RGBA, _output: &str) {}
+
+    fn set(&mut self, _x: i32, _y: i32, _fg: RGBA, _bg: RGBA, _glyph: u16) {}
+
+    fn set_bg(&mut self, _x: i32, _y: i32, _bg: RGBA) {}
+
+    fn draw_box(&mut self, _x: i32, _y: i32, _width: i32, _height: i32, _fg: RGBA, _bg: RGBA) {}
+
+    fn draw_hollow_box(
+        &mut self,
+        _x: i32,
+        _y: i32,
+        _width: i32,
+        _height: i32,
+        _fg: RGBA,
+        _bg: RGBA,
+    ) {
+    }
+
+    fn draw_box_double(
+        &mut self,
+        _x: i32,
+        _y: i32,
+        _width: i32,
+        _height: i32,
+        _fg: RGBA,
+        _bg: RGBA,
+    ) {
+    }
+
+    fn draw_hollow_box_double(
+        &mut self,
+        _x: i32,
+        _y: i32,
+        _width: i32,
+        _height: i32,
+        _fg: RGBA,
+        _bg: RGBA,
+    ) {
+    }
+
+    fn fill_region(&mut self, _target: Rect, _glyph: u16, _fg: RGBA, _bg: RGBA) {}
+
+    /// Sprite consoles have no cells, so this is always None
+    fn get(&self, _x: i32, _y: i32) -> Option<(&u16, &RGBA, &RGBA)> {
+        None
+    }
+
+    fn draw_bar_horizontal(
+        &mut self,
+        _x: i32,
+        _y: i32,
+        _width: i32,
+        _n: i32,
+        _max: i32,
+        _fg: RGBA,
+        _bg: RGBA,
+    ) {
+    }
+
+    fn draw_bar_vertical(
+        &mut self,
+        _x: i32,
+        _y: i32,
+        _height: i32,
+        _n: i32,
+        _max: i32,
+        _fg: RGBA,
+        _bg: RGBA,
+    ) {
+    }
+
+    fn print_centered(&mut self, _y: i32, _text: &str) {}
+
+    fn print_color_centered(&mut self, _y: i32, _fg: RGBA, _bg: RGBA, _text: &str) {}
+
+    /// REX Paint has no notion of sprites, so this produces an empty layer.
+    fn to_xp_layer(&self) -> XpLayer {
+        XpLayer::new(0, 0)
+    }
+
+    /// Sprites are placed in pixels, so offsets are not supported.
+    fn set_offset(&mut self, _x: f32, _y: f32) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -2,7 +2,7 @@
 
 use crate::codepage437::string_to_cp437;
 use crate::color;
-use crate::color::RGB;
+use crate::color::RGBA;
 use crate::console::Console;
 use crate::console::Tile;
 
@@ -12,8 +12,8 @@ pub struct TextBlock {
     y: i32,
     width: i32,
     height: i32,
-    fg: RGB,
-    bg: RGB,
+    fg: RGBA,
+    bg: RGBA,
     buffer: Vec<Tile>,
     cursor: (i32, i32),
 }
@@ -26,13 +26,13 @@ impl TextBlock {
             y,
             width,
             height,
-            fg: color::WHITE,
-            bg: color::BLACK,
+            fg: color::WHITE.into(),
+            bg: color::BLACK.into(),
             buffer: vec![
                 Tile {
                     glyph: 0,
-                    fg: color::WHITE,
-                    bg: color::BLACK,
+                    fg: color::WHITE.into(),
+                    bg: color::BLACK.into(),
                 };
                 width as usize * height as usize
             ],
@@ -41,13 +41,13 @@ impl TextBlock {
     }
 
     /// Sets the foreground
-    pub fn fg(&mut self, fg: RGB) {
-        self.fg = fg;
+    pub fn fg<C: Into<RGBA>>(&mut self, fg: C) {
+        self.fg = fg.into();
     }
 
     /// Sets the background
-    pub fn bg(&mut self, bg: RGB) {
-        self.bg = bg;
+    pub fn bg<C: Into<RGBA>>(&mut self, bg: C) {
+        self.bg = bg.into();
     }
 
     /// Moves the cursor to a new location
@@ -118,13 +118,13 @@ impl TextBlock {
                 CommandType::Background { col } => self.bg = *col,
                 CommandType::Reset {} => {
                     self.cursor = (0, 0);
-                    self.fg = color::WHITE;
-                    self.bg = color::BLACK;
+                    self.fg = color::WHITE.into();
+                    self.bg = color::BLACK.into();
                 }
 
                 CommandType::TextWrapper { block: t } => {
                     for word in t.split(' ') {
-                        let mut chrs = string_to_cp437(&word);
+                        let mut chrs = string_to_cp437(word);
                         chrs.push(32);
                         if self.cursor.0 + chrs.len() as i32 >= self.width {
                             self.cursor.0 = 0;
@@ -153,24 +153,24 @@ pub enum CommandType {
     /// TODO
     Text {
         /// TODO
-        block: Vec<u8>,
+        block: Vec<u16>,
     },
     /// TODO
     Centered {
         /// TODO
-        block: Vec<u8>,
+        block: Vec<u16>,
     },
     /// TODO
     NewLine {},
     /// TODO
     Foreground {
         /// TODO
-        col: RGB,
+        col: RGBA,
     },
     /// TODO
     Background {
         /// TODO
-        col: RGB,
+        col: RGBA,
     },
     /// TODO
     TextWrapper {
@@ -182,6 +182,7 @@ pub enum CommandType {
 }
 
 /// Struct to easily create TextBlocks
+#[derive(Default)]
 pub struct TextBuilder {
     commands: Vec<CommandType>,
 }
@@ -189,14 +190,14 @@ pub struct TextBuilder {
 impl TextBuilder {
     /// Appends a string to the buffer
     pub fn append(&mut self, text: &str) -> &mut Self {
-        let chrs = string_to_cp437(&text);
+        let chrs = string_to_cp437(text);
         self.commands.push(CommandType::Text { block: chrs });
         self
     }
 
     /// Appends a centered string to the buffer
     pub fn centered(&mut self, text: &str) -> &mut Self {
-        let chrs = string_to_cp437(&text);
+        let chrs = string_to_cp437(text);
         self.commands.push(CommandType::Centered { block: chrs });
         self
     }
@@ -214,14 +215,16 @@ impl TextBuilder {
     }
 
     /// Sets the foreground color
-    pub fn fg(&mut self, col: RGB) -> &mut Self {
-        self.commands.push(CommandType::Foreground { col });
+    pub fn fg<C: Into<RGBA>>(&mut self, col: C) -> &mut Self {
+        self.commands
+            .push(CommandType::Foreground { col: col.into() });
         self
     }
 
     /// Sets the background color
-    pub fn bg(&mut self, col: RGB) -> &mut Self {
-        self.commands.push(CommandType::Background { col });
+    pub fn bg<C: Into<RGBA>>(&mut self, col: C) -> &mut Self {
+        self.commands
+            .push(CommandType::Background { col: col.into() });
         self
     }
 
@@ -233,11 +236,3 @@ impl TextBuilder {
         self
     }
 }
-
-impl Default for TextBuilder {
-    fn default() -> Self {
-        Self {
-            commands: Vec::new(),
-        }
-    }
-}
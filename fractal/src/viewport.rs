@@ -0,0 +1,113 @@
+//! Fitting the consoles into the window when it changes size
+
+/// What happens to the consoles when the window is resized
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ResizePolicy {
+    /// Stretch the consoles over the whole window, whatever its shape
+    #[default]
+    Stretch,
+    /// Scale by the largest whole number that fits and center the result, with black bars
+    /// around it. Glyphs stay pixel-perfect; a window smaller than the original falls back to
+    /// KeepAspect.
+    Letterbox,
+    /// Scale as large as fits without changing the aspect ratio, with black bars around it
+    KeepAspect,
+    /// Keep glyphs at their original size, and change the number of characters in each console
+    /// to fill the window
+    GrowConsoles,
+}
+
+/// The part of the window the consoles are drawn into, in pixels from the top-left corner
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    /// Left edge in pixels
+    pub x: i32,
+    /// Top edge in pixels
+    pub y: i32,
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+}
+
+impl Viewport {
+    /// A viewport covering a whole window of the given size
+    pub fn full(width: u32, height: u32) -> Viewport {
+        Viewport {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }
+    }
+
+    /// Works out the viewport for a window of `window` pixels, when the consoles were laid out
+    /// for a window of `content` pixels.
+    pub fn for_window(policy: ResizePolicy, window: (u32, u32), content: (u32, u32)) -> Viewport {
+        let (window_width, window_height) = window;
+        let (content_width, content_height) = (u32::max(1, content.0), u32::max(1, content.1));
+
+        let (width, height) = match policy {
+            ResizePolicy::Stretch | ResizePolicy::GrowConsoles => {
+                return Viewport::full(window_width, window_height)
+            }
+            ResizePolicy::Letterbox => {
+                let scale = u32::min(window_width / content_width, window_height / content_height);
+                if scale == 0 {
+                    return Viewport::for_window(ResizePolicy::KeepAspect, window, content);
+                }
+                (content_width * scale, content_height * scale)
+            }
+            ResizePolicy::KeepAspect => {
+                let scale = f32::min(
+                    window_width as f32 / content_width as f32,
+                    window_height as f32 / content_height as f32,
+                );
+                (
+                    (content_width as f32 * scale) as u32,
+                    (content_height as f32 * scale) as u32,
+                )
+            }
+        };
+
+        Viewport {
+            x: (window_width.saturating_sub(width) / 2) as i32,
+            y: (window_height.saturating_sub(height) / 2) as i32,
+            width,
+            height,
+        }
+    }
+
+    /// True if a position in window pixels is inside the viewport, rather than over the bars
+    /// around it
+    pub fn contains(&self, pos: (i32, i32)) -> bool {
+        pos.0 >= self.x
+            && pos.1 >= self.y
+            && i64::from(pos.0) < i64::from(self.x) + i64::from(self.width)
+            && i64::from(pos.1) < i64::from(self.y) + i64::from(self.height)
+    }
+
+    /// Converts a position in window pixels to a cell of a console that is `chars` characters
+    /// in size and fills this viewport. Positions outside the viewport are clamped to the
+    /// nearest edge cell; use `cell_at` to tell them apart.
+    pub fn to_cell(&self, pos: (i32, i32), chars: (u32, u32)) -> (i32, i32) {
+        let cell = |pos: i32, start: i32, pixels: u32, chars: u32| {
+            let pixels = i64::from(u32::max(1, pixels));
+            let cell = i64::from(pos - start) * i64::from(chars) / pixels;
+            i64::max(0, i64::min(cell, i64::from(chars) - 1)) as i32
+        };
+        (
+            cell(pos.0, self.x, self.width, chars.0),
+            cell(pos.1, self.y, self.height, chars.1),
+        )
+    }
+
+    /// Like `to_cell`, but None for positions over the bars around the viewport
+    pub fn cell_at(&self, pos: (i32, i32), chars: (u32, u32)) -> Option<(i32, i32)> {
+        if self.contains(pos) {
+            Some(self.to_cell(pos, chars))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! Renders consoles through the headless backend and checks the pixels that come out
+
+use fractal::animation::Animation;
+use fractal::backend::font::Font;
+use fractal::backend::headless::HeadlessBackend;
+use fractal::backend::mainloop;
+use fractal::color;
+use fractal::console::Console;
+use fractal::draw_batch::DrawBatch;
+use fractal::fractal::Fractal;
+use fractal::geometry::Point;
+use fractal::particles::Particle;
+use fractal::simple_console::SimpleConsole;
+use image::{Rgba, RgbaImage};
+
+const WIDTH: u32 = 10;
+const HEIGHT: u32 = 5;
+
+fn context() -> Fractal {
+    let mut ctx = Fractal::init_headless(WIDTH * 8, HEIGHT * 8);
+    let font = ctx
+        .register_font(Font::load("resources/terminal8x8.png", (8, 8)).unwrap())
+        .unwrap();
+    let console = SimpleConsole::init(WIDTH, HEIGHT, ctx.backend.as_mut());
+    ctx.register_console(console, font);
+    ctx
+}
+
+fn frame(ctx: &Fractal) -> &RgbaImage {
+    ctx.backend
+        .as_any()
+        .downcast_ref::<HeadlessBackend>()
+        .unwrap()
+        .frame()
+}
+
+/// The pixel in the middle of a console cell
+fn cell_center(frame: &RgbaImage, x: u32, y: u32) -> Rgba<u8> {
+    *frame.get_pixel(x * 8 + 4, y * 8 + 4)
+}
+
+#[test]
+fn renders_frame_at_window_size() {
+    let mut ctx = context();
+    mainloop::render(&mut ctx);
+    let frame = frame(&ctx);
+    assert_eq!(frame.dimensions(), (WIDTH * 8, HEIGHT * 8));
+}
+
+#[test]
+fn resize_changes_frame_size() {
+    let mut ctx = context();
+    ctx.backend.resize(WIDTH * 16, HEIGHT * 16);
+    assert_eq!(frame(&ctx).dimensions(), (WIDTH * 16, HEIGHT * 16));
+}
+
+#[test]
+fn draws_glyphs_and_backgrounds() {
+    let mut ctx = context();
+    ctx.cls();
+    // A space shows only its background; a full block only its foreground
+    ctx.set(0, 0, color::WHITE, color::BLUE, 32);
+    ctx.set(3, 2, color::RED, color::BLACK, 219);
+    ctx.print_color(5, 4, color::GREEN, color::YELLOW, "  ");
+    mainloop::render(&mut ctx);
+
+    let frame = frame(&ctx);
+    assert_eq!(cell_center(frame, 0, 0), Rgba([0, 0, 255, 255]));
+    assert_eq!(cell_center(frame, 3, 2), Rgba([255, 0, 0, 255]));
+    assert_eq!(cell_center(frame, 5, 4), Rgba([255, 255, 0, 255]));
+    assert_eq!(cell_center(frame, 6, 4), Rgba([255, 255, 0, 255]));
+    assert_eq!(cell_center(frame, 9, 0), Rgba([0, 0, 0, 255]));
+}
+
+#[test]
+fn tween_shows_on_console_that_was_not_redrawn() {
+    let mut ctx = context();
+    ctx.cls();
+    mainloop::render(&mut ctx);
+
+    ctx.animate(Animation::bg(2, 1, color::BLACK, color::MAGENTA, 0.0));
+    mainloop::render(&mut ctx);
+    assert_eq!(cell_center(frame(&ctx), 2, 1), Rgba([255, 0, 255, 255]));
+}
+
+#[test]
+fn moving_particle_leaves_no_trail() {
+    let mut ctx = context();
+    ctx.cls();
+    ctx.spawn_particle(Particle::new(2, 1, 219, color::RED, 1000.0));
+    mainloop::render(&mut ctx);
+    assert_eq!(cell_center(frame(&ctx), 2, 1), Rgba([255, 0, 0, 255]));
+
+    ctx.particles.particles[0].position = (4.0, 1.0);
+    mainloop::render(&mut ctx);
+    let frame = frame(&ctx);
+    assert_eq!(cell_center(frame, 2, 1), Rgba([0, 0, 0, 255]));
+    assert_eq!(cell_center(frame, 4, 1), Rgba([255, 0, 0, 255]));
+}
+
+#[test]
+fn submitted_batches_draw_in_z_order() {
+    let mut ctx = context();
+    ctx.cls();
+    let buffer = ctx.draw_buffer.clone();
+    let mut batch = DrawBatch::new();
+    batch.set(Point::new(1, 1), color::BLUE, color::BLACK, 219);
+    batch.submit(&buffer, 1);
+    batch.set(Point::new(1, 1), color::RED, color::BLACK, 219);
+    batch.submit(&buffer, 0);
+    mainloop::render(&mut ctx);
+    assert_eq!(cell_center(frame(&ctx), 1, 1), Rgba([0, 0, 255, 255]));
+}
+
+#[test]
+fn screenshot_includes_batches_and_particles() {
+    let mut ctx = context();
+    ctx.cls();
+    let mut batch = DrawBatch::new();
+    batch.set(Point::new(1, 1), color::BLUE, color::BLACK, 219);
+    batch.submit(&ctx.draw_buffer, 0);
+    ctx.spawn_particle(Particle::new(3, 2, 219, color::RED, 1000.0));
+
+    let shot = ctx.screenshot();
+    assert_eq!(cell_center(&shot, 1, 1), Rgba([0, 0, 255, 255]));
+    assert_eq!(cell_center(&shot, 3, 2), Rgba([255, 0, 0, 255]));
+    assert!(*shot == **frame(&ctx));
+}
@@ -28,9 +28,7 @@ pub fn derive(input: TokenStream) -> TokenStream {
     let opt_fields = fields.iter().map(|f| {
         let name = &f.ident;
         let ty = &f.ty;
-        if get_inner_ty("Option", ty).is_some() {
-            quote! { #name: #ty }
-        } else if builder_of(f).is_some() {
+        if get_inner_ty("Option", ty).is_some() || builder_of(f).is_some() {
             quote! { #name: #ty }
         } else {
             quote! { #name: std::option::Option<#ty> }
@@ -118,12 +116,9 @@ pub fn derive(input: TokenStream) -> TokenStream {
 }
 
 fn builder_of(f: &Field) -> Option<&Attribute> {
-    for attr in &f.attrs {
-        if attr.path.segments.len() == 1 && attr.path.segments[0].ident == "builder" {
-            return Some(attr);
-        }
-    }
-    None
+    f.attrs
+        .iter()
+        .find(|attr| attr.path.segments.len() == 1 && attr.path.segments[0].ident == "builder")
 }
 
 macro_rules! err {
@@ -182,13 +177,11 @@ fn get_inner_ty<'a>(outer: &str, ty: &'a syn::Type) -> Option<&'a syn::Type> {
                 return None;
             }
 
-            for arg in &inner_ty.args {
-                return if let GenericArgument::Type(t) = arg {
-                    Some(t)
-                } else {
-                    None
-                };
-            }
+            return if let Some(GenericArgument::Type(t)) = inner_ty.args.first() {
+                Some(t)
+            } else {
+                None
+            };
         }
     }
     None
@@ -0,0 +1,143 @@
+use fractal::input_map::{Binding, InputMap};
+use fractal::VirtualKeyCode as Key;
+use std::fmt;
+
+/// Where players can put their own key bindings
+pub const CONTROLS_FILE: &str = "./controls.toml";
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Action {
+    MoveWest,
+    MoveEast,
+    MoveNorth,
+    MoveSouth,
+    MoveNorthEast,
+    MoveNorthWest,
+    MoveSouthEast,
+    MoveSouthWest,
+    PickUp,
+    Inventory,
+    Drop,
+    Remove,
+    Descend,
+    SkipTurn,
+    SaveAndQuit,
+    TogglePerfOverlay,
+}
+
+impl Action {
+    pub const ALL: [Action; 16] = [
+        Action::MoveWest,
+        Action::MoveEast,
+        Action::MoveNorth,
+        Action::MoveSouth,
+        Action::MoveNorthEast,
+        Action::MoveNorthWest,
+        Action::MoveSouthEast,
+        Action::MoveSouthWest,
+        Action::PickUp,
+        Action::Inventory,
+        Action::Drop,
+        Action::Remove,
+        Action::Descend,
+        Action::SkipTurn,
+        Action::SaveAndQuit,
+        Action::TogglePerfOverlay,
+    ];
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Actions used to get around the menus, bound in the same map as the ones above
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum MenuAction {
+    Up,
+    Down,
+    Select,
+    Cancel,
+    /// Picks the nth entry of a lettered menu
+    Item(u8),
+}
+
+impl fmt::Display for MenuAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MenuAction::Item(n) => write!(f, "MenuItem{}", (b'A' + n) as char),
+            _ => write!(f, "Menu{:?}", self),
+        }
+    }
+}
+
+/// The keys that pick lettered menu entries, in order
+const MENU_LETTERS: [Key; 26] = [
+    Key::A,
+    Key::B,
+    Key::C,
+    Key::D,
+    Key::E,
+    Key::F,
+    Key::G,
+    Key::H,
+    Key::I,
+    Key::J,
+    Key::K,
+    Key::L,
+    Key::M,
+    Key::N,
+    Key::O,
+    Key::P,
+    Key::Q,
+    Key::R,
+    Key::S,
+    Key::T,
+    Key::U,
+    Key::V,
+    Key::W,
+    Key::X,
+    Key::Y,
+    Key::Z,
+];
+
+/// Arrow keys, the numeric keypad and vi keys; letters and Return/Escape in menus
+pub fn default_controls() -> InputMap {
+    let mut controls = InputMap::new();
+    let mut bind = |action: Action, keys: &[Key]| {
+        for key in keys {
+            controls.bind(action, Binding::key(*key));
+        }
+    };
+    bind(Action::MoveWest, &[Key::Left, Key::Numpad4, Key::H]);
+    bind(Action::MoveEast, &[Key::Right, Key::Numpad6, Key::L]);
+    bind(Action::MoveNorth, &[Key::Up, Key::Numpad8, Key::K]);
+    bind(Action::MoveSouth, &[Key::Down, Key::Numpad2, Key::J]);
+    bind(Action::MoveNorthEast, &[Key::Numpad9, Key::U]);
+    bind(Action::MoveNorthWest, &[Key::Numpad7, Key::Y]);
+    bind(Action::MoveSouthEast, &[Key::Numpad3, Key::N]);
+    bind(Action::MoveSouthWest, &[Key::Numpad1, Key::B]);
+    bind(Action::PickUp, &[Key::G]);
+    bind(Action::Inventory, &[Key::I]);
+    bind(Action::Drop, &[Key::D]);
+    bind(Action::Remove, &[Key::R]);
+    bind(Action::Descend, &[Key::Period]);
+    bind(Action::SkipTurn, &[Key::Numpad5, Key::Space]);
+    bind(Action::SaveAndQuit, &[Key::Escape]);
+    bind(Action::TogglePerfOverlay, &[Key::F3]);
+
+    controls.bind(MenuAction::Up, Binding::key(Key::Up));
+    controls.bind(MenuAction::Down, Binding::key(Key::Down));
+    controls.bind(MenuAction::Select, Binding::key(Key::Return));
+    controls.bind(MenuAction::Cancel, Binding::key(Key::Escape));
+    for (n, key) in MENU_LETTERS.iter().enumerate() {
+        controls.bind(MenuAction::Item(n as u8), Binding::key(*key));
+    }
+    controls
+}
+
+/// The player's bindings if they have a controls file, the defaults otherwise
+pub fn load_controls() -> InputMap {
+    InputMap::load(CONTROLS_FILE).unwrap_or_else(|_| default_controls())
+}
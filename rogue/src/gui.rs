@@ -1,16 +1,16 @@
 use crate::components::*;
+use crate::controls::MenuAction;
 use crate::gamelog::GameLog;
 use crate::map::Map;
 use crate::RunState;
 use crate::State;
+use fractal::camera::Camera;
 use fractal::codepage437::to_cp437;
 use fractal::color;
 use fractal::console::Console;
-use fractal::fractal::letter_to_option;
 use fractal::fractal::Fractal;
 use fractal::geometry::DistanceAlg::Pythagoras;
 use fractal::geometry::Point;
-use fractal::VirtualKeyCode;
 use specs::prelude::*;
 
 #[derive(PartialEq, Copy, Clone)]
@@ -56,32 +56,32 @@ pub fn draw_ui(ecs: &World, ctx: &mut Fractal) {
     ctx.print_color(2, 43, color::YELLOW, color::BLACK, &depth);
 
     let log = ecs.fetch::<GameLog>();
-    let mut y = 44;
-    for s in log.entries.iter() {
+    for (y, s) in (44..).zip(log.entries.iter()) {
         if y < 49 {
             ctx.print(2, y, &s.to_string());
         }
-        y += 1;
     }
 
     // Draw mouse cursor
-    let mouse_pos = ctx.mouse_pos();
-    ctx.set_bg(mouse_pos.0, mouse_pos.1, color::MAGENTA);
+    if let Some(mouse_pos) = ctx.mouse_cell() {
+        ctx.set_bg(mouse_pos.0, mouse_pos.1, color::MAGENTA);
+    }
     draw_tooltips(ecs, ctx);
 }
 
 fn draw_tooltips(ecs: &World, ctx: &mut Fractal) {
-    let map = ecs.fetch::<Map>();
+    let camera = ecs.fetch::<Camera>();
     let names = ecs.read_storage::<Name>();
     let positions = ecs.read_storage::<Position>();
 
     let mouse_pos = ctx.mouse_pos();
-    if mouse_pos.0 >= map.width || mouse_pos.1 >= map.height {
-        return;
-    }
+    let mouse_world = match camera.mouse_world_pos(ctx) {
+        Some(point) => point,
+        None => return,
+    };
     let mut tooltip: Vec<String> = Vec::new();
     for (name, position) in (&names, &positions).join() {
-        if position.x == mouse_pos.0 && position.y == mouse_pos.1 {
+        if position.x == mouse_world.x && position.y == mouse_world.y {
             tooltip.push(name.name.to_string());
         }
     }
@@ -98,53 +98,25 @@ fn draw_tooltips(ecs: &World, ctx: &mut Fractal) {
         if mouse_pos.0 > 40 {
             let arrow_pos = Point::new(mouse_pos.0 - 2, mouse_pos.1);
             let left_x = mouse_pos.0 - width;
-            let mut y = mouse_pos.1;
-            for s in tooltip.iter() {
-                ctx.print_color(left_x, y, color::WHITE, color::GRAY, &s.to_string());
+            for (y, s) in (mouse_pos.1..).zip(tooltip.iter()) {
+                ctx.print_color(left_x, y, color::WHITE, color::GRAY, s);
                 let padding = (width - s.len() as i32) - 1;
                 for i in 0..padding {
-                    ctx.print_color(
-                        arrow_pos.x - i,
-                        y,
-                        color::WHITE,
-                        color::GRAY,
-                        &" ".to_string(),
-                    );
+                    ctx.print_color(arrow_pos.x - i, y, color::WHITE, color::GRAY, " ");
                 }
-                y += 1;
             }
-            ctx.print_color(
-                arrow_pos.x,
-                arrow_pos.y,
-                color::WHITE,
-                color::GRAY,
-                &"->".to_string(),
-            );
+            ctx.print_color(arrow_pos.x, arrow_pos.y, color::WHITE, color::GRAY, "->");
         } else {
             let arrow_pos = Point::new(mouse_pos.0 + 1, mouse_pos.1);
             let left_x = mouse_pos.0 + 3;
-            let mut y = mouse_pos.1;
-            for s in tooltip.iter() {
-                ctx.print_color(left_x + 1, y, color::WHITE, color::GRAY, &s.to_string());
+            for (y, s) in (mouse_pos.1..).zip(tooltip.iter()) {
+                ctx.print_color(left_x + 1, y, color::WHITE, color::GRAY, s);
                 let padding = (width - s.len() as i32) - 1;
                 for i in 0..padding {
-                    ctx.print_color(
-                        arrow_pos.x + 1 + i,
-                        y,
-                        color::WHITE,
-                        color::GRAY,
-                        &" ".to_string(),
-                    );
+                    ctx.print_color(arrow_pos.x + 1 + i, y, color::WHITE, color::GRAY, " ");
                 }
-                y += 1;
             }
-            ctx.print_color(
-                arrow_pos.x,
-                arrow_pos.y,
-                color::WHITE,
-                color::GRAY,
-                &"<-".to_string(),
-            );
+            ctx.print_color(arrow_pos.x, arrow_pos.y, color::WHITE, color::GRAY, "<-");
         }
     }
 }
@@ -160,7 +132,7 @@ pub fn show_inventory(gs: &mut State, ctx: &mut Fractal) -> (ItemMenuResult, Opt
         .filter(|item| item.0.owner == *player_entity);
     let count = inventory.count();
 
-    let mut y = (25 - (count / 2)) as i32;
+    let y = (25 - (count / 2)) as i32;
     ctx.draw_box(
         15,
         y - 2,
@@ -179,37 +151,21 @@ pub fn show_inventory(gs: &mut State, ctx: &mut Fractal) -> (ItemMenuResult, Opt
     );
 
     let mut equippable: Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (entity, _pack, name) in (&entities, &backpack, &names)
+    for (j, (entity, _pack, name)) in (&entities, &backpack, &names)
         .join()
         .filter(|item| item.1.owner == *player_entity)
+        .enumerate()
     {
+        let y = y + j as i32;
         ctx.set(17, y, color::WHITE, color::BLACK, to_cp437('('));
-        ctx.set(18, y, color::YELLOW, color::BLACK, 97 + j as u8);
+        ctx.set(18, y, color::YELLOW, color::BLACK, 97 + j as u16);
         ctx.set(19, y, color::WHITE, color::BLACK, to_cp437(')'));
 
         ctx.print(21, y, &name.name.to_string());
         equippable.push(entity);
-        y += 1;
-        j += 1;
     }
 
-    match ctx.key {
-        None => (ItemMenuResult::NoResponse, None),
-        Some(key) => match key {
-            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
-            _ => {
-                let selection = letter_to_option(key);
-                if selection > -1 && selection < count as i32 {
-                    return (
-                        ItemMenuResult::Selected,
-                        Some(equippable[selection as usize]),
-                    );
-                }
-                (ItemMenuResult::NoResponse, None)
-            }
-        },
-    }
+    item_menu_input(ctx, &equippable)
 }
 
 pub fn drop_item_menu(gs: &mut State, ctx: &mut Fractal) -> (ItemMenuResult, Option<Entity>) {
@@ -223,7 +179,7 @@ pub fn drop_item_menu(gs: &mut State, ctx: &mut Fractal) -> (ItemMenuResult, Opt
         .filter(|item| item.0.owner == *player_entity);
     let count = inventory.count();
 
-    let mut y = (25 - (count / 2)) as i32;
+    let y = (25 - (count / 2)) as i32;
     ctx.draw_box(
         15,
         y - 2,
@@ -242,36 +198,35 @@ pub fn drop_item_menu(gs: &mut State, ctx: &mut Fractal) -> (ItemMenuResult, Opt
     );
 
     let mut equippable: Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (entity, _pack, name) in (&entities, &backpack, &names)
+    for (j, (entity, _pack, name)) in (&entities, &backpack, &names)
         .join()
         .filter(|item| item.1.owner == *player_entity)
+        .enumerate()
     {
+        let y = y + j as i32;
         ctx.set(17, y, color::WHITE, color::BLACK, to_cp437('('));
-        ctx.set(18, y, color::YELLOW, color::BLACK, 97 + j as u8);
+        ctx.set(18, y, color::YELLOW, color::BLACK, 97 + j as u16);
         ctx.set(19, y, color::WHITE, color::BLACK, to_cp437(')'));
 
         ctx.print(21, y, &name.name.to_string());
         equippable.push(entity);
-        y += 1;
-        j += 1;
     }
 
-    match ctx.key {
+    item_menu_input(ctx, &equippable)
+}
+
+/// Cancels the menu, or picks the entry whose letter was pressed
+fn item_menu_input(ctx: &Fractal, items: &[Entity]) -> (ItemMenuResult, Option<Entity>) {
+    if ctx.action_pressed(MenuAction::Cancel) {
+        return (ItemMenuResult::Cancel, None);
+    }
+    let selection = items
+        .iter()
+        .enumerate()
+        .find(|(n, _)| *n < 26 && ctx.action_pressed(MenuAction::Item(*n as u8)));
+    match selection {
+        Some((_, item)) => (ItemMenuResult::Selected, Some(*item)),
         None => (ItemMenuResult::NoResponse, None),
-        Some(key) => match key {
-            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
-            _ => {
-                let selection = letter_to_option(key);
-                if selection > -1 && selection < count as i32 {
-                    return (
-                        ItemMenuResult::Selected,
-                        Some(equippable[selection as usize]),
-                    );
-                }
-                (ItemMenuResult::NoResponse, None)
-            }
-        },
     }
 }
 
@@ -282,6 +237,7 @@ pub fn ranged_target(
 ) -> (ItemMenuResult, Option<Point>) {
     let player_entity = gs.ecs.fetch::<Entity>();
     let player_pos = gs.ecs.fetch::<Point>();
+    let camera = gs.ecs.fetch::<Camera>();
     let viewsheds = gs.ecs.read_storage::<Viewshed>();
 
     ctx.print_color(5, 0, color::YELLOW, color::BLACK, "Select Target:");
@@ -294,7 +250,9 @@ pub fn ranged_target(
         for idx in visible.visible_tiles.iter() {
             let distance = Pythagoras.distance2d(*player_pos, *idx);
             if distance <= range as f32 {
-                ctx.set_bg(idx.x, idx.y, color::BLUE);
+                if let Some(screen) = camera.world_to_screen(*idx) {
+                    ctx.set_bg(screen.x, screen.y, color::BLUE);
+                }
                 available_cells.push(idx);
             }
         }
@@ -302,21 +260,17 @@ pub fn ranged_target(
         return (ItemMenuResult::Cancel, None);
     }
 
-    // Draw mouse cursor
-    let mouse_pos = ctx.mouse_pos();
-    let mut valid_target = false;
-    for idx in available_cells.iter() {
-        if idx.x == mouse_pos.0 && idx.y == mouse_pos.1 {
-            valid_target = true;
-        }
-    }
+    // Draw mouse cursor. Over the bars around the viewport there is nothing to target.
+    let mouse_pos = match ctx.mouse_cell() {
+        Some(pos) => pos,
+        None => return (ItemMenuResult::NoResponse, None),
+    };
+    let mouse_world = camera.mouse_world_pos(ctx);
+    let valid_target = available_cells.iter().any(|idx| Some(**idx) == mouse_world);
     if valid_target {
         ctx.set_bg(mouse_pos.0, mouse_pos.1, color::CYAN);
         if ctx.left_click {
-            return (
-                ItemMenuResult::Selected,
-                Some(Point::new(mouse_pos.0, mouse_pos.1)),
-            );
+            return (ItemMenuResult::Selected, mouse_world);
         }
     } else {
         ctx.set_bg(mouse_pos.0, mouse_pos.1, color::RED);
@@ -358,58 +312,47 @@ pub fn main_menu(gs: &mut State, ctx: &mut Fractal) -> MainMenuResult {
             ctx.print_color_centered(26, color::WHITE, color::BLACK, "Quit");
         }
 
-        match ctx.key {
-            None => {
-                return MainMenuResult::NoSelection {
-                    selected: selection,
-                }
+        if ctx.action_pressed(MenuAction::Cancel) {
+            return MainMenuResult::NoSelection {
+                selected: MainMenuSelection::Quit,
+            };
+        }
+        if ctx.action_pressed(MenuAction::Up) {
+            let mut newselection;
+            match selection {
+                MainMenuSelection::NewGame => newselection = MainMenuSelection::Quit,
+                MainMenuSelection::LoadGame => newselection = MainMenuSelection::NewGame,
+                MainMenuSelection::Quit => newselection = MainMenuSelection::LoadGame,
             }
-            Some(key) => match key {
-                VirtualKeyCode::Escape => {
-                    return MainMenuResult::NoSelection {
-                        selected: MainMenuSelection::Quit,
-                    }
-                }
-                VirtualKeyCode::Up => {
-                    let mut newselection;
-                    match selection {
-                        MainMenuSelection::NewGame => newselection = MainMenuSelection::Quit,
-                        MainMenuSelection::LoadGame => newselection = MainMenuSelection::NewGame,
-                        MainMenuSelection::Quit => newselection = MainMenuSelection::LoadGame,
-                    }
-                    if newselection == MainMenuSelection::LoadGame && !save_exists {
-                        newselection = MainMenuSelection::NewGame;
-                    }
-                    return MainMenuResult::NoSelection {
-                        selected: newselection,
-                    };
-                }
-                VirtualKeyCode::Down => {
-                    let mut newselection;
-                    match selection {
-                        MainMenuSelection::NewGame => newselection = MainMenuSelection::LoadGame,
-                        MainMenuSelection::LoadGame => newselection = MainMenuSelection::Quit,
-                        MainMenuSelection::Quit => newselection = MainMenuSelection::NewGame,
-                    }
-                    if newselection == MainMenuSelection::LoadGame && !save_exists {
-                        newselection = MainMenuSelection::Quit;
-                    }
-                    return MainMenuResult::NoSelection {
-                        selected: newselection,
-                    };
-                }
-                VirtualKeyCode::Return => {
-                    return MainMenuResult::Selected {
-                        selected: selection,
-                    }
-                }
-                _ => {
-                    return MainMenuResult::NoSelection {
-                        selected: selection,
-                    }
-                }
-            },
+            if newselection == MainMenuSelection::LoadGame && !save_exists {
+                newselection = MainMenuSelection::NewGame;
+            }
+            return MainMenuResult::NoSelection {
+                selected: newselection,
+            };
         }
+        if ctx.action_pressed(MenuAction::Down) {
+            let mut newselection;
+            match selection {
+                MainMenuSelection::NewGame => newselection = MainMenuSelection::LoadGame,
+                MainMenuSelection::LoadGame => newselection = MainMenuSelection::Quit,
+                MainMenuSelection::Quit => newselection = MainMenuSelection::NewGame,
+            }
+            if newselection == MainMenuSelection::LoadGame && !save_exists {
+                newselection = MainMenuSelection::Quit;
+            }
+            return MainMenuResult::NoSelection {
+                selected: newselection,
+            };
+        }
+        if ctx.action_pressed(MenuAction::Select) {
+            return MainMenuResult::Selected {
+                selected: selection,
+            };
+        }
+        return MainMenuResult::NoSelection {
+            selected: selection,
+        };
     }
 
     MainMenuResult::NoSelection {
@@ -428,7 +371,7 @@ pub fn remove_item_menu(gs: &mut State, ctx: &mut Fractal) -> (ItemMenuResult, O
         .filter(|item| item.0.owner == *player_entity);
     let count = inventory.count();
 
-    let mut y = (25 - (count / 2)) as i32;
+    let y = (25 - (count / 2)) as i32;
     ctx.draw_box(
         15,
         y - 2,
@@ -447,37 +390,21 @@ pub fn remove_item_menu(gs: &mut State, ctx: &mut Fractal) -> (ItemMenuResult, O
     );
 
     let mut equippable: Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (entity, _pack, name) in (&entities, &backpack, &names)
+    for (j, (entity, _pack, name)) in (&entities, &backpack, &names)
         .join()
         .filter(|item| item.1.owner == *player_entity)
+        .enumerate()
     {
+        let y = y + j as i32;
         ctx.set(17, y, color::WHITE, color::BLACK, to_cp437('('));
-        ctx.set(18, y, color::YELLOW, color::BLACK, 97 + j as u8);
+        ctx.set(18, y, color::YELLOW, color::BLACK, 97 + j as u16);
         ctx.set(19, y, color::WHITE, color::BLACK, to_cp437(')'));
 
         ctx.print(21, y, &name.name.to_string());
         equippable.push(entity);
-        y += 1;
-        j += 1;
     }
 
-    match ctx.key {
-        None => (ItemMenuResult::NoResponse, None),
-        Some(key) => match key {
-            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
-            _ => {
-                let selection = letter_to_option(key);
-                if selection > -1 && selection < count as i32 {
-                    return (
-                        ItemMenuResult::Selected,
-                        Some(equippable[selection as usize]),
-                    );
-                }
-                (ItemMenuResult::NoResponse, None)
-            }
-        },
-    }
+    item_menu_input(ctx, &equippable)
 }
 
 pub fn game_over(ctx: &mut Fractal) -> GameOverResult {
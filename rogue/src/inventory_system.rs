@@ -1,6 +1,7 @@
 use crate::components::*;
 use crate::gamelog::GameLog;
 use crate::map::Map;
+use crate::particle_system::{blast_tile, sparks, ParticleRequests};
 use fractal::fieldofview::field_of_view;
 use specs::prelude::*;
 
@@ -65,6 +66,7 @@ impl<'a> System<'a> for ItemUseSystem {
         ReadStorage<'a, Equippable>,
         WriteStorage<'a, Equipped>,
         WriteStorage<'a, InBackpack>,
+        WriteExpect<'a, ParticleRequests>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -85,6 +87,7 @@ impl<'a> System<'a> for ItemUseSystem {
             equippable,
             mut equipped,
             mut backpack,
+            mut particles,
         ) = data;
 
         for (entity, useitem) in (&entities, &wants_use).join() {
@@ -117,7 +120,9 @@ impl<'a> System<'a> for ItemUseSystem {
                                 for mob in map.tile_content[idx].iter() {
                                     targets.push(*mob);
                                 }
+                                particles.request(blast_tile(tile_idx.x, tile_idx.y));
                             }
+                            particles.request(sparks(target.x, target.y));
                         }
                     }
                 }
@@ -6,6 +6,7 @@ extern crate specs_derive;
 extern crate serde_derive;
 
 mod components;
+mod controls;
 mod damage_system;
 mod gamelog;
 mod gui;
@@ -14,6 +15,7 @@ mod map;
 mod map_index_system;
 mod melee_compat_system;
 mod monster_ai_system;
+mod particle_system;
 mod player;
 mod random_table;
 mod rect;
@@ -22,6 +24,7 @@ mod spawner;
 mod visibility_system;
 
 use crate::components::*;
+use crate::controls::Action;
 use crate::damage_system::DamageSystem;
 use crate::inventory_system::*;
 use crate::map::*;
@@ -30,14 +33,20 @@ use crate::melee_compat_system::MeleeCombatSystem;
 use crate::monster_ai_system::MonsterAI;
 use crate::player::*;
 use crate::visibility_system::VisibilitySystem;
+use fractal::builder::FractalBuilder;
+use fractal::camera::Camera;
 use fractal::console::Console;
 use fractal::fractal::main_loop;
 use fractal::fractal::Fractal;
 use fractal::geometry::Point;
+use fractal::perf::Perf;
 use fractal::random::RandomNumberGenerator;
+use fractal::viewport::ResizePolicy;
 use fractal::GameState;
 use specs::prelude::*;
 use specs::saveload::*;
+use std::cmp::Reverse;
+use std::env;
 
 #[derive(PartialEq, Copy, Clone)]
 pub enum RunState {
@@ -65,25 +74,25 @@ pub struct State {
 }
 
 impl State {
-    fn run_systems(&mut self) {
+    fn run_systems(&mut self, perf: &mut Perf) {
         let mut vis = VisibilitySystem {};
-        vis.run_now(&self.ecs);
+        perf.measure("visibility", || vis.run_now(&self.ecs));
         let mut mob = MonsterAI {};
-        mob.run_now(&self.ecs);
+        perf.measure("monster ai", || mob.run_now(&self.ecs));
         let mut mapindex = MapIndexingSystem {};
-        mapindex.run_now(&self.ecs);
+        perf.measure("map index", || mapindex.run_now(&self.ecs));
         let mut melee = MeleeCombatSystem {};
-        melee.run_now(&self.ecs);
+        perf.measure("melee", || melee.run_now(&self.ecs));
         let mut damage = DamageSystem {};
-        damage.run_now(&self.ecs);
+        perf.measure("damage", || damage.run_now(&self.ecs));
         let mut pickup = ItemCollectionSystem {};
-        pickup.run_now(&self.ecs);
+        perf.measure("pickup", || pickup.run_now(&self.ecs));
         let mut itemuse = ItemUseSystem {};
-        itemuse.run_now(&self.ecs);
+        perf.measure("item use", || itemuse.run_now(&self.ecs));
         let mut drop_items = ItemDropSystem {};
-        drop_items.run_now(&self.ecs);
+        perf.measure("item drop", || drop_items.run_now(&self.ecs));
         let mut item_remove = ItemRemoveSystem {};
-        item_remove.run_now(&self.ecs);
+        perf.measure("item remove", || item_remove.run_now(&self.ecs));
 
         self.ecs.maintain();
     }
@@ -141,8 +150,9 @@ impl State {
         let current_depth;
         {
             let mut worldmap_resource = self.ecs.write_resource::<Map>();
+            let mut rng = self.ecs.write_resource::<RandomNumberGenerator>();
             current_depth = worldmap_resource.depth;
-            *worldmap_resource = Map::new_map_rooms_and_corridors(current_depth + 1);
+            *worldmap_resource = Map::new_map_rooms_and_corridors(current_depth + 1, &mut rng);
             worldmap = worldmap_resource.clone();
         }
         // Spawn bad guys
@@ -193,7 +203,8 @@ impl State {
         let worldmap;
         {
             let mut worldmap_resource = self.ecs.write_resource::<Map>();
-            *worldmap_resource = Map::new_map_rooms_and_corridors(1);
+            let mut rng = self.ecs.write_resource::<RandomNumberGenerator>();
+            *worldmap_resource = Map::new_map_rooms_and_corridors(1, &mut rng);
             worldmap = worldmap_resource.clone();
         }
 
@@ -233,24 +244,36 @@ impl GameState for State {
             newrunstate = *runstate;
         }
 
+        if ctx.action_pressed(Action::TogglePerfOverlay) {
+            ctx.perf.toggle_overlay();
+        }
+
         ctx.cls();
 
         match newrunstate {
             RunState::MainMenu { .. } => {}
             _ => {
+                {
+                    let player_pos = *self.ecs.fetch::<Point>();
+                    self.ecs.write_resource::<Camera>().center_on(player_pos);
+                }
                 draw_map(&self.ecs, ctx);
 
                 {
                     let positions = self.ecs.read_storage::<Position>();
                     let renderables = self.ecs.read_storage::<Renderable>();
                     let map = self.ecs.fetch::<Map>();
+                    let camera = self.ecs.fetch::<Camera>();
 
                     let mut data = (&positions, &renderables).join().collect::<Vec<_>>();
-                    data.sort_by(|&a, &b| b.1.render_order.cmp(&a.1.render_order));
+                    data.sort_by_key(|&(_, render)| Reverse(render.render_order));
                     for (pos, render) in data.iter() {
                         let idx = map.xy_idx(pos.x, pos.y);
-                        if map.visible_tiles[idx] {
-                            ctx.set(pos.x, pos.y, render.fg, render.bg, render.glyph)
+                        if !map.visible_tiles[idx] {
+                            continue;
+                        }
+                        if let Some(screen) = camera.world_to_screen(Point::new(pos.x, pos.y)) {
+                            ctx.set(screen.x, screen.y, render.fg, render.bg, render.glyph)
                         }
                     }
 
@@ -261,7 +284,7 @@ impl GameState for State {
 
         match newrunstate {
             RunState::PreRun => {
-                self.run_systems();
+                self.run_systems(&mut ctx.perf);
                 self.ecs.maintain();
                 newrunstate = RunState::AwaitingInput;
             }
@@ -269,12 +292,12 @@ impl GameState for State {
                 newrunstate = player_input(self, ctx);
             }
             RunState::PlayerTurn => {
-                self.run_systems();
+                self.run_systems(&mut ctx.perf);
                 self.ecs.maintain();
                 newrunstate = RunState::MonsterTurn;
             }
             RunState::MonsterTurn => {
-                self.run_systems();
+                self.run_systems(&mut ctx.perf);
                 self.ecs.maintain();
                 newrunstate = RunState::AwaitingInput;
             }
@@ -362,7 +385,7 @@ impl GameState for State {
                             saveload_system::delete_save();
                         }
                         gui::MainMenuSelection::Quit => {
-                            ::std::process::exit(0);
+                            ctx.quit();
                         }
                     },
                 }
@@ -413,13 +436,21 @@ impl GameState for State {
             let mut runwriter = self.ecs.write_resource::<RunState>();
             *runwriter = newrunstate;
         }
+        particle_system::spawn_particles(&self.ecs, ctx);
         damage_system::delete_the_dead(&mut self.ecs);
     }
 }
 
 fn main() {
-    let mut context = Fractal::init_simple8x8(80, 50, "Hello Rust World", "resources");
-    context.with_post_scanlines(true);
+    let mut context = FractalBuilder::simple8x8(80, 50)
+        .with_title("Hello Rust World")
+        .with_post_scanlines(true)
+        .with_fps_cap(60.0)
+        .with_resize_policy(ResizePolicy::Letterbox)
+        .with_hot_reload(cfg!(debug_assertions))
+        .build()
+        .expect("Unable to start the game");
+    context.with_input_map(controls::load_controls());
     let mut gs = State { ecs: World::new() };
     gs.ecs.register::<Position>();
     gs.ecs.register::<Renderable>();
@@ -452,22 +483,45 @@ fn main() {
 
     gs.ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
 
-    let map: Map = Map::new_map_rooms_and_corridors(1);
+    // --record <file> saves the run's input for a bug report, --replay <file> plays it back
+    let args: Vec<String> = env::args().collect();
+    let mut rng = match (args.get(1).map(|a| a.as_str()), args.get(2)) {
+        (Some("--record"), Some(path)) => {
+            let seed = RandomNumberGenerator::new().rand::<u64>();
+            context
+                .record_input(path, seed)
+                .expect("Unable to create the replay file");
+            RandomNumberGenerator::seeded(seed)
+        }
+        (Some("--replay"), Some(path)) => {
+            let seed = context
+                .play_input(path)
+                .expect("Unable to load the replay file");
+            RandomNumberGenerator::seeded(seed)
+        }
+        _ => RandomNumberGenerator::new(),
+    };
+
+    let map: Map = Map::new_map_rooms_and_corridors(1, &mut rng);
     let (player_x, player_y) = map.rooms[0].center();
 
     let player_entity = spawner::player(&mut gs.ecs, player_x, player_y);
 
-    gs.ecs.insert(RandomNumberGenerator::new());
+    gs.ecs.insert(rng);
     for room in map.rooms.iter().skip(1) {
         spawner::spawn_room(&mut gs.ecs, room, 1);
     }
 
     gs.ecs.insert(map);
+    gs.ecs.insert(
+        Camera::new(VIEWWIDTH, VIEWHEIGHT).with_world_size(MAPWIDTH as i32, MAPHEIGHT as i32),
+    );
     gs.ecs.insert(Point::new(player_x, player_y));
     gs.ecs.insert(player_entity);
     gs.ecs.insert(RunState::MainMenu {
         menu_selection: gui::MainMenuSelection::NewGame,
     });
+    gs.ecs.insert(particle_system::ParticleRequests::new());
     gs.ecs.insert(gamelog::GameLog {
         entries: vec!["Welcome to Rusty Roguelike".to_string()],
     });
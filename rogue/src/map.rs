@@ -1,8 +1,8 @@
 use crate::rect::Rect;
+use fractal::camera::Camera;
 use fractal::codepage437::to_cp437;
 use fractal::color;
 use fractal::color::RGB;
-use fractal::console::Console;
 use fractal::fractal::Fractal;
 use fractal::geometry::DistanceAlg::Pythagoras;
 use fractal::geometry::Point;
@@ -12,10 +12,14 @@ use fractal::random::RandomNumberGenerator;
 use specs::prelude::*;
 use std::cmp::{max, min};
 
-pub const MAPWIDTH: usize = 80;
-pub const MAPHEIGHT: usize = 43;
+pub const MAPWIDTH: usize = 120;
+pub const MAPHEIGHT: usize = 70;
 pub const MAPCOUNT: usize = MAPHEIGHT * MAPWIDTH;
 
+/// Size of the part of the map shown on screen, above the log
+pub const VIEWWIDTH: i32 = 80;
+pub const VIEWHEIGHT: i32 = 43;
+
 #[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub enum TileType {
     Wall,
@@ -57,7 +61,7 @@ impl Map {
         for x in min(x1, x2)..=max(x1, x2) {
             let idx = self.xy_idx(x, y);
             if idx > 0 && idx < self.width as usize * self.height as usize {
-                self.tiles[idx as usize] = TileType::Floor;
+                self.tiles[idx] = TileType::Floor;
             }
         }
     }
@@ -66,7 +70,7 @@ impl Map {
         for y in min(y1, y2)..=max(y1, y2) {
             let idx = self.xy_idx(x, y);
             if idx > 0 && idx < self.width as usize * self.height as usize {
-                self.tiles[idx as usize] = TileType::Floor;
+                self.tiles[idx] = TileType::Floor;
             }
         }
     }
@@ -93,7 +97,7 @@ impl Map {
 
     /// Makes a new map using the algorithm from http://rogueliketutorials.com/tutorials/tcod/part-3/
     /// This gives a handful of random rooms and corridors joining them together.
-    pub fn new_map_rooms_and_corridors(new_depth: i32) -> Map {
+    pub fn new_map_rooms_and_corridors(new_depth: i32, rng: &mut RandomNumberGenerator) -> Map {
         let mut map = Map {
             tiles: vec![TileType::Wall; MAPCOUNT],
             rooms: Vec::new(),
@@ -110,8 +114,6 @@ impl Map {
         const MIN_SIZE: i32 = 6;
         const MAX_SIZE: i32 = 10;
 
-        let mut rng = RandomNumberGenerator::new();
-
         for _ in 0..MAX_ROOMS {
             let w = rng.range(MIN_SIZE, MAX_SIZE);
             let h = rng.range(MIN_SIZE, MAX_SIZE);
@@ -217,12 +219,13 @@ impl Algorithm2D for Map {
 
 pub fn draw_map(ecs: &World, ctx: &mut Fractal) {
     let map = ecs.fetch::<Map>();
+    let camera = ecs.fetch::<Camera>();
 
-    let mut y = 0;
-    let mut x = 0;
-    for (idx, tile) in map.tiles.iter().enumerate() {
-        // Render a tile depending upon the tile type
+    camera.for_each(|world, screen| {
+        let idx = map.xy_idx(world.x, world.y);
+        let tile = map.tiles[idx];
 
+        // Render a tile depending upon the tile type
         if map.revealed_tiles[idx] {
             let glyph;
             let mut fg;
@@ -232,7 +235,7 @@ pub fn draw_map(ecs: &World, ctx: &mut Fractal) {
                     fg = RGB::from_u8(0, 128, 128);
                 }
                 TileType::Wall => {
-                    glyph = wall_glyph(&*map, x, y);
+                    glyph = wall_glyph(&map, world.x, world.y);
                     fg = color::GREEN;
                 }
                 TileType::DownStairs => {
@@ -243,20 +246,13 @@ pub fn draw_map(ecs: &World, ctx: &mut Fractal) {
             if !map.visible_tiles[idx] {
                 fg = fg.to_greyscale()
             }
-            ctx.set(x, y, fg, color::BLACK, glyph);
-        }
-
-        // Move the coordinates
-        x += 1;
-        if x > MAPWIDTH as i32 - 1 {
-            x = 0;
-            y += 1;
+            ctx.set(screen.x, screen.y, fg, color::BLACK, glyph);
         }
-    }
+    });
 }
 
-fn wall_glyph(map: &Map, x: i32, y: i32) -> u8 {
-    if x < 1 || x > map.width - 2 || y < 1 || y > map.height - 2 as i32 {
+fn wall_glyph(map: &Map, x: i32, y: i32) -> u16 {
+    if x < 1 || x > map.width - 2 || y < 1 || y > map.height - 2 {
         return 35;
     }
     let mut mask: u8 = 0;
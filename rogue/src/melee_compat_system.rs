@@ -1,5 +1,6 @@
 use crate::components::*;
 use crate::gamelog::GameLog;
+use crate::particle_system::{hit_flash, ParticleRequests};
 use specs::prelude::*;
 
 pub struct MeleeCombatSystem {}
@@ -16,6 +17,8 @@ impl<'a> System<'a> for MeleeCombatSystem {
         ReadStorage<'a, MeleePowerBonus>,
         ReadStorage<'a, DefenseBonus>,
         ReadStorage<'a, Equipped>,
+        ReadStorage<'a, Position>,
+        WriteExpect<'a, ParticleRequests>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -29,6 +32,8 @@ impl<'a> System<'a> for MeleeCombatSystem {
             melee_power_bonuses,
             defense_bonuses,
             equipped,
+            positions,
+            mut particles,
         ) = data;
 
         for (entity, wants_melee, name, stats) in
@@ -78,6 +83,9 @@ impl<'a> System<'a> for MeleeCombatSystem {
                         inflict_damage
                             .insert(wants_melee.target, SufferDamage { amount: damage })
                             .expect("Unable to do damage");
+                        if let Some(pos) = positions.get(wants_melee.target) {
+                            particles.request(hit_flash(pos.x, pos.y));
+                        }
                     }
                 }
             }
@@ -0,0 +1,71 @@
+use fractal::camera::Camera;
+use fractal::color;
+use fractal::fractal::Fractal;
+use fractal::geometry::Point;
+use fractal::particles::Emitter;
+use specs::prelude::*;
+use std::f32::consts::PI;
+
+/// Particle effects asked for by systems this turn, started by `spawn_particles`. Emitters
+/// are placed at map positions, and moved onto the screen when they start.
+pub struct ParticleRequests {
+    pub requests: Vec<Emitter>,
+}
+
+impl ParticleRequests {
+    pub fn new() -> ParticleRequests {
+        ParticleRequests {
+            requests: Vec::new(),
+        }
+    }
+
+    pub fn request(&mut self, emitter: Emitter) {
+        self.requests.push(emitter);
+    }
+}
+
+/// A quick orange flash over something that was hit
+pub fn hit_flash(x: i32, y: i32) -> Emitter {
+    Emitter::new(x, y)
+        .with_glyphs(&[15])
+        .with_colors(&[color::ORANGE, color::RED])
+        .with_bg(color::BLACK)
+        .with_fade(true)
+        .with_lifetime(200.0, 200.0)
+}
+
+/// Flames on one tile of a blast
+pub fn blast_tile(x: i32, y: i32) -> Emitter {
+    Emitter::new(x, y)
+        .with_glyphs(&[176, 177])
+        .with_colors(&[color::YELLOW, color::ORANGE, color::RED])
+        .with_bg(color::BLACK)
+        .with_fade(true)
+        .with_lifetime(250.0, 450.0)
+}
+
+/// Sparks thrown out from the middle of a blast
+pub fn sparks(x: i32, y: i32) -> Emitter {
+    Emitter::new(x, y)
+        .with_glyphs(&[7, 42, 249])
+        .with_colors(&[color::YELLOW, color::ORANGE])
+        .with_fade(true)
+        .with_lifetime(300.0, 600.0)
+        .with_speed(4.0, 10.0)
+        .with_direction(0.0, PI)
+        .with_burst(12)
+}
+
+/// Starts every requested effect that the camera can see on the map console. Particles stay
+/// on the screen cells they started on, even if the camera moves while they are alive.
+pub fn spawn_particles(ecs: &World, ctx: &mut Fractal) {
+    let camera = ecs.fetch::<Camera>();
+    let mut particles = ecs.write_resource::<ParticleRequests>();
+    for mut emitter in particles.requests.drain(..) {
+        if let Some(screen) = camera.world_to_screen(Point::new(emitter.x, emitter.y)) {
+            emitter.x = screen.x;
+            emitter.y = screen.y;
+            ctx.emit_particles(emitter.on_console(0));
+        }
+    }
+}
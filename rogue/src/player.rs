@@ -1,13 +1,12 @@
 use crate::components::*;
+use crate::controls::Action;
 use crate::gamelog::GameLog;
 use crate::map::*;
 use crate::RunState;
 use crate::State;
 use fractal::fractal::Fractal;
 use fractal::geometry::Point;
-use fractal::VirtualKeyCode;
 use specs::prelude::*;
-use std::cmp::{max, min};
 
 pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
     let mut positions = ecs.write_storage::<Position>();
@@ -46,8 +45,8 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
         }
 
         if !map.blocked[destination_idx] {
-            pos.x = min(79, max(0, pos.x + delta_x));
-            pos.y = min(49, max(0, pos.y + delta_y));
+            pos.x += delta_x;
+            pos.y += delta_y;
 
             viewshed.dirty = true;
             let mut ppos = ecs.write_resource::<Point>();
@@ -107,57 +106,42 @@ pub fn try_next_level(ecs: &mut World) -> bool {
 }
 
 pub fn player_input(gs: &mut State, ctx: &mut Fractal) -> RunState {
-    // Player movement
-    match ctx.key {
+    let action = Action::ALL.iter().find(|a| ctx.action_pressed(**a));
+    match action {
         None => return RunState::AwaitingInput, // Nothing happened
-        Some(key) => match key {
-            VirtualKeyCode::Left | VirtualKeyCode::Numpad4 | VirtualKeyCode::H => {
-                try_move_player(-1, 0, &mut gs.ecs)
-            }
-
-            VirtualKeyCode::Right | VirtualKeyCode::Numpad6 | VirtualKeyCode::L => {
-                try_move_player(1, 0, &mut gs.ecs)
-            }
-
-            VirtualKeyCode::Up | VirtualKeyCode::Numpad8 | VirtualKeyCode::K => {
-                try_move_player(0, -1, &mut gs.ecs)
-            }
-
-            VirtualKeyCode::Down | VirtualKeyCode::Numpad2 | VirtualKeyCode::J => {
-                try_move_player(0, 1, &mut gs.ecs)
-            }
+        Some(action) => match action {
+            Action::MoveWest => try_move_player(-1, 0, &mut gs.ecs),
+            Action::MoveEast => try_move_player(1, 0, &mut gs.ecs),
+            Action::MoveNorth => try_move_player(0, -1, &mut gs.ecs),
+            Action::MoveSouth => try_move_player(0, 1, &mut gs.ecs),
 
             // Diagonals
-            VirtualKeyCode::Numpad9 | VirtualKeyCode::U => try_move_player(1, -1, &mut gs.ecs),
-
-            VirtualKeyCode::Numpad7 | VirtualKeyCode::Y => try_move_player(-1, -1, &mut gs.ecs),
-
-            VirtualKeyCode::Numpad3 | VirtualKeyCode::N => try_move_player(1, 1, &mut gs.ecs),
-
-            VirtualKeyCode::Numpad1 | VirtualKeyCode::B => try_move_player(-1, 1, &mut gs.ecs),
+            Action::MoveNorthEast => try_move_player(1, -1, &mut gs.ecs),
+            Action::MoveNorthWest => try_move_player(-1, -1, &mut gs.ecs),
+            Action::MoveSouthEast => try_move_player(1, 1, &mut gs.ecs),
+            Action::MoveSouthWest => try_move_player(-1, 1, &mut gs.ecs),
 
             // Picking up items
-            VirtualKeyCode::G => get_item(&mut gs.ecs),
-            VirtualKeyCode::I => return RunState::ShowInventory,
-            VirtualKeyCode::D => return RunState::ShowDropItem,
+            Action::PickUp => get_item(&mut gs.ecs),
+            Action::Inventory => return RunState::ShowInventory,
+            Action::Drop => return RunState::ShowDropItem,
+            Action::Remove => return RunState::ShowRemoveItem,
 
             // Save and Quit
-            VirtualKeyCode::Escape => return RunState::SaveGame,
+            Action::SaveAndQuit => return RunState::SaveGame,
 
             // Level changes
-            VirtualKeyCode::Period => {
+            Action::Descend => {
                 if try_next_level(&mut gs.ecs) {
                     return RunState::NextLevel;
                 }
             }
 
             // Skip Turn
-            VirtualKeyCode::Numpad5 => return skip_turn(&mut gs.ecs),
-            VirtualKeyCode::Space => return skip_turn(&mut gs.ecs),
+            Action::SkipTurn => return skip_turn(&mut gs.ecs),
 
-            VirtualKeyCode::R => return RunState::ShowRemoveItem,
-            
-            _ => return RunState::AwaitingInput,
+            // Handled by State::tick, and doesn't take a turn
+            Action::TogglePerfOverlay => return RunState::AwaitingInput,
         },
     }
     RunState::PlayerTurn
@@ -7,6 +7,7 @@ use fractal::color;
 use fractal::random::RandomNumberGenerator;
 use specs::prelude::*;
 use specs::saveload::*;
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 
 /// Spawns the player and returns his/her entity object.
@@ -60,8 +61,8 @@ pub fn spawn_room(ecs: &mut World, room: &Rect, map_depth: i32) {
                 let x = (room.x1 + rng.roll_dice(1, i32::abs(room.x2 - room.x1))) as usize;
                 let y = (room.y1 + rng.roll_dice(1, i32::abs(room.y2 - room.y1))) as usize;
                 let idx = (y * MAPWIDTH) + x;
-                if !spawn_points.contains_key(&idx) {
-                    spawn_points.insert(idx, spawn_table.roll(&mut rng));
+                if let Entry::Vacant(entry) = spawn_points.entry(idx) {
+                    entry.insert(spawn_table.roll(&mut rng));
                     added = true;
                 } else {
                     tries += 1;
@@ -99,7 +100,7 @@ fn goblin(ecs: &mut World, x: i32, y: i32) {
     monster(ecs, x, y, to_cp437('g'), "Goblin");
 }
 
-fn monster<S: ToString>(ecs: &mut World, x: i32, y: i32, glyph: u8, name: S) {
+fn monster<S: ToString>(ecs: &mut World, x: i32, y: i32, glyph: u16, name: S) {
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {